@@ -1,19 +1,24 @@
 use crate::cal::calmar_trait::CalendarDataType;
 use crate::cal::{calmar_error::CalmarError, event::Event};
+use crate::cli::{history::track, hooks};
+use crate::CONFIG;
 use core::fmt;
 use serde_derive::{Deserialize, Serialize};
-use std::{
-    fmt::{Display, Formatter},
-    io::Write,
-};
+use std::fmt::{Display, Formatter};
 
-use super::deadline::Deadline;
+use super::{
+    deadline::Deadline,
+    id::find_by_key,
+    migration::{self, FileKind, SCHEMA_VERSION},
+    storage,
+};
 
 /// Holds its own name and a vector of `Event` structs.
 /// # Use
 /// An empty `Calendar` may be created with `Calendar::new("foo")`
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Calendar {
+    version: u32,
     name: String,
     events: Vec<Event>,
     deadlines: Vec<Deadline>,
@@ -37,12 +42,22 @@ impl Display for Calendar {
 impl Calendar {
     pub fn new(name: &str) -> Self {
         Calendar {
+            version: SCHEMA_VERSION,
             name: name.to_string(),
             events: vec![],
             deadlines: vec![],
         }
     }
 
+    /// Returns `Calendar` struct parsed from the file under `path`.
+    pub fn get(path: &str) -> Result<Self, CalmarError> {
+        let content = storage::read(path)?;
+        migration::load(path, &content, FileKind::Calendar)
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
     pub fn events(&self) -> &Vec<Event> {
         &self.events
     }
@@ -56,28 +71,40 @@ impl Calendar {
         &mut self.deadlines
     }
 
+    /// Indices of the events `key` refers to by name or ID, see `id::find_by_key`.
+    pub fn find_events(&self, key: &str) -> Vec<usize> {
+        find_by_key(self.events.iter().map(|e| (e.name(), e.id())), key)
+    }
+
     pub fn set_name(&mut self, name: String) {
         self.name = name
     }
 
-    pub fn save(&self, path: &String) -> Result<(), CalmarError> {
-        let mut calendar_file = match std::fs::OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(&path)
-        {
-            Ok(file) => file,
-            Err(e) => return Err(CalmarError::ReadFile { e }),
-        };
+    /// Saves the calendar to the file under `path`.
+    /// Fails with `CalmarError::ChangedOnDisk` if the file was changed by another program since it was read.
+    pub fn save(&self, path: &str) -> Result<(), CalmarError> {
+        self.write(path, true)
+    }
+
+    /// Saves the calendar to the file under `path`, even if it was changed since it was read.
+    pub fn overwrite(&self, path: &str) -> Result<(), CalmarError> {
+        self.write(path, false)
+    }
 
+    fn write(&self, path: &str, check: bool) -> Result<(), CalmarError> {
+        track(path);
         let calendar_json = match serde_json::to_string_pretty(&self) {
             Ok(result) => result,
             Err(e) => return Err(CalmarError::ParseJSON { e }),
         };
-
-        if let Err(e) = write!(calendar_file, "{}", calendar_json) {
-            return Err(CalmarError::WriteFile { e });
-        }
+        let before = match CONFIG.hooks.is_empty() {
+            true => None,
+            false => std::fs::read_to_string(path).ok(),
+        };
+        hooks::before_save(path, before.as_deref(), &calendar_json)?;
+        migration::keep_original(path)?;
+        storage::write_atomic(path, &calendar_json, check)?;
+        hooks::after_save(path, before.as_deref(), &calendar_json);
         Ok(())
     }
 
@@ -1,13 +1,22 @@
 use crate::{
-    cal::{calendar::Calendar, calendar_ref::CalendarReference, calmar_error::CalmarError},
-    cli::validator::get_home_dir,
+    cal::{
+        calendar::Calendar,
+        calendar_ref::CalendarReference,
+        calmar_error::CalmarError,
+        id::find_by_key,
+        migration::{self, FileKind, SCHEMA_VERSION},
+        storage,
+    },
+    cli::{history::track, hooks, validator::get_home_dir},
+    CONFIG,
 };
 use serde_derive::{Deserialize, Serialize};
-use std::{fmt::Display, fs::read_to_string, io::Write};
+use std::fmt::Display;
 
 /// Holds a vector of `CalendarReference` structs.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CalendarIndex {
+    version: u32,
     calendars: Vec<CalendarReference>,
 }
 
@@ -29,19 +38,16 @@ impl CalendarIndex {
     pub fn get() -> Result<Self, CalmarError> {
         let index_file_path = get_home_dir().join(".config/calmar/index.json");
 
-        let content = match read_to_string(&index_file_path) {
-            Ok(result) => result,
-            Err(e) => return Err(CalmarError::ReadFile { e }),
-        };
-
-        match serde_json::from_str(&content) {
-            Ok(result) => Ok(result),
-            Err(e) => Err(CalmarError::ParseJSON { e }),
-        }
+        let index_file_path = index_file_path.to_string_lossy();
+        let content = storage::read(&index_file_path)?;
+        migration::load(&index_file_path, &content, FileKind::Index)
     }
 
     pub fn new() -> Self {
-        CalendarIndex { calendars: vec![] }
+        CalendarIndex {
+            version: SCHEMA_VERSION,
+            calendars: vec![],
+        }
     }
 
     // Getters
@@ -52,8 +58,9 @@ impl CalendarIndex {
         &mut self.calendars
     }
 
-    pub fn num_named(&self, name: &String) -> usize {
-        self.calendars.iter().filter(|r| r.name() == *name).count()
+    /// Indices of the references `key` refers to by name or ID, see `id::find_by_key`.
+    pub fn find(&self, key: &str) -> Vec<usize> {
+        find_by_key(self.calendars.iter().map(|r| (r.name(), r.id())), key)
     }
 
     /// Returns `Calendar` struct parsed from the file pointed at by a `CalendarReference`
@@ -66,14 +73,14 @@ impl CalendarIndex {
             _ => return Err(CalmarError::ActiveCalendarCount { e: num }),
         };
 
-        let current_calendar_content = match read_to_string(&current_calendar.path()) {
-            Ok(content) => content,
-            Err(e) => return Err(CalmarError::ReadFile { e }),
-        };
+        Calendar::get(&current_calendar.path())
+    }
 
-        match serde_json::from_str(&current_calendar_content) {
-            Ok(result) => Ok(result),
-            Err(e) => Err(CalmarError::ParseJSON { e }),
+    /// Returns the `CalendarReference` named `key` or with an ID starting with it, if there is exactly one.
+    pub fn reference_named(&self, key: &str) -> Option<&CalendarReference> {
+        match self.find(key)[..] {
+            [i] => Some(&self.calendars[i]),
+            _ => None,
         }
     }
 
@@ -94,29 +101,39 @@ impl CalendarIndex {
         self.calendars.iter().filter(|c| c.active()).count()
     }
 
+    /// Saves the index to `$HOME/.config/calmar/index.json`.
+    /// Fails with `CalmarError::ChangedOnDisk` if the file was changed by another program since it was read.
     pub fn save(&self) -> Result<(), CalmarError> {
+        self.write(true)
+    }
+
+    /// Saves the index, even if the file was changed since it was read.
+    pub fn overwrite(&self) -> Result<(), CalmarError> {
+        self.write(false)
+    }
+
+    fn write(&self, check: bool) -> Result<(), CalmarError> {
         let index_file_path = get_home_dir().join(".config/calmar/index.json");
+        let index_file_path = index_file_path.to_string_lossy();
+        track(&index_file_path);
 
-        let mut index_file = match std::fs::OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(index_file_path)
-        {
-            Ok(file) => file,
-            Err(e) => return Err(CalmarError::ReadFile { e }),
-        };
         let calendar_index_json: String = match serde_json::ser::to_string_pretty(&self) {
             Ok(result) => result,
             Err(e) => return Err(CalmarError::ToJSON { e }),
         };
-
-        match write!(index_file, "{}", calendar_index_json) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(CalmarError::WriteFile { e }),
-        }
+        let before = match CONFIG.hooks.is_empty() {
+            true => None,
+            false => std::fs::read_to_string(&*index_file_path).ok(),
+        };
+        hooks::before_save(&index_file_path, before.as_deref(), &calendar_index_json)?;
+        migration::keep_original(&index_file_path)?;
+        storage::write_atomic(&index_file_path, &calendar_index_json, check)?;
+        hooks::after_save(&index_file_path, before.as_deref(), &calendar_index_json);
+        Ok(())
     }
 
-    pub fn set_active(&mut self, name: String) {
+    /// Sets the calendar with the given `id` as the only active one.
+    pub fn set_active(&mut self, id: &str) {
         // Set the currently active calendar as not active
         // Set the desired calendar as active
 
@@ -124,7 +141,7 @@ impl CalendarIndex {
             if r.active() {
                 r.set_inactive()
             }
-            if r.name() == name {
+            if r.id() == id {
                 r.set_active()
             }
         }
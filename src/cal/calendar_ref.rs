@@ -1,6 +1,13 @@
-use crate::cal::{calendar::Calendar, calmar_error::CalmarError};
+use crate::cal::{
+    calendar::Calendar,
+    calmar_error::CalmarError,
+    id::{new_id, short_id},
+    storage,
+};
+use crate::cli::history::track;
+use chrono_tz::Tz;
 use serde_derive::{Deserialize, Serialize};
-use std::{fmt::Display, io::Write};
+use std::fmt::Display;
 use struct_field_names_as_array::FieldNamesAsArray;
 
 /// Holds a "pointer" to a file containing a `Calendar` struct.
@@ -8,29 +15,51 @@ use struct_field_names_as_array::FieldNamesAsArray;
 /// `name`: name of the calendar in file under `path`
 /// `path`: path to the file containing a `Calendar` struct
 /// `active`: determines if the `Calendar` under `path` is currently selected.
+/// `timezone`: time zone of events added to the calendar, floating local time if `None`.
 #[derive(Clone, Debug, Serialize, Deserialize, FieldNamesAsArray)]
 pub struct CalendarReference {
+    #[serde(default = "new_id")]
+    #[field_names_as_array(skip)]
+    id: String,
     name: String,
     path: String,
     active: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timezone: Option<Tz>,
 }
 
 impl Display for CalendarReference {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Reference name: {} | Path: {} | Active: {}",
-            self.name, self.path, self.active
+            "Reference name: {} | ID: {} | Path: {} | Active: {} | Time zone: {}",
+            self.name,
+            short_id(&self.id),
+            self.path,
+            self.active,
+            match self.timezone {
+                Some(tz) => tz.name(),
+                None => "local",
+            }
         )
     }
 }
 
 impl CalendarReference {
     pub fn new(name: String, path: String, active: bool) -> Self {
-        CalendarReference { name, path, active }
+        CalendarReference {
+            id: new_id(),
+            name,
+            path,
+            active,
+            timezone: None,
+        }
     }
 
     // Getters
+    pub fn id(&self) -> &str {
+        &self.id
+    }
     pub fn name(&self) -> String {
         self.name.clone()
     }
@@ -40,6 +69,9 @@ impl CalendarReference {
     pub fn active(&self) -> bool {
         self.active
     }
+    pub fn timezone(&self) -> Option<Tz> {
+        self.timezone
+    }
 
     // Setters
     pub fn set_name(&mut self, name: String) {
@@ -58,22 +90,19 @@ impl CalendarReference {
         self.active = false
     }
 
+    pub fn set_timezone(&mut self, timezone: Option<Tz>) {
+        self.timezone = timezone
+    }
+
     //Other
     pub fn create_file(&self) -> Result<(), CalmarError> {
-        let mut calendar_file = match std::fs::File::create(&self.path) {
-            Ok(file) => file,
-            Err(e) => return Err(CalmarError::CreateFile { e }),
-        };
-
+        track(&self.path);
         let calendar_json: String =
             match serde_json::to_string_pretty(&Calendar::new(self.name.as_str())) {
                 Ok(result) => result,
                 Err(e) => return Err(CalmarError::ToJSON { e }),
             };
 
-        if let Err(e) = write!(calendar_file, "{}", calendar_json) {
-            return Err(CalmarError::WriteFile { e });
-        }
-        Ok(())
+        storage::write_atomic(&self.path, &calendar_json, false)
     }
 }
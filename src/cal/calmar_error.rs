@@ -7,4 +7,10 @@ pub enum CalmarError {
     ToJSON { e: serde_json::Error },
     ActiveCalendarCount { e: usize },
     CreateDir { e: std::io::Error },
+    ParseICS { e: String },
+    LockFile { e: std::io::Error },
+    ChangedOnDisk { path: String },
+    NewerSchema { version: u32 },
+    UnknownSchema { e: String },
+    Hook { e: String },
 }
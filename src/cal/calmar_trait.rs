@@ -1,4 +1,5 @@
 pub trait CalendarDataType {
     fn name(&self) -> String;
     fn priority(&self) -> u8;
+    fn id(&self) -> &str;
 }
@@ -1,38 +1,88 @@
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Duration, Local};
 use serde_derive::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result};
 
-use super::calmar_trait::CalendarDataType;
+use crate::cli::util::{date_fmt, time_fmt};
+
+use super::{
+    calmar_trait::CalendarDataType,
+    details::Details,
+    id::{new_id, short_id},
+};
+
+/// How far work towards a deadline has come.
+/// Done and dropped deadlines are finished and hidden from most listings.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeadlineStatus {
+    #[default]
+    Open,
+    InProgress,
+    Done,
+    Dropped,
+}
+
+impl Display for DeadlineStatus {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DeadlineStatus::Open => "open",
+                DeadlineStatus::InProgress => "in progress",
+                DeadlineStatus::Done => "done",
+                DeadlineStatus::Dropped => "dropped",
+            }
+        )
+    }
+}
 
 #[serde_with::serde_as]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Deadline {
+    #[serde(default = "new_id")]
+    id: String,
     name: String,
     date: DateTime<Local>,
     priority: u8,
+    #[serde(default)]
+    status: DeadlineStatus,
+    /// Percentage of the work done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    progress: Option<u8>,
+    /// When the deadline was marked done or dropped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed: Option<DateTime<Local>>,
+    /// Date the deadline had before it was first postponed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    original_date: Option<DateTime<Local>>,
+    /// Number of times the deadline was postponed
+    #[serde(default, skip_serializing_if = "is_zero")]
+    postponements: u32,
+    #[serde(flatten)]
+    details: Details,
+    /// UID of the iCalendar component this deadline was imported from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uid: Option<String>,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
 }
 
 impl Display for Deadline {
     fn fmt(&self, f: &mut Formatter) -> Result {
         write!(
             f,
-            // name, date, time, priority, days left
-            "{}\t{}\t{}\t{}\t{}",
+            // name, ID, date, time, priority, days left, status
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
             self.name,
-            self.date.date_naive(),
-            self.date.time(),
+            short_id(&self.id),
+            date_fmt(self.date.date_naive()),
+            time_fmt(self.date.time()),
             self.priority,
-            {
-                let days = (self.date - Local::now()).num_days();
-                if days > 0 {
-                    format!("In {days} day(s)")
-                } else if days < 0 {
-                    format!("{} day(s) ago", days.abs())
-                } else {
-                    "Today".to_string()
-                }
-            }
-            .as_str()
+            self.due(),
+            self.status_fmt()
         )
     }
 }
@@ -44,17 +94,139 @@ impl CalendarDataType for Deadline {
     fn priority(&self) -> u8 {
         self.priority
     }
+    fn id(&self) -> &str {
+        &self.id
+    }
 }
 
 impl Deadline {
     pub fn new(name: String, date: DateTime<Local>, priority: u8) -> Self {
         Deadline {
+            id: new_id(),
             name,
             date,
             priority,
+            status: DeadlineStatus::Open,
+            progress: None,
+            completed: None,
+            original_date: None,
+            postponements: 0,
+            details: Details::default(),
+            uid: None,
         }
     }
     pub fn date(&self) -> DateTime<Local> {
         self.date
     }
+    /// When the deadline is due relative to today, such as "In 3 day(s)".
+    pub fn due(&self) -> String {
+        let days = (self.date - Local::now()).num_days();
+        if days > 0 {
+            format!("In {days} day(s)")
+        } else if days < 0 {
+            format!("{} day(s) ago", days.abs())
+        } else {
+            "Today".to_string()
+        }
+    }
+    pub fn original_date(&self) -> Option<DateTime<Local>> {
+        self.original_date
+    }
+    pub fn postponements(&self) -> u32 {
+        self.postponements
+    }
+    /// Moves the deadline `by` later, remembering the date it had at first.
+    pub fn postpone(&mut self, by: Duration) {
+        self.original_date.get_or_insert(self.date);
+        self.date += by;
+        self.postponements += 1;
+    }
+    pub fn status(&self) -> DeadlineStatus {
+        self.status
+    }
+    pub fn progress(&self) -> Option<u8> {
+        self.progress
+    }
+    pub fn completed(&self) -> Option<DateTime<Local>> {
+        self.completed
+    }
+    /// Whether the deadline is done or dropped.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status, DeadlineStatus::Done | DeadlineStatus::Dropped)
+    }
+    /// The status along with the progress or the completion date, such as "in progress (40%)".
+    pub fn status_fmt(&self) -> String {
+        match (self.completed, self.progress) {
+            (Some(completed), _) => {
+                format!("{} on {}", self.status, date_fmt(completed.date_naive()))
+            }
+            (None, Some(progress)) => format!("{} ({progress}%)", self.status),
+            (None, None) => self.status.to_string(),
+        }
+    }
+    /// Marks the deadline as done or dropped at `time`.
+    pub fn finish(&mut self, status: DeadlineStatus, time: DateTime<Local>) {
+        self.status = status;
+        self.completed = Some(time);
+        if status == DeadlineStatus::Done {
+            self.progress = Some(100)
+        }
+    }
+    /// Status of an unfinished deadline with the current progress.
+    fn unfinished_status(&self) -> DeadlineStatus {
+        match self.progress {
+            Some(p) if p > 0 => DeadlineStatus::InProgress,
+            _ => DeadlineStatus::Open,
+        }
+    }
+    /// Marks a finished deadline as open again, or in progress if some of the work was done.
+    pub fn reopen(&mut self) {
+        self.completed = None;
+        if self.progress == Some(100) {
+            self.progress = None
+        }
+        self.status = self.unfinished_status()
+    }
+    /// Sets the percentage of the work done. An unfinished deadline with some progress is in progress.
+    pub fn set_progress(&mut self, progress: Option<u8>) {
+        self.progress = progress;
+        if !self.is_finished() {
+            self.status = self.unfinished_status()
+        }
+    }
+    /// Description, location, URL and tags of the deadline.
+    pub fn details(&self) -> &Details {
+        &self.details
+    }
+    pub fn details_mut(&mut self) -> &mut Details {
+        &mut self.details
+    }
+    pub fn uid(&self) -> Option<String> {
+        self.uid.clone()
+    }
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string()
+    }
+    pub fn set_date(&mut self, date: DateTime<Local>) {
+        self.date = date
+    }
+    pub fn set_priority(&mut self, p: u8) {
+        self.priority = p
+    }
+    pub fn set_uid(&mut self, uid: Option<String>) {
+        self.uid = uid
+    }
+    /// Takes over the name, date, priority and details of an iCalendar import of this deadline.
+    /// Status, progress and postponements are tracked in calmar,
+    /// so an import can finish an unfinished deadline but not change them otherwise.
+    pub fn update_from(&mut self, imported: Deadline) {
+        if imported.is_finished() && !self.is_finished() {
+            self.finish(imported.status, imported.completed.unwrap_or_else(Local::now))
+        }
+        self.name = imported.name;
+        self.date = imported.date;
+        self.priority = imported.priority;
+        self.uid = imported.uid;
+        self.details.update_from(&imported.details);
+    }
 }
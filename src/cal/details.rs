@@ -0,0 +1,90 @@
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Optional information about an event or a deadline, stored alongside its other fields.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct Details {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    tags: BTreeSet<String>,
+    /// Minutes before the start of an event or the due date of a deadline that `watch` reminds of it at
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    reminders: BTreeSet<u32>,
+}
+
+/// `None` for an empty or blank `s`, `s` without surrounding whitespace otherwise.
+fn non_empty(s: &str) -> Option<String> {
+    match s.trim() {
+        "" => None,
+        trimmed => Some(trimmed.to_string()),
+    }
+}
+
+/// Tags in a comma separated list such as "exam, math".
+pub fn parse_tags(s: &str) -> BTreeSet<String> {
+    s.split(',').filter_map(non_empty).collect()
+}
+
+impl Details {
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+    pub fn tags(&self) -> &BTreeSet<String> {
+        &self.tags
+    }
+    /// Tags as a comma separated list, the way they are entered.
+    pub fn tags_fmt(&self) -> String {
+        self.tags
+            .iter()
+            .cloned()
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+    pub fn reminders(&self) -> &BTreeSet<u32> {
+        &self.reminders
+    }
+
+    // Blank values clear a field
+    pub fn set_description(&mut self, description: &str) {
+        self.description = non_empty(description)
+    }
+    pub fn set_location(&mut self, location: &str) {
+        self.location = non_empty(location)
+    }
+    pub fn set_url(&mut self, url: &str) {
+        self.url = non_empty(url)
+    }
+    pub fn set_tags(&mut self, tags: BTreeSet<String>) {
+        self.tags = tags
+    }
+    pub fn set_reminders(&mut self, reminders: BTreeSet<u32>) {
+        self.reminders = reminders
+    }
+
+    /// Takes over the fields `imported` has a value for, keeping the others and the reminders.
+    pub fn update_from(&mut self, imported: &Details) {
+        if imported.description.is_some() {
+            self.description = imported.description.clone()
+        }
+        if imported.location.is_some() {
+            self.location = imported.location.clone()
+        }
+        if imported.url.is_some() {
+            self.url = imported.url.clone()
+        }
+        if !imported.tags.is_empty() {
+            self.tags = imported.tags.clone()
+        }
+    }
+}
@@ -1,30 +1,106 @@
-use chrono::{DateTime, Duration, Local};
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate};
+use chrono_tz::Tz;
 use serde_derive::{Deserialize, Serialize};
-use serde_with::serde_as;
 use std::fmt::{self, Display, Formatter};
 use struct_field_names_as_array::FieldNamesAsArray;
 
-use super::calmar_trait::CalendarDataType;
+use super::{
+    calmar_trait::CalendarDataType,
+    details::Details,
+    id::{new_id, short_id},
+    recurrence::Recurrence,
+    timezone::from_wall_clock,
+};
 
-#[serde_with::serde_as]
+/// An event in a calendar.
+/// Events with a `timezone` happen at a fixed moment and repeat on the wall clock of that zone.
+/// Events without one are floating: they follow the local wall clock,
+/// wherever the computer running calmar is.
 #[derive(Debug, PartialEq, Eq, FieldNamesAsArray, Serialize, Deserialize, Clone)]
+#[serde(from = "EventData")]
 pub struct Event {
+    #[field_names_as_array(skip)]
+    id: String,
     name: String,
     start: DateTime<Local>,
     end: DateTime<Local>,
-    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
-    repeat: Duration,
+    repeat: Recurrence,
+    /// Last date a recurring event may start on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[field_names_as_array(skip)]
+    repeat_until: Option<NaiveDate>,
+    /// Number of occurrences of a recurring event, including exceptions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[field_names_as_array(skip)]
+    repeat_count: Option<u32>,
     priority: u8,
     difficulty: u8,
     exceptions: Vec<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timezone: Option<Tz>,
+    #[serde(flatten)]
+    #[field_names_as_array(skip)]
+    details: Details,
+    /// UID of the iCalendar component this event was imported from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[field_names_as_array(skip)]
+    uid: Option<String>,
+}
+
+/// `Event` as stored in a calendar file, with the UTC offsets the times were written with.
+#[derive(Deserialize)]
+struct EventData {
+    #[serde(default = "new_id")]
+    id: String,
+    name: String,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    repeat: Recurrence,
+    #[serde(default)]
+    repeat_until: Option<NaiveDate>,
+    #[serde(default)]
+    repeat_count: Option<u32>,
+    priority: u8,
+    difficulty: u8,
+    exceptions: Vec<DateTime<FixedOffset>>,
+    #[serde(default)]
+    timezone: Option<Tz>,
+    #[serde(flatten)]
+    details: Details,
+    #[serde(default)]
+    uid: Option<String>,
+}
+
+impl From<EventData> for Event {
+    fn from(data: EventData) -> Self {
+        // floating events keep the wall clock time they were saved with,
+        // even if the local time zone has changed since
+        let timezone = data.timezone;
+        let resolve = |t: DateTime<FixedOffset>| match timezone {
+            Some(_) => t.with_timezone(&Local),
+            None => from_wall_clock(t.naive_local(), None).unwrap_or(t.with_timezone(&Local)),
+        };
+        Event {
+            id: data.id,
+            name: data.name,
+            start: resolve(data.start),
+            end: resolve(data.end),
+            repeat: data.repeat,
+            repeat_until: data.repeat_until,
+            repeat_count: data.repeat_count,
+            priority: data.priority,
+            difficulty: data.difficulty,
+            exceptions: data.exceptions.into_iter().map(resolve).collect(),
+            timezone,
+            details: data.details,
+            uid: data.uid,
+        }
+    }
 }
 
 impl PartialOrd for Event {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        if self.start == other.start {
-            return Some(self.name.cmp(&other.name));
-        }
-        Some(self.start.cmp(&other.start))
+        Some(self.cmp(other))
     }
 }
 
@@ -38,8 +114,9 @@ impl Display for Event {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(
             f,
-            "Name: {} | Start: {} | End: {} | Repeat: {} | Priority: {} | Difficulty: {}",
+            "Name: {} | ID: {} | Start: {} | End: {} | Repeat: {} | Priority: {} | Difficulty: {}",
             self.name,
+            short_id(&self.id),
             self.start,
             self.end,
             self.repeat(),
@@ -56,6 +133,9 @@ impl CalendarDataType for Event {
     fn priority(&self) -> u8 {
         self.priority
     }
+    fn id(&self) -> &str {
+        &self.id
+    }
 }
 
 impl Event {
@@ -63,19 +143,25 @@ impl Event {
         name: String,
         start: DateTime<Local>,
         end: DateTime<Local>,
-        repeat: Duration,
+        repeat: Recurrence,
         priority: u8,
         difficulty: u8,
         exceptions: Vec<DateTime<Local>>,
     ) -> Self {
         Event {
+            id: new_id(),
             name,
             start,
             end,
             repeat,
+            repeat_until: None,
+            repeat_count: None,
             priority,
             difficulty,
             exceptions,
+            timezone: None,
+            details: Details::default(),
+            uid: None,
         }
     }
 
@@ -93,8 +179,31 @@ impl Event {
     pub fn end(&self) -> DateTime<Local> {
         self.end
     }
-    pub fn repeat(&self) -> Duration {
+    pub fn repeat(&self) -> &Recurrence {
+        &self.repeat
+    }
+    pub fn repeat_until(&self) -> Option<NaiveDate> {
+        self.repeat_until
+    }
+    pub fn repeat_count(&self) -> Option<u32> {
+        self.repeat_count
+    }
+    /// Start times of all occurrences, bounded by `repeat_until` and `repeat_count`.
+    /// Exceptions are included.
+    pub fn occurrences(&self) -> impl Iterator<Item = DateTime<Local>> {
+        let until = self.repeat_until;
         self.repeat
+            .occurrences(self.start, self.timezone)
+            .take(self.repeat_count.map_or(usize::MAX, |c| c as usize))
+            .take_while(move |s| until.is_none_or(|u| s.date_naive() <= u))
+    }
+    /// Start of the last occurrence, or `None` if the event repeats forever.
+    pub fn last_occurrence(&self) -> Option<DateTime<Local>> {
+        match (self.repeat.is_none(), self.repeat_until, self.repeat_count) {
+            (true, _, _) => Some(self.start),
+            (false, None, None) => None,
+            (false, _, _) => self.occurrences().last(),
+        }
     }
     pub fn difficulty(&self) -> u8 {
         self.difficulty
@@ -105,6 +214,20 @@ impl Event {
     pub fn exceptions_mut(&mut self) -> &mut Vec<DateTime<Local>> {
         &mut self.exceptions
     }
+    /// Time zone of the event, `None` for floating events.
+    pub fn timezone(&self) -> Option<Tz> {
+        self.timezone
+    }
+    /// Description, location, URL and tags of the event.
+    pub fn details(&self) -> &Details {
+        &self.details
+    }
+    pub fn details_mut(&mut self) -> &mut Details {
+        &mut self.details
+    }
+    pub fn uid(&self) -> Option<String> {
+        self.uid.clone()
+    }
 
     pub fn set_name(&mut self, name: &String) {
         self.name = name.to_string()
@@ -115,8 +238,14 @@ impl Event {
     pub fn set_end(&mut self, new_end: &DateTime<Local>) {
         self.end = *new_end
     }
-    pub fn set_repeat(&mut self, d: &Duration) {
-        self.repeat = *d
+    pub fn set_repeat(&mut self, r: &Recurrence) {
+        self.repeat = r.clone()
+    }
+    pub fn set_repeat_until(&mut self, until: Option<NaiveDate>) {
+        self.repeat_until = until
+    }
+    pub fn set_repeat_count(&mut self, count: Option<u32>) {
+        self.repeat_count = count
     }
     pub fn set_priority(&mut self, p: u8) {
         self.priority = p
@@ -124,6 +253,23 @@ impl Event {
     pub fn set_difficulty(&mut self, d: u8) {
         self.difficulty = d
     }
+    pub fn set_timezone(&mut self, timezone: Option<Tz>) {
+        self.timezone = timezone
+    }
+    pub fn set_uid(&mut self, uid: Option<String>) {
+        self.uid = uid
+    }
+    /// Takes over everything an iCalendar import of this event carries,
+    /// keeping the ID, the reminders and the details the import has no value for.
+    pub fn update_from(&mut self, imported: Event) {
+        let mut details = self.details.clone();
+        details.update_from(&imported.details);
+        *self = Event {
+            id: self.id.clone(),
+            details,
+            ..imported
+        }
+    }
     pub fn duration(&self) -> Duration {
         self.end - self.start
     }
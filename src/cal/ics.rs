@@ -0,0 +1,1119 @@
+use crate::cal::{
+    calendar::Calendar,
+    calmar_error::CalmarError,
+    calmar_trait::CalendarDataType,
+    deadline::{Deadline, DeadlineStatus},
+    details::Details,
+    event::Event,
+    recurrence::{Frequency, NthWeekday, Recurrence, RecurrenceRule},
+    timezone::{from_wall_clock, parse_timezone},
+};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc, Weekday,
+};
+use chrono_tz::{OffsetComponents, OffsetName};
+use std::collections::HashMap;
+
+/// Product identifier written to every exported iCalendar document.
+const PRODID: &str = "-//calmar-team//calmar//EN";
+
+/// Lines of an iCalendar document may not be longer than 75 octets, excluding the line break.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// How many years past the current year, or the last event in a time zone if it is later,
+/// the VTIMEZONE of that zone lists offset changes for.
+const TIMEZONE_YEARS_AHEAD: i32 = 10;
+
+/// Longest repeat interval imported, 100 years like the longest duration calmar accepts.
+/// Dates of later occurrences of longer intervals soon overflow.
+const MAX_INTERVAL_SECONDS: i64 = 36525 * 24 * 60 * 60;
+
+/// Serializes a `Calendar` as an iCalendar (RFC 5545) document.
+/// # Mapping
+/// * `Event` - VEVENT, the recurrence becomes an RRULE and exceptions become EXDATEs.
+///   Recurring events are written with the IANA name of their time zone as TZID,
+///   or in floating local time, so that they repeat at the same wall clock time.
+///   A rule iCalendar cannot express is written as RDATEs covering the next year,
+///   and kept in X-CALMAR-RULE, X-CALMAR-REPEAT-UNTIL and X-CALMAR-REPEAT-COUNT for a re-import.
+///   Every time zone used gets a VTIMEZONE with its offset changes, as a TZID has to refer to one.
+/// * `Deadline` - VTODO with its date as DUE, and its status, progress and completion time
+///   as STATUS, PERCENT-COMPLETE and COMPLETED.
+///
+/// Description, location, URL and tags become DESCRIPTION, LOCATION, URL and CATEGORIES.
+/// Priority and difficulty are kept in X-CALMAR-* properties, so they survive a re-import.
+pub fn to_ics(calendar: &Calendar) -> String {
+    let stamp = format_utc(Utc::now());
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{PRODID}"),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(&calendar.name())),
+    ];
+    lines.append(&mut timezone_lines(calendar.events()));
+    for event in calendar.events() {
+        lines.append(&mut event_lines(event, &calendar.name(), &stamp));
+    }
+    for deadline in calendar.deadlines() {
+        lines.append(&mut deadline_lines(deadline, &calendar.name(), &stamp));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|l| fold_line(l)).collect()
+}
+
+fn event_lines(event: &Event, calendar_name: &str, stamp: &str) -> Vec<String> {
+    // Recurring events are written on the wall clock they repeat on, so that other programs
+    // expand them across daylight saving changes the same way. That is the wall clock
+    // of the event's time zone or floating time. Other events are written in UTC.
+    let timezone = event.timezone();
+    let floating = timezone.is_none() && !event.repeat().is_none();
+    let tzid = match timezone {
+        Some(tz) => format!(";TZID={}", tz.name()),
+        None => "".to_string(),
+    };
+    let format_time = |t: DateTime<Local>| match timezone {
+        Some(tz) => t.with_timezone(&tz).format("%Y%m%dT%H%M%S").to_string(),
+        None if floating => format_floating(t),
+        None => format_utc(t),
+    };
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event_uid(calendar_name, event)),
+        format!("DTSTAMP:{stamp}"),
+        format!("DTSTART{tzid}:{}", format_time(event.start())),
+        format!("DTEND{tzid}:{}", format_time(event.end())),
+        format!("SUMMARY:{}", escape_text(&event.name())),
+    ];
+    lines.append(&mut details_lines(event.details()));
+    match event.repeat() {
+        Recurrence::Interval(d) if d.is_zero() => {}
+        Recurrence::Interval(d) => {
+            lines.push(format!("RRULE:{}{}", interval_rrule(*d), rrule_bounds(event)))
+        }
+        Recurrence::Rule(rule) => match rule_rrule(rule, event.start()) {
+            Some(rrule) => lines.push(format!("RRULE:{rrule}{}", rrule_bounds(event))),
+            None => {
+                let until = Local::now() + Duration::days(366);
+                let dates: Vec<String> = event
+                    .occurrences()
+                    .skip(1)
+                    .take_while(|s| *s < until)
+                    .map(format_time)
+                    .collect();
+                if !dates.is_empty() {
+                    lines.push(format!("RDATE{tzid}:{}", dates.join(",")));
+                }
+                // the RDATEs are for other programs, calmar reads the rule itself back
+                let rule = serde_json::to_string(rule).unwrap_or_default();
+                lines.push(format!("X-CALMAR-RULE:{}", escape_text(&rule)));
+                if let Some(until) = event.repeat_until() {
+                    lines.push(format!("X-CALMAR-REPEAT-UNTIL:{}", until.format("%Y%m%d")));
+                }
+                if let Some(count) = event.repeat_count() {
+                    lines.push(format!("X-CALMAR-REPEAT-COUNT:{count}"));
+                }
+            }
+        },
+    }
+    if !event.exceptions().is_empty() {
+        lines.push(format!(
+            "EXDATE{tzid}:{}",
+            event
+                .exceptions()
+                .iter()
+                .map(|e| format_time(*e))
+                .collect::<Vec<String>>()
+                .join(",")
+        ));
+    }
+    lines.push(format!("PRIORITY:{}", to_ics_priority(event.priority())));
+    lines.push(format!("X-CALMAR-PRIORITY:{}", event.priority()));
+    lines.push(format!("X-CALMAR-DIFFICULTY:{}", event.difficulty()));
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+/// DESCRIPTION, LOCATION, URL and CATEGORIES for the details that are set.
+fn details_lines(details: &Details) -> Vec<String> {
+    let mut lines = vec![];
+    if let Some(description) = details.description() {
+        lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+    }
+    if let Some(location) = details.location() {
+        lines.push(format!("LOCATION:{}", escape_text(location)));
+    }
+    // a URL is a URI, not text, so it is not escaped
+    if let Some(url) = details.url() {
+        lines.push(format!("URL:{url}"));
+    }
+    if !details.tags().is_empty() {
+        let tags: Vec<String> = details.tags().iter().map(|t| escape_text(t)).collect();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+    lines
+}
+
+/// A VTIMEZONE for every time zone of `events`, covering the years from the earliest event in it
+/// until `TIMEZONE_YEARS_AHEAD` years ahead.
+fn timezone_lines(events: &[Event]) -> Vec<String> {
+    let mut zones: Vec<(chrono_tz::Tz, i32, i32)> = vec![];
+    for event in events {
+        if let Some(tz) = event.timezone() {
+            let (first, last) = (event.start().year(), event.end().year());
+            match zones.iter_mut().find(|(zone, _, _)| *zone == tz) {
+                Some((_, from, to)) => {
+                    *from = (*from).min(first);
+                    *to = (*to).max(last);
+                }
+                None => zones.push((tz, first, last)),
+            }
+        }
+    }
+    let this_year = Local::now().year();
+    zones
+        .into_iter()
+        .flat_map(|(tz, from, to)| {
+            vtimezone_lines(tz, from, to.max(this_year) + TIMEZONE_YEARS_AHEAD)
+        })
+        .collect()
+}
+
+/// A VTIMEZONE listing each offset change of `tz` from the start of `from_year`
+/// to the end of `to_year` as an observance of its own.
+fn vtimezone_lines(tz: chrono_tz::Tz, from_year: i32, to_year: i32) -> Vec<String> {
+    let offset_at = |t: NaiveDateTime| tz.offset_from_utc_datetime(&t);
+    let start = NaiveDate::from_ymd(from_year, 1, 1).and_hms(0, 0, 0);
+    let end = NaiveDate::from_ymd(to_year + 1, 1, 1).and_hms(0, 0, 0);
+
+    let mut lines = vec!["BEGIN:VTIMEZONE".to_string(), format!("TZID:{}", tz.name())];
+    let initial = offset_at(start);
+    lines.append(&mut observance_lines(start, initial, initial));
+    // offsets change at most a few times a year, so look for changes day by day
+    // and find the second they happen at by bisection
+    let (mut before, mut offset) = (start, initial);
+    while before < end {
+        let after = before + Duration::days(1);
+        let next = offset_at(after);
+        if next != offset {
+            let (mut low, mut high) = (before, after);
+            while high - low > Duration::seconds(1) {
+                let middle = low + (high - low) / 2;
+                match offset_at(middle) == offset {
+                    true => low = middle,
+                    false => high = middle,
+                }
+            }
+            lines.append(&mut observance_lines(high, offset, next));
+            offset = next;
+        }
+        before = after;
+    }
+    lines.push("END:VTIMEZONE".to_string());
+    lines
+}
+
+/// A STANDARD or DAYLIGHT observance for the change from offset `from` to `to` at `at` in UTC.
+fn observance_lines(
+    at: NaiveDateTime,
+    from: <chrono_tz::Tz as TimeZone>::Offset,
+    to: <chrono_tz::Tz as TimeZone>::Offset,
+) -> Vec<String> {
+    let kind = match to.dst_offset().is_zero() {
+        true => "STANDARD",
+        false => "DAYLIGHT",
+    };
+    // the start of an observance is given in the wall clock time of the offset it replaces
+    let local_start = at + Duration::seconds(from.fix().local_minus_utc() as i64);
+    vec![
+        format!("BEGIN:{kind}"),
+        format!("DTSTART:{}", local_start.format("%Y%m%dT%H%M%S")),
+        format!("TZOFFSETFROM:{}", format_utc_offset(from.fix().local_minus_utc())),
+        format!("TZOFFSETTO:{}", format_utc_offset(to.fix().local_minus_utc())),
+        format!("TZNAME:{}", escape_text(to.abbreviation())),
+        format!("END:{kind}"),
+    ]
+}
+
+/// A UTC offset in seconds as iCalendar writes it, such as `+0530` or `-0456`.
+/// Seconds are only written if there are any.
+fn format_utc_offset(seconds: i32) -> String {
+    let sign = match seconds < 0 {
+        true => '-',
+        false => '+',
+    };
+    let seconds = seconds.abs();
+    match seconds % 60 {
+        0 => format!("{sign}{:02}{:02}", seconds / 3600, seconds / 60 % 60),
+        s => format!("{sign}{:02}{:02}{s:02}", seconds / 3600, seconds / 60 % 60),
+    }
+}
+
+fn deadline_lines(deadline: &Deadline, calendar_name: &str, stamp: &str) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VTODO".to_string(),
+        format!("UID:{}", deadline_uid(calendar_name, deadline)),
+        format!("DTSTAMP:{stamp}"),
+        format!("DUE:{}", format_utc(deadline.date())),
+        format!("SUMMARY:{}", escape_text(&deadline.name())),
+        format!("PRIORITY:{}", to_ics_priority(deadline.priority())),
+        format!("X-CALMAR-PRIORITY:{}", deadline.priority()),
+        format!(
+            "STATUS:{}",
+            match deadline.status() {
+                DeadlineStatus::Open => "NEEDS-ACTION",
+                DeadlineStatus::InProgress => "IN-PROCESS",
+                DeadlineStatus::Done => "COMPLETED",
+                DeadlineStatus::Dropped => "CANCELLED",
+            }
+        ),
+    ];
+    lines.append(&mut details_lines(deadline.details()));
+    if let Some(progress) = deadline.progress() {
+        lines.push(format!("PERCENT-COMPLETE:{progress}"));
+    }
+    if let Some(completed) = deadline.completed() {
+        lines.push(format!("COMPLETED:{}", format_utc(completed)));
+    }
+    lines.push("END:VTODO".to_string());
+    lines
+}
+
+/// Express a fixed repeat interval as an RRULE value, using the largest unit it is a multiple of.
+pub fn interval_rrule(repeat: Duration) -> String {
+    let seconds = repeat.num_seconds();
+    let (frequency, unit) = [
+        ("WEEKLY", 7 * 24 * 60 * 60),
+        ("DAILY", 24 * 60 * 60),
+        ("HOURLY", 60 * 60),
+        ("MINUTELY", 60),
+        ("SECONDLY", 1),
+    ]
+    .into_iter()
+    .find(|(_, unit)| seconds % unit == 0)
+    .unwrap();
+    format!("FREQ={frequency};INTERVAL={}", seconds / unit)
+}
+
+/// The COUNT or UNTIL part of the RRULE of a bounded recurring event.
+/// An RRULE may not have both, so an event bounded by both ends at its last occurrence.
+fn rrule_bounds(event: &Event) -> String {
+    // UNTIL has to be in UTC if DTSTART has a time zone and in floating time if it is floating
+    let format_until = |t: DateTime<Local>| match event.timezone() {
+        Some(_) => format_utc(t),
+        None => format_floating(t),
+    };
+    match (event.repeat_until(), event.repeat_count()) {
+        (None, None) => String::new(),
+        (None, Some(count)) => format!(";COUNT={count}"),
+        (Some(until), None) => match from_wall_clock(until.and_hms(23, 59, 59), event.timezone()) {
+            Some(until) => format!(";UNTIL={}", format_until(until)),
+            None => String::new(),
+        },
+        (Some(_), Some(_)) => match event.last_occurrence() {
+            Some(last) => format!(";UNTIL={}", format_until(last)),
+            None => String::new(),
+        },
+    }
+}
+
+/// Express a recurrence rule as an RRULE value.
+/// Returns `None` for rules iCalendar cannot express, such as a rule on both days of the month
+/// and weekdays of the month, which an RRULE would intersect instead of joining.
+pub fn rule_rrule(rule: &RecurrenceRule, start: DateTime<Local>) -> Option<String> {
+    let mut parts = vec![
+        format!("FREQ={}", ics_frequency(rule.frequency)),
+        format!("INTERVAL={}", rule.interval),
+    ];
+    match rule.frequency {
+        Frequency::Daily | Frequency::Weekly => {
+            if !rule.by_weekday.is_empty() {
+                let days: Vec<&str> = rule.by_weekday.iter().map(|d| ics_weekday(*d)).collect();
+                parts.push(format!("BYDAY={}", days.join(",")));
+            }
+            // calmar weeks start on Monday
+            if rule.frequency == Frequency::Weekly {
+                parts.push("WKST=MO".to_string());
+            }
+        }
+        Frequency::Monthly | Frequency::Yearly => {
+            if rule.frequency == Frequency::Yearly {
+                parts.push(format!("BYMONTH={}", start.month()));
+            }
+            let clamped: Vec<i8> = match rule.clamp_month_end {
+                true => rule
+                    .by_month_day
+                    .iter()
+                    .copied()
+                    .filter(|d| *d > 28)
+                    .collect(),
+                false => vec![],
+            };
+            match (rule.by_month_day.is_empty(), rule.by_nth_weekday.is_empty()) {
+                (false, false) => return None,
+                (true, true) => parts.push(format!("BYMONTHDAY={}", start.day())),
+                (true, false) => {
+                    let days: Vec<String> = rule
+                        .by_nth_weekday
+                        .iter()
+                        .map(|d| format!("{}{}", d.n, ics_weekday(d.weekday)))
+                        .collect();
+                    parts.push(format!("BYDAY={}", days.join(",")));
+                }
+                // the last existing day out of 28..=day is the day itself or the end of the month
+                (false, true) if !clamped.is_empty() => match rule.by_month_day[..] {
+                    [day] => {
+                        let days: Vec<String> = (28..=day).map(|d| d.to_string()).collect();
+                        parts.push(format!("BYMONTHDAY={};BYSETPOS=-1", days.join(",")));
+                    }
+                    _ => return None,
+                },
+                (false, true) => {
+                    let days: Vec<String> =
+                        rule.by_month_day.iter().map(|d| d.to_string()).collect();
+                    parts.push(format!("BYMONTHDAY={}", days.join(",")));
+                }
+            }
+        }
+    }
+    Some(parts.join(";"))
+}
+
+fn ics_frequency(frequency: Frequency) -> &'static str {
+    match frequency {
+        Frequency::Daily => "DAILY",
+        Frequency::Weekly => "WEEKLY",
+        Frequency::Monthly => "MONTHLY",
+        Frequency::Yearly => "YEARLY",
+    }
+}
+
+/// Calmar priorities range from 0 (lowest) to 10 (highest).
+/// iCalendar uses 1 (highest) to 9 (lowest) and 0 for an undefined priority.
+pub fn to_ics_priority(priority: u8) -> u8 {
+    match priority {
+        0 => 0,
+        p => 10u8.saturating_sub(p).max(1),
+    }
+}
+
+/// The UID an event is exported with: the one it was imported with or a generated one.
+pub fn event_uid(calendar_name: &str, event: &Event) -> String {
+    event
+        .uid()
+        .unwrap_or_else(|| generate_uid(calendar_name, &event.name(), event.start()))
+}
+
+/// The UID a deadline is exported with: the one it was imported with or a generated one.
+pub fn deadline_uid(calendar_name: &str, deadline: &Deadline) -> String {
+    deadline
+        .uid()
+        .unwrap_or_else(|| generate_uid(calendar_name, &deadline.name(), deadline.date()))
+}
+
+/// A UID that stays the same across exports of an unchanged item.
+fn generate_uid(calendar_name: &str, name: &str, start: DateTime<Local>) -> String {
+    // 64-bit FNV-1a, stable across platforms and Rust versions
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in format!("{calendar_name}\u{0}{name}").bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{}-{hash:016x}@calmar", format_utc(start))
+}
+
+pub fn format_utc<Tz: TimeZone>(datetime: DateTime<Tz>) -> String {
+    datetime
+        .with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Local time without a time zone, which iCalendar calls floating time.
+pub fn format_floating(datetime: DateTime<Local>) -> String {
+    datetime.format("%Y%m%dT%H%M%S").to_string()
+}
+
+/// Escape a TEXT property value as described in RFC 5545, section 3.3.11.
+pub fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Split a content line into lines of at most 75 octets, as described in RFC 5545, section 3.1.
+/// Continuation lines start with a single space. Multi-byte characters are never split.
+pub fn fold_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + 2);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // the leading space counts towards the length of the continuation line
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+/// Events and deadlines read from an iCalendar document.
+/// `problems` describes every component that was skipped or only partially imported.
+#[derive(Debug, Default)]
+pub struct IcsImport {
+    pub events: Vec<Event>,
+    pub deadlines: Vec<Deadline>,
+    pub problems: Vec<String>,
+}
+
+/// A content line split into its parts. Parameter names are uppercased.
+#[derive(Debug, Clone)]
+struct Property {
+    name: String,
+    params: HashMap<String, String>,
+    value: String,
+}
+
+/// A VEVENT or VTODO with the line number of its BEGIN line.
+struct Component {
+    kind: String,
+    line: usize,
+    properties: Vec<Property>,
+}
+
+impl Component {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+    fn get_all(&self, name: &str) -> Vec<&Property> {
+        self.properties.iter().filter(|p| p.name == name).collect()
+    }
+    fn describe(&self) -> String {
+        match self.get("SUMMARY") {
+            Some(s) => {
+                format!("{} \"{}\" (line {})", self.kind, unescape_text(&s.value), self.line)
+            }
+            None => format!("{} (line {})", self.kind, self.line),
+        }
+    }
+}
+
+/// Parses an iCalendar (RFC 5545) document into events and deadlines.
+///
+/// Only a malformed document as a whole results in an error.
+/// Components calmar cannot represent are skipped and reported in `IcsImport::problems`.
+pub fn from_ics(content: &str) -> Result<IcsImport, CalmarError> {
+    let lines = unfold_lines(content);
+    let mut import = IcsImport::default();
+
+    if !lines
+        .iter()
+        .any(|(_, l)| l.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+    {
+        return Err(CalmarError::ParseICS {
+            e: "No VCALENDAR component found.".to_string(),
+        });
+    }
+
+    // Components nested in a VEVENT or VTODO (e.g. VALARM) are skipped along with their properties.
+    let mut current: Option<Component> = None;
+    let mut nested_depth = 0;
+    for (line_number, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let property = match parse_content_line(&line) {
+            Some(p) => p,
+            None => {
+                return Err(CalmarError::ParseICS {
+                    e: format!("Line {line_number} is not a valid content line: {line}"),
+                })
+            }
+        };
+        let value = property.value.to_uppercase();
+        match (property.name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value == "VEVENT" || value == "VTODO" => {
+                current = Some(Component {
+                    kind: value,
+                    line: line_number,
+                    properties: vec![],
+                })
+            }
+            ("BEGIN", Some(_)) => nested_depth += 1,
+            ("END", Some(_)) if nested_depth > 0 => nested_depth -= 1,
+            ("END", Some(c)) if c.kind == value => {
+                let component = current.take().unwrap();
+                let result = match component.kind.as_str() {
+                    "VEVENT" => component_to_event(&component).map(|(e, notes)| {
+                        import.events.push(e);
+                        notes
+                    }),
+                    _ => component_to_deadline(&component).map(|(d, notes)| {
+                        import.deadlines.push(d);
+                        notes
+                    }),
+                };
+                match result {
+                    Ok(notes) => import.problems.extend(
+                        notes
+                            .into_iter()
+                            .map(|n| format!("{}: {n}", component.describe())),
+                    ),
+                    Err(e) => import
+                        .problems
+                        .push(format!("{}: Skipped. {e}", component.describe())),
+                }
+            }
+            (_, Some(c)) if nested_depth == 0 => c.properties.push(property),
+            _ => {}
+        }
+    }
+    if let Some(c) = current {
+        return Err(CalmarError::ParseICS {
+            e: format!("{} is never closed with END:{}", c.describe(), c.kind),
+        });
+    }
+    Ok(import)
+}
+
+/// Converts a VEVENT. Returns notes about properties that were only partially imported.
+fn component_to_event(c: &Component) -> Result<(Event, Vec<String>), String> {
+    let mut notes = vec![];
+    if c.get("RECURRENCE-ID").is_some() {
+        return Err("Modified occurrences of recurring events are not supported.".to_string());
+    }
+    // calmar writes RDATEs only next to the rule they were expanded from
+    let calmar_rule = c.get("X-CALMAR-RULE");
+    if c.get("RDATE").is_some() && calmar_rule.is_none() {
+        return Err("RDATE is not supported.".to_string());
+    }
+    let name = match c.get("SUMMARY") {
+        Some(p) => unescape_text(&p.value),
+        None => return Err("SUMMARY is missing.".to_string()),
+    };
+    let dtstart = match c.get("DTSTART") {
+        Some(p) => p,
+        None => return Err("DTSTART is missing.".to_string()),
+    };
+    let (start, all_day) = parse_date_time(dtstart, &mut notes)?;
+    let end = match (c.get("DTEND"), c.get("DURATION")) {
+        (Some(p), _) => parse_date_time(p, &mut notes)?.0,
+        (None, Some(p)) => match parse_ics_duration(&p.value) {
+            Some(d) => start + d,
+            None => return Err(format!("Invalid DURATION {}.", p.value)),
+        },
+        (None, None) if all_day => start + Duration::days(1),
+        (None, None) => start,
+    };
+    if end < start {
+        return Err("DTEND is before DTSTART.".to_string());
+    }
+    let (repeat, repeat_until, repeat_count) = match (c.get("RRULE"), calmar_rule) {
+        (Some(p), _) => {
+            let (until, count) = rrule_to_bounds(p, start, &mut notes)?;
+            (rrule_to_recurrence(&p.value, start)?, until, count)
+        }
+        (None, Some(p)) => {
+            let rule = match serde_json::from_str::<RecurrenceRule>(&unescape_text(&p.value)) {
+                Ok(rule)
+                    if check_interval(ics_frequency(rule.frequency), rule.interval).is_ok() =>
+                {
+                    rule
+                }
+                _ => return Err(format!("Invalid X-CALMAR-RULE {}.", p.value)),
+            };
+            let until = match c.get("X-CALMAR-REPEAT-UNTIL") {
+                Some(p) => match NaiveDate::parse_from_str(&p.value, "%Y%m%d") {
+                    Ok(date) => Some(date),
+                    Err(_) => return Err(format!("Invalid X-CALMAR-REPEAT-UNTIL {}.", p.value)),
+                },
+                None => None,
+            };
+            let count = match c.get("X-CALMAR-REPEAT-COUNT") {
+                Some(p) => match p.value.parse::<u32>() {
+                    Ok(count) if count > 0 => Some(count),
+                    _ => return Err(format!("Invalid X-CALMAR-REPEAT-COUNT {}.", p.value)),
+                },
+                None => None,
+            };
+            (Recurrence::Rule(rule), until, count)
+        }
+        (None, None) => (Recurrence::none(), None, None),
+    };
+    let mut exceptions = vec![];
+    for p in c.get_all("EXDATE") {
+        for value in p.value.split(',') {
+            let exdate = Property {
+                value: value.to_string(),
+                ..p.clone()
+            };
+            exceptions.push(parse_date_time(&exdate, &mut notes)?.0);
+        }
+    }
+
+    let mut event = Event::new(
+        name,
+        start,
+        end,
+        repeat,
+        import_priority(c),
+        match c.get("X-CALMAR-DIFFICULTY").map(|p| p.value.parse::<u8>()) {
+            Some(Ok(d)) if d <= 10 => d,
+            _ => 0,
+        },
+        exceptions,
+    );
+    event.set_repeat_until(repeat_until);
+    event.set_repeat_count(repeat_count);
+    event.set_timezone(
+        dtstart
+            .params
+            .get("TZID")
+            .and_then(|tzid| parse_timezone(tzid.trim_start_matches('/'))),
+    );
+    event.set_uid(c.get("UID").map(|p| p.value.clone()));
+    *event.details_mut() = import_details(c);
+    notes.dedup();
+    Ok((event, notes))
+}
+
+/// Converts a VTODO. Returns notes about properties that were only partially imported.
+fn component_to_deadline(c: &Component) -> Result<(Deadline, Vec<String>), String> {
+    let mut notes = vec![];
+    let name = match c.get("SUMMARY") {
+        Some(p) => unescape_text(&p.value),
+        None => return Err("SUMMARY is missing.".to_string()),
+    };
+    let date = match c.get("DUE") {
+        Some(p) => parse_date_time(p, &mut notes)?.0,
+        None => return Err("DUE is missing, calmar deadlines need a date.".to_string()),
+    };
+    let mut deadline = Deadline::new(name, date, import_priority(c));
+    deadline.set_uid(c.get("UID").map(|p| p.value.clone()));
+    *deadline.details_mut() = import_details(c);
+    if let Some(Ok(progress)) = c.get("PERCENT-COMPLETE").map(|p| p.value.parse::<u8>()) {
+        if progress <= 100 {
+            deadline.set_progress(Some(progress));
+        }
+    }
+    let completed = match c.get("COMPLETED") {
+        Some(p) => parse_date_time(p, &mut notes)?.0,
+        None => Local::now(),
+    };
+    match c.get("STATUS").map(|p| p.value.as_str()) {
+        Some("COMPLETED") => deadline.finish(DeadlineStatus::Done, completed),
+        Some("CANCELLED") => deadline.finish(DeadlineStatus::Dropped, completed),
+        _ => {}
+    }
+    notes.dedup();
+    Ok((deadline, notes))
+}
+
+/// Description, location, URL and tags from DESCRIPTION, LOCATION, URL and CATEGORIES.
+fn import_details(c: &Component) -> Details {
+    let mut details = Details::default();
+    if let Some(p) = c.get("DESCRIPTION") {
+        details.set_description(&unescape_text(&p.value));
+    }
+    if let Some(p) = c.get("LOCATION") {
+        details.set_location(&unescape_text(&p.value));
+    }
+    if let Some(p) = c.get("URL") {
+        details.set_url(&p.value);
+    }
+    details.set_tags(
+        c.get_all("CATEGORIES")
+            .iter()
+            .flat_map(|p| split_text_list(&p.value))
+            .map(|tag| unescape_text(&tag).trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect(),
+    );
+    details
+}
+
+/// Splits a list of text values at the commas that are not escaped.
+fn split_text_list(value: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ',' => items.push(String::new()),
+            '\\' => {
+                let item = items.last_mut().unwrap();
+                item.push(c);
+                item.extend(chars.next());
+            }
+            c => items.last_mut().unwrap().push(c),
+        }
+    }
+    items
+}
+
+/// Prefers the exact calmar priority, falls back to mapping the iCalendar PRIORITY.
+fn import_priority(c: &Component) -> u8 {
+    if let Some(Ok(p)) = c.get("X-CALMAR-PRIORITY").map(|p| p.value.parse::<u8>()) {
+        if p <= 10 {
+            return p;
+        }
+    }
+    match c.get("PRIORITY").map(|p| p.value.parse::<u8>()) {
+        Some(Ok(p)) if (1..=9).contains(&p) => 10 - p,
+        _ => 0,
+    }
+}
+
+/// Rejects an interval of the RRULE frequency `frequency` longer than `MAX_INTERVAL_SECONDS`.
+/// Months and years are counted by their shortest length.
+fn check_interval(frequency: &str, interval: u32) -> Result<(), String> {
+    let day = 24 * 60 * 60;
+    let period = match frequency {
+        "YEARLY" => 365 * day,
+        "MONTHLY" => 28 * day,
+        "WEEKLY" => 7 * day,
+        "DAILY" => day,
+        "HOURLY" => 60 * 60,
+        "MINUTELY" => 60,
+        _ => 1,
+    };
+    match interval > 0 && interval as i64 * period <= MAX_INTERVAL_SECONDS {
+        true => Ok(()),
+        false => Err(format!("RRULE interval {interval} is too long.")),
+    }
+}
+
+/// Maps an RRULE onto a recurrence.
+/// Rules without any BY* parts become fixed intervals, rules on calendar dates become
+/// recurrence rules. COUNT and UNTIL are read by `rrule_to_bounds`.
+fn rrule_to_recurrence(rule: &str, start: DateTime<Local>) -> Result<Recurrence, String> {
+    let mut frequency = None;
+    let mut interval = 1;
+    let mut by_day = None;
+    let mut by_month_day = None;
+    let mut last_of_set = false;
+    for part in rule.split(';').filter(|p| !p.is_empty()) {
+        let (key, value) = match part.split_once('=') {
+            Some(kv) => kv,
+            None => return Err(format!("Invalid RRULE part {part}.")),
+        };
+        match key.to_uppercase().as_str() {
+            "FREQ" => frequency = Some(value.to_uppercase()),
+            "INTERVAL" => match value.parse::<u32>() {
+                Ok(i) if i > 0 => interval = i,
+                _ => return Err(format!("Invalid RRULE interval {value}.")),
+            },
+            // the week start only matters for BYDAY and BYWEEKNO
+            "WKST" => {}
+            "COUNT" | "UNTIL" => {}
+            "BYDAY" => by_day = Some(value.to_uppercase()),
+            "BYMONTHDAY" => by_month_day = Some(value.to_string()),
+            // only the month of the start is supported, which is what a yearly rule repeats in
+            "BYMONTH" if value.parse::<u32>() == Ok(start.month()) => {}
+            "BYSETPOS" if value == "-1" => last_of_set = true,
+            _ => return Err(format!("RRULE part {part} is not supported.")),
+        }
+    }
+    if let Some(frequency) = &frequency {
+        check_interval(frequency, interval)?;
+    }
+    let unit = match frequency.as_deref() {
+        Some("DAILY") => Frequency::Daily,
+        Some("WEEKLY") => Frequency::Weekly,
+        Some("MONTHLY") => Frequency::Monthly,
+        Some("YEARLY") => Frequency::Yearly,
+        Some(f) => {
+            let unit = match f {
+                "HOURLY" => Duration::hours(1),
+                "MINUTELY" => Duration::minutes(1),
+                "SECONDLY" => Duration::seconds(1),
+                f => return Err(format!("RRULE frequency {f} is not supported.")),
+            };
+            if by_day.is_some() || by_month_day.is_some() || last_of_set {
+                return Err(format!("RRULE {rule} is not supported."));
+            }
+            return Ok(Recurrence::Interval(unit * interval as i32));
+        }
+        None => return Err("RRULE has no FREQ.".to_string()),
+    };
+
+    // plain daily and weekly rules repeat at a fixed interval
+    let start_day = ics_weekday(start.weekday());
+    match (unit, by_day.as_deref(), &by_month_day, last_of_set) {
+        (Frequency::Daily, None, None, false) => {
+            return Ok(Recurrence::Interval(Duration::days(interval as i64)))
+        }
+        (Frequency::Weekly, None, None, false) => {
+            return Ok(Recurrence::Interval(Duration::weeks(interval as i64)))
+        }
+        (Frequency::Weekly, Some(d), None, false) if d == start_day => {
+            return Ok(Recurrence::Interval(Duration::weeks(interval as i64)))
+        }
+        _ => {}
+    }
+
+    let mut recurrence = RecurrenceRule::new(unit, interval);
+    let unsupported = || Err(format!("RRULE {rule} is not supported."));
+    match unit {
+        Frequency::Daily | Frequency::Weekly => {
+            if by_month_day.is_some() || last_of_set {
+                return unsupported();
+            }
+            for day in by_day.unwrap_or_default().split(',') {
+                match parse_ics_weekday(day) {
+                    Some(d) => recurrence.by_weekday.push(d),
+                    None => return unsupported(),
+                }
+            }
+        }
+        Frequency::Monthly | Frequency::Yearly => {
+            if by_day.is_some() && by_month_day.is_some() {
+                return unsupported();
+            }
+            for day in by_day
+                .unwrap_or_default()
+                .split(',')
+                .filter(|d| !d.is_empty())
+            {
+                // the weekday is the last two characters, which need not be ASCII in a broken file
+                let split = day.char_indices().rev().nth(1).map_or(0, |(i, _)| i);
+                match (day[..split].parse::<i8>(), parse_ics_weekday(&day[split..])) {
+                    (Ok(n), Some(weekday)) if n != 0 && (-5..=5).contains(&n) => {
+                        recurrence.by_nth_weekday.push(NthWeekday { n, weekday })
+                    }
+                    _ => return unsupported(),
+                }
+            }
+            for day in by_month_day
+                .unwrap_or_default()
+                .split(',')
+                .filter(|d| !d.is_empty())
+            {
+                match day.parse::<i8>() {
+                    Ok(d) if d != 0 && (-31..=31).contains(&d) => recurrence.by_month_day.push(d),
+                    _ => return unsupported(),
+                }
+            }
+            if last_of_set {
+                // BYMONTHDAY=28,29,30,31;BYSETPOS=-1 is the 31st or the last day of shorter months
+                match recurrence.by_month_day.iter().max() {
+                    Some(max) if recurrence.by_month_day.iter().all(|d| *d >= 28) => {
+                        recurrence.by_month_day = vec![*max];
+                        recurrence.clamp_month_end = true;
+                    }
+                    _ => return unsupported(),
+                }
+            }
+            if recurrence.by_month_day.is_empty() && recurrence.by_nth_weekday.is_empty() {
+                recurrence.by_month_day.push(start.day() as i8);
+            }
+        }
+    }
+    Ok(Recurrence::Rule(recurrence))
+}
+
+/// Reads the COUNT and UNTIL parts of an RRULE property.
+/// UNTIL becomes the last date an occurrence may start on.
+fn rrule_to_bounds(
+    p: &Property,
+    start: DateTime<Local>,
+    notes: &mut Vec<String>,
+) -> Result<(Option<NaiveDate>, Option<u32>), String> {
+    let mut until = None;
+    let mut count = None;
+    for (key, value) in p.value.split(';').filter_map(|part| part.split_once('=')) {
+        match key.to_uppercase().as_str() {
+            "COUNT" => match value.parse::<u32>() {
+                Ok(c) if c > 0 => count = Some(c),
+                _ => return Err(format!("Invalid RRULE count {value}.")),
+            },
+            "UNTIL" => {
+                let until_property = Property {
+                    name: "UNTIL".to_string(),
+                    params: HashMap::new(),
+                    value: value.to_string(),
+                };
+                let (end, all_day) = parse_date_time(&until_property, notes)?;
+                // an occurrence on the last date only counts if it starts before UNTIL
+                until = match all_day || end.time() >= start.time() {
+                    true => Some(end.date_naive()),
+                    false => end.date_naive().pred_opt(),
+                };
+            }
+            _ => {}
+        }
+    }
+    Ok((until, count))
+}
+
+fn parse_ics_weekday(day: &str) -> Option<Weekday> {
+    [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ]
+    .into_iter()
+    .find(|d| ics_weekday(*d) == day)
+}
+
+pub fn ics_weekday(weekday: Weekday) -> &'static str {
+    ["MO", "TU", "WE", "TH", "FR", "SA", "SU"][weekday.num_days_from_monday() as usize]
+}
+
+/// Parses a DATE or DATE-TIME value. Returns the time and whether it was a date only.
+/// Floating times are taken as local time. Times with a TZID are taken in that IANA time zone,
+/// or as local time with a note added to `notes` if the zone is unknown.
+fn parse_date_time(
+    p: &Property,
+    notes: &mut Vec<String>,
+) -> Result<(DateTime<Local>, bool), String> {
+    let value = p.value.trim();
+    let invalid = || format!("Invalid {} value {value}.", p.name);
+
+    if value.len() == 8 || p.params.get("VALUE").map(|v| v.as_str()) == Some("DATE") {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid())?;
+        return match Local.from_local_datetime(&date.and_hms(0, 0, 0)).earliest() {
+            Some(d) => Ok((d, true)),
+            None => Err(invalid()),
+        };
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        return Ok((Utc.from_utc_datetime(&naive).with_timezone(&Local), false));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+    let timezone = match p.params.get("TZID") {
+        Some(tzid) => match parse_timezone(tzid.trim_start_matches('/')) {
+            Some(tz) => Some(tz),
+            None => {
+                notes.push(format!("Unknown time zone {tzid} was taken as local time."));
+                None
+            }
+        },
+        None => None,
+    };
+    match from_wall_clock(naive, timezone) {
+        Some(d) => Ok((d, false)),
+        None => Err(invalid()),
+    }
+}
+
+/// Parses a DURATION value such as `PT1H30M`, `P1D` or `P2W`.
+pub fn parse_ics_duration(value: &str) -> Option<Duration> {
+    let (negative, rest) = match value.trim().strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim().trim_start_matches('+')),
+    };
+    let rest = rest.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    let mut components = 0;
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if !in_time && number.is_empty() => in_time = true,
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                components += 1;
+                total = total
+                    + match (unit, in_time) {
+                        ('W', false) => Duration::weeks(n),
+                        ('D', false) => Duration::days(n),
+                        ('H', true) => Duration::hours(n),
+                        ('M', true) => Duration::minutes(n),
+                        ('S', true) => Duration::seconds(n),
+                        _ => return None,
+                    }
+            }
+        }
+    }
+    if !number.is_empty() || components == 0 {
+        return None;
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// Join folded lines. Returns every logical line with the number of its first physical line.
+fn unfold_lines(content: &str) -> Vec<(usize, String)> {
+    let mut out: Vec<(usize, String)> = vec![];
+    for (i, line) in content.lines().enumerate() {
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), out.last_mut()) {
+            (Some(continuation), Some((_, last))) => last.push_str(continuation),
+            _ => out.push((i + 1, line.to_string())),
+        }
+    }
+    out
+}
+
+/// Split `NAME;PARAM=VALUE;...:VALUE`. Colons and semicolons in quoted parameter values are kept.
+fn parse_content_line(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let mut separators = vec![];
+    let mut colon = None;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => separators.push(i),
+            ':' if !in_quotes => {
+                colon = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let colon = colon?;
+    let head = &line[..colon];
+    let name_end = *separators.first().unwrap_or(&colon);
+    let name = head[..name_end].trim().to_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut params = HashMap::new();
+    let mut bounds = separators.clone();
+    bounds.push(colon);
+    for w in bounds.windows(2) {
+        let (key, value) = line[w[0] + 1..w[1]].split_once('=')?;
+        params.insert(key.to_uppercase(), value.trim_matches('"').to_string());
+    }
+    Some(Property {
+        name,
+        params,
+        value: line[colon + 1..].to_string(),
+    })
+}
+
+/// Reverse `escape_text`.
+pub fn unescape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(escaped) => out.push(escaped),
+                None => out.push('\\'),
+            },
+            (c, false) => out.push(c),
+        }
+    }
+    out
+}
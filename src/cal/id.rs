@@ -0,0 +1,54 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+/// Number of characters of an ID shown in listings.
+pub const SHORT_ID_LENGTH: usize = 6;
+
+/// The shortest beginning of an ID commands accept in place of a name.
+pub const MIN_ID_PREFIX: usize = 4;
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Returns a new ID of 16 hexadecimal digits, unique across calendars and calmar instances.
+pub fn new_id() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    SystemTime::now().hash(&mut hasher);
+    std::process::id().hash(&mut hasher);
+    COUNTER.fetch_add(1, Ordering::Relaxed).hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// The beginning of `id` shown in listings.
+pub fn short_id(id: &str) -> &str {
+    match id.char_indices().nth(SHORT_ID_LENGTH) {
+        Some((i, _)) => &id[..i],
+        None => id,
+    }
+}
+
+/// Indices of the items `key` refers to, given the name and ID of every item.
+/// These are all items named `key` or, if there are none, the items whose ID starts with `key`.
+/// An ID has to be given with at least `MIN_ID_PREFIX` characters.
+pub fn find_by_key<'a>(
+    items: impl Iterator<Item = (String, &'a str)> + Clone,
+    key: &str,
+) -> Vec<usize> {
+    let named: Vec<usize> = items
+        .clone()
+        .enumerate()
+        .filter(|(_, (name, _))| name == key)
+        .map(|(i, _)| i)
+        .collect();
+    if !named.is_empty() || key.chars().count() < MIN_ID_PREFIX {
+        return named;
+    }
+    items
+        .enumerate()
+        .filter(|(_, (_, id))| id.starts_with(&key.to_lowercase()))
+        .map(|(i, _)| i)
+        .collect()
+}
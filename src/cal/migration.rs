@@ -0,0 +1,174 @@
+use crate::{cal::calmar_error::CalmarError, success, CONFIG};
+use lazy_static::lazy_static;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::Mutex,
+};
+
+/// Version of the calendar and index file format written by this calmar.
+/// Files without a version were written before versions were introduced and count as version 0.
+pub const SCHEMA_VERSION: u32 = 6;
+
+lazy_static! {
+    /// Contents of the files that were upgraded when read, by path, until the upgraded versions are saved.
+    static ref ORIGINALS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Calendar,
+    Index,
+}
+
+/// Upgrades a calendar or index `document` read from a file to `SCHEMA_VERSION`,
+/// one version at a time. Returns whether anything had to be upgraded.
+/// Documents written by a newer calmar are refused, as they may hold data this version would lose.
+pub fn migrate(document: &mut Value, kind: FileKind) -> Result<bool, CalmarError> {
+    let version = match document.get("version") {
+        None => 0,
+        Some(v) => match v.as_u64() {
+            Some(v) => v as u32,
+            None => {
+                return Err(CalmarError::UnknownSchema {
+                    e: format!("{v} is not a valid version"),
+                })
+            }
+        },
+    };
+    if version > SCHEMA_VERSION {
+        return Err(CalmarError::NewerSchema { version });
+    }
+    if !document.is_object() {
+        return Err(CalmarError::UnknownSchema {
+            e: "expected a JSON object".to_string(),
+        });
+    }
+
+    for from in version..SCHEMA_VERSION {
+        match (from, kind) {
+            (0, FileKind::Calendar) => unversioned_calendar(document),
+            (0, FileKind::Index) => unversioned_index(document),
+            (1, FileKind::Calendar) => {
+                add_ids(document, "events");
+                add_ids(document, "deadlines");
+            }
+            (1, FileKind::Index) => add_ids(document, "calendars"),
+            (3, FileKind::Calendar) => for_each_in(document, "deadlines", |deadline| {
+                fill(deadline, &[("status", json!("open"))])
+            }),
+            // versions 3, 5 and 6 added optional fields, which older files simply lack.
+            // The version still changes, so that older calmars refuse files they would lose these fields of.
+            _ => {}
+        }
+        document["version"] = json!(from + 1);
+    }
+    Ok(version < SCHEMA_VERSION)
+}
+
+/// Parses the `content` of the calendar or index file under `path`.
+/// A file written by an older calmar is upgraded in memory only, the file itself is left alone
+/// until the upgraded version is saved, see `keep_original`.
+pub fn load<T: DeserializeOwned>(
+    path: &str,
+    content: &str,
+    kind: FileKind,
+) -> Result<T, CalmarError> {
+    let mut document: Value = match serde_json::from_str(content) {
+        Ok(result) => result,
+        Err(e) => return Err(CalmarError::ParseJSON { e }),
+    };
+    let migrated = migrate(&mut document, kind)?;
+    let result: T = match serde_json::from_value(document) {
+        Ok(result) => result,
+        Err(e) => return Err(CalmarError::ParseJSON { e }),
+    };
+    if migrated {
+        ORIGINALS
+            .lock()
+            .unwrap()
+            .entry(path.to_string())
+            .or_insert_with(|| content.to_string());
+    }
+    Ok(result)
+}
+
+/// Called before the file under `path` is saved. If it was upgraded when read,
+/// its original contents are kept next to it with a `.pre-migration` extension.
+pub fn keep_original(path: &str) -> Result<(), CalmarError> {
+    let original = match ORIGINALS.lock().unwrap().get(path) {
+        Some(original) => original.clone(),
+        None => return Ok(()),
+    };
+    let copy = format!("{path}.pre-migration");
+    if let Err(e) = std::fs::write(&copy, original) {
+        return Err(CalmarError::WriteFile { e });
+    }
+    ORIGINALS.lock().unwrap().remove(path);
+    success!(
+        "Upgraded {path} to file format version {SCHEMA_VERSION}. The original is kept in {copy}"
+    );
+    Ok(())
+}
+
+/// Inserts `defaults` missing from `object`.
+fn fill(object: &mut Value, defaults: &[(&str, Value)]) {
+    if let Some(map) = object.as_object_mut() {
+        for (key, default) in defaults {
+            if !map.contains_key(*key) {
+                map.insert(key.to_string(), default.clone());
+            }
+        }
+    }
+}
+
+/// Applies `f` to every object in the array under `key`.
+fn for_each_in(document: &mut Value, key: &str, f: impl Fn(&mut Value)) {
+    if let Some(items) = document.get_mut(key).and_then(|v| v.as_array_mut()) {
+        items.iter_mut().for_each(f);
+    }
+}
+
+// Calendars from before version 1 may lack deadlines and the fields added to events over time.
+fn unversioned_calendar(document: &mut Value) {
+    fill(
+        document,
+        &[
+            ("name", json!("")),
+            ("events", json!([])),
+            ("deadlines", json!([])),
+        ],
+    );
+    for_each_in(document, "events", |event| {
+        fill(
+            event,
+            &[
+                ("repeat", json!(0)),
+                ("priority", json!(0)),
+                ("difficulty", json!(0)),
+                ("exceptions", json!([])),
+            ],
+        )
+    });
+    for_each_in(document, "deadlines", |deadline| fill(deadline, &[("priority", json!(0))]));
+}
+
+fn unversioned_index(document: &mut Value) {
+    fill(document, &[("calendars", json!([]))]);
+    for_each_in(document, "calendars", |reference| fill(reference, &[("active", json!(false))]));
+}
+
+// Version 2 gives every event, deadline and calendar an ID.
+// It is derived from the item and its position, so that the IDs stay the same
+// each time a file that has not been saved since is upgraded.
+fn add_ids(document: &mut Value, key: &str) {
+    if let Some(items) = document.get_mut(key).and_then(|v| v.as_array_mut()) {
+        for (position, item) in items.iter_mut().enumerate() {
+            let mut hasher = DefaultHasher::new();
+            (key, position, item.to_string()).hash(&mut hasher);
+            fill(item, &[("id", json!(format!("{:016x}", hasher.finish())))]);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use crate::cal::timezone::{from_wall_clock, to_wall_clock};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
+use chrono_tz::Tz;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// Describes when an event repeats.
+///
+/// `Interval` is the original calmar repetition, a fixed amount of time between occurrences.
+/// It is stored as a number of seconds, so calendar files without rules stay readable
+/// by older versions. A zero interval means that the event does not repeat.
+/// Intervals of whole days are counted in wall clock time, so that an event keeps its time
+/// of day across daylight saving changes. Shorter intervals are counted in elapsed time.
+///
+/// `Rule` repeats on calendar dates, for example every month on the 15th.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "RecurrenceRepr", into = "RecurrenceRepr")]
+pub enum Recurrence {
+    Interval(Duration),
+    Rule(RecurrenceRule),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RecurrenceRepr {
+    Seconds(i64),
+    Rule(RecurrenceRule),
+}
+
+impl From<RecurrenceRepr> for Recurrence {
+    fn from(r: RecurrenceRepr) -> Self {
+        match r {
+            RecurrenceRepr::Seconds(s) => Recurrence::Interval(Duration::seconds(s)),
+            RecurrenceRepr::Rule(rule) => Recurrence::Rule(rule),
+        }
+    }
+}
+
+impl From<Recurrence> for RecurrenceRepr {
+    fn from(r: Recurrence) -> Self {
+        match r {
+            Recurrence::Interval(d) => RecurrenceRepr::Seconds(d.num_seconds()),
+            Recurrence::Rule(rule) => RecurrenceRepr::Rule(rule),
+        }
+    }
+}
+
+impl From<Duration> for Recurrence {
+    fn from(d: Duration) -> Self {
+        Recurrence::Interval(d)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The `n`th given weekday of a month. Negative values count from the end of the month,
+/// so `{ n: -1, weekday: Fri }` is the last Friday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NthWeekday {
+    pub n: i8,
+    pub weekday: Weekday,
+}
+
+/// A calendar-aware repetition rule, modelled after a subset of the iCalendar RRULE.
+/// # Fields
+/// `frequency`, `interval`: the rule applies to every `interval`th day, week, month or year
+/// counted from the start of the event.
+/// `by_weekday`: days of the week the event happens on. Weekly and daily rules only.
+/// `by_month_day`: days of the month. Negative values count from the end of the month.
+/// `by_nth_weekday`: weekdays of the month such as the first Monday.
+/// `clamp_month_end`: a day missing in a shorter month is moved to the last day of that month
+/// instead of being skipped.
+///
+/// Monthly and yearly rules without any days repeat on the day of the month the event starts on.
+/// Yearly rules repeat in the month the event starts in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub by_weekday: Vec<Weekday>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub by_month_day: Vec<i8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub by_nth_weekday: Vec<NthWeekday>,
+    #[serde(default)]
+    pub clamp_month_end: bool,
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Recurrence::Interval(d) => write!(f, "{d}"),
+            Recurrence::Rule(rule) => write!(f, "{rule}"),
+        }
+    }
+}
+
+impl Display for RecurrenceRule {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let weekdays = [
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ];
+        if self.frequency == Frequency::Daily
+            && self.interval == 1
+            && self.by_weekday.len() == 5
+            && weekdays.iter().all(|d| self.by_weekday.contains(d))
+        {
+            return write!(f, "every weekday");
+        }
+
+        let unit = match self.frequency {
+            Frequency::Daily => "day",
+            Frequency::Weekly => "week",
+            Frequency::Monthly => "month",
+            Frequency::Yearly => "year",
+        };
+        match self.interval {
+            1 => write!(f, "every {unit}")?,
+            n => write!(f, "every {n} {unit}s")?,
+        }
+
+        let mut days: Vec<String> = self.by_weekday.iter().map(|d| d.to_string()).collect();
+        days.extend(self.by_month_day.iter().map(|d| match d {
+            -1 => "last day".to_string(),
+            d if *d < 0 => format!("{} day from the end", ordinal(-*d as i64)),
+            d => format!("day {d}"),
+        }));
+        days.extend(self.by_nth_weekday.iter().map(|d| match d.n {
+            -1 => format!("last {}", d.weekday),
+            n if n < 0 => format!("{} last {}", ordinal(-n as i64), d.weekday),
+            n => format!("{} {}", ordinal(n as i64), d.weekday),
+        }));
+        if !days.is_empty() {
+            write!(f, " on {}", days.join(", "))?;
+        }
+        if self.clamp_month_end {
+            write!(f, " (or the last day of shorter months)")?;
+        }
+        Ok(())
+    }
+}
+
+fn ordinal(n: i64) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (1, x) if x != 11 => "st",
+        (2, x) if x != 12 => "nd",
+        (3, x) if x != 13 => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+impl Recurrence {
+    /// No repetition.
+    pub fn none() -> Self {
+        Recurrence::Interval(Duration::zero())
+    }
+
+    pub fn is_none(&self) -> bool {
+        matches!(self, Recurrence::Interval(d) if d.is_zero())
+    }
+
+    /// Start times of all occurrences of an event starting at `start`, in chronological order.
+    /// Dates are computed on the wall clock of `zone`, or of local time if it is `None`.
+    /// The first item is always `start` itself. The iterator is infinite for repeating events.
+    pub fn occurrences(&self, start: DateTime<Local>, zone: Option<Tz>) -> Occurrences {
+        Occurrences {
+            recurrence: self.clone(),
+            start,
+            zone,
+            period: 0,
+            pending: vec![],
+            empty_periods: 0,
+            first: true,
+        }
+    }
+}
+
+/// Iterator over occurrence start times, returned by `Recurrence::occurrences`.
+pub struct Occurrences {
+    recurrence: Recurrence,
+    start: DateTime<Local>,
+    zone: Option<Tz>,
+    // index of the next period (day, week, month or year) to generate dates for
+    period: i64,
+    // dates of the current period, in reverse order
+    pending: Vec<NaiveDate>,
+    // number of consecutive periods without any date, used to stop rules that never match
+    empty_periods: u32,
+    first: bool,
+}
+
+/// A rule without a single occurrence in this many periods in a row is considered exhausted.
+const MAX_EMPTY_PERIODS: u32 = 1000;
+
+impl Iterator for Occurrences {
+    type Item = DateTime<Local>;
+
+    fn next(&mut self) -> Option<DateTime<Local>> {
+        // the start always counts as the first occurrence, even if a rule does not match it
+        if self.first {
+            self.first = false;
+            return Some(self.start);
+        }
+        let start_naive = to_wall_clock(self.start, self.zone);
+        let rule = match &self.recurrence {
+            // a negative interval would never reach a later date
+            Recurrence::Interval(d) if *d <= Duration::zero() => return None,
+            Recurrence::Interval(d) => {
+                // occurrences end where dates do instead of overflowing
+                self.period += 1;
+                let step = Duration::milliseconds(d.num_milliseconds().checked_mul(self.period)?);
+                if d.num_seconds() % (24 * 60 * 60) != 0 {
+                    return self.start.checked_add_signed(step);
+                }
+                return from_wall_clock(start_naive.checked_add_signed(step)?, self.zone);
+            }
+            Recurrence::Rule(rule) => rule.clone(),
+        };
+
+        loop {
+            while let Some(date) = self.pending.pop() {
+                if date <= start_naive.date() {
+                    continue;
+                }
+                if let Some(dt) = from_wall_clock(date.and_time(start_naive.time()), self.zone) {
+                    return Some(dt);
+                }
+            }
+            if self.empty_periods >= MAX_EMPTY_PERIODS {
+                return None;
+            }
+            let mut dates = rule.dates_in_period(start_naive.date(), self.period)?;
+            self.period += 1;
+            match dates.is_empty() {
+                true => self.empty_periods += 1,
+                false => self.empty_periods = 0,
+            }
+            dates.sort();
+            dates.dedup();
+            dates.reverse();
+            self.pending = dates;
+        }
+    }
+}
+
+impl RecurrenceRule {
+    pub fn new(frequency: Frequency, interval: u32) -> Self {
+        RecurrenceRule {
+            frequency,
+            interval: interval.max(1),
+            by_weekday: vec![],
+            by_month_day: vec![],
+            by_nth_weekday: vec![],
+            clamp_month_end: false,
+        }
+    }
+
+    /// Candidate dates in the `period`th period counted from the period containing `start`.
+    /// Returns `None` if the period lies beyond the range of dates.
+    fn dates_in_period(&self, start: NaiveDate, period: i64) -> Option<Vec<NaiveDate>> {
+        let step = period.checked_mul(self.interval.max(1) as i64)?;
+        let dates = match self.frequency {
+            Frequency::Daily => {
+                let date = start.checked_add_signed(Duration::days(step))?;
+                match self.by_weekday.is_empty() || self.by_weekday.contains(&date.weekday()) {
+                    true => vec![date],
+                    false => vec![],
+                }
+            }
+            Frequency::Weekly => {
+                let monday = (start
+                    - Duration::days(start.weekday().num_days_from_monday() as i64))
+                .checked_add_signed(Duration::weeks(step))?;
+                let weekdays = match self.by_weekday.is_empty() {
+                    true => vec![start.weekday()],
+                    false => self.by_weekday.clone(),
+                };
+                weekdays
+                    .iter()
+                    .filter_map(|d| {
+                        monday.checked_add_signed(Duration::days(d.num_days_from_monday() as i64))
+                    })
+                    .collect()
+            }
+            Frequency::Monthly | Frequency::Yearly => {
+                let months = match self.frequency {
+                    Frequency::Monthly => step,
+                    _ => step.checked_mul(12)?,
+                };
+                let months =
+                    (start.year() as i64 * 12 + start.month0() as i64).checked_add(months)?;
+                let (year, month) =
+                    (i32::try_from(months.div_euclid(12)).ok()?, months.rem_euclid(12) as u32 + 1);
+                // the month after has to exist as well to know the length of this one
+                NaiveDate::from_ymd_opt(year.checked_add(1)?, month, 1)?;
+                self.dates_in_month(year, month, start)
+            }
+        };
+        Some(dates)
+    }
+
+    fn dates_in_month(&self, year: i32, month: u32, start: NaiveDate) -> Vec<NaiveDate> {
+        let length = days_in_month(year, month);
+        let mut days: Vec<u32> = vec![];
+
+        let by_month_day = match self.by_month_day.is_empty() && self.by_nth_weekday.is_empty() {
+            true => vec![start.day() as i8],
+            false => self.by_month_day.clone(),
+        };
+        for d in by_month_day {
+            let day = match d {
+                d if d > 0 && d as u32 <= length => Some(d as u32),
+                d if d > 0 && self.clamp_month_end => Some(length),
+                d if d < 0 && (-d as u32) <= length => Some(length + 1 - (-d as u32)),
+                _ => None,
+            };
+            days.extend(day);
+        }
+        for nth in &self.by_nth_weekday {
+            days.extend(nth_weekday_of_month(year, month, *nth, length));
+        }
+
+        days.iter()
+            .filter_map(|d| NaiveDate::from_ymd_opt(year, month, *d))
+            .collect()
+    }
+}
+
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = match month {
+        12 => (year + 1, 1),
+        m => (year, m + 1),
+    };
+    NaiveDate::from_ymd(next_year, next_month, 1).pred().day()
+}
+
+fn nth_weekday_of_month(year: i32, month: u32, nth: NthWeekday, length: u32) -> Option<u32> {
+    let first = NaiveDate::from_ymd(year, month, 1).weekday();
+    let first_match =
+        1 + (7 + nth.weekday.num_days_from_monday() - first.num_days_from_monday()) % 7;
+    let day = match nth.n {
+        n if n > 0 => first_match as i64 + 7 * (n as i64 - 1),
+        n if n < 0 => {
+            let last_match = first_match + (length - first_match) / 7 * 7;
+            last_match as i64 - 7 * (-n as i64 - 1)
+        }
+        _ => return None,
+    };
+    match (1..=length as i64).contains(&day) {
+        true => Some(day as u32),
+        false => None,
+    }
+}
@@ -0,0 +1,123 @@
+use crate::cal::calmar_error::CalmarError;
+use lazy_static::lazy_static;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs::{File, OpenOptions},
+    hash::{Hash, Hasher},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+lazy_static! {
+    /// Hashes of file contents as they were last read or written by calmar, by path.
+    static ref LOADED: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+}
+
+fn hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A hidden file next to `path`, such as `.uni.json.lock` for `uni.json`.
+fn sibling(path: &str, extension: &str) -> PathBuf {
+    let path = Path::new(path);
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => String::new(),
+    };
+    path.with_file_name(format!(".{name}.{extension}"))
+}
+
+/// Opens the lock file of `path`. The file itself cannot be locked,
+/// because saving replaces it with a new one.
+fn lock_file(path: &str) -> Result<File, CalmarError> {
+    match OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(sibling(path, "lock"))
+    {
+        Ok(file) => Ok(file),
+        Err(e) => Err(CalmarError::LockFile { e }),
+    }
+}
+
+/// Returns the contents of the file under `path`, read while no other calmar is writing to it.
+/// The contents are remembered, so that `write_atomic` can tell if the file changed since.
+pub fn read(path: &str) -> Result<String, CalmarError> {
+    if !Path::new(path).exists() {
+        return match std::fs::read_to_string(path) {
+            Ok(content) => Ok(content),
+            Err(e) => Err(CalmarError::ReadFile { e }),
+        };
+    }
+    let lock = lock_file(path)?;
+    if let Err(e) = lock.lock_shared() {
+        return Err(CalmarError::LockFile { e });
+    }
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => return Err(CalmarError::ReadFile { e }),
+    };
+    LOADED
+        .lock()
+        .unwrap()
+        .insert(path.to_string(), hash(&content));
+    Ok(content)
+}
+
+/// Replaces the contents of the file under `path` with `content`.
+///
+/// The new contents are written to a temporary file, flushed to disk and moved in place of the old file,
+/// so that the file is never left half written.
+/// If `check` is set and the file changed since calmar last read or wrote it,
+/// nothing is written and `CalmarError::ChangedOnDisk` is returned.
+pub fn write_atomic(path: &str, content: &str, check: bool) -> Result<(), CalmarError> {
+    let lock = lock_file(path)?;
+    if let Err(e) = lock.lock() {
+        return Err(CalmarError::LockFile { e });
+    }
+
+    if check {
+        if let Some(loaded) = LOADED.lock().unwrap().get(path) {
+            let current = std::fs::read_to_string(path).ok();
+            if current.map(|c| hash(&c)) != Some(*loaded) {
+                return Err(CalmarError::ChangedOnDisk {
+                    path: path.to_string(),
+                });
+            }
+        }
+    }
+
+    let temp_path = sibling(path, "tmp");
+    let mut temp = match File::create(&temp_path) {
+        Ok(file) => file,
+        Err(e) => return Err(CalmarError::CreateFile { e }),
+    };
+    if let Err(e) = temp
+        .write_all(content.as_bytes())
+        .and_then(|_| temp.sync_all())
+    {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(CalmarError::WriteFile { e });
+    }
+    if let Err(e) = std::fs::rename(&temp_path, path) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(CalmarError::WriteFile { e });
+    }
+    // make the rename itself durable
+    if let Some(dir) = Path::new(path).parent() {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    LOADED
+        .lock()
+        .unwrap()
+        .insert(path.to_string(), hash(content));
+    Ok(())
+}
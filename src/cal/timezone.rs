@@ -0,0 +1,29 @@
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+
+/// Parses an IANA time zone name such as `Europe/Warsaw`, ignoring case.
+pub fn parse_timezone(name: &str) -> Option<Tz> {
+    Tz::from_str_insensitive(name.trim()).ok()
+}
+
+/// Wall clock time of `t` in `zone`, or in local time if `zone` is `None`.
+pub fn to_wall_clock(t: DateTime<Local>, zone: Option<Tz>) -> NaiveDateTime {
+    match zone {
+        Some(tz) => t.with_timezone(&tz).naive_local(),
+        None => t.naive_local(),
+    }
+}
+
+/// The moment the wall clock in `zone` shows `naive`, or the local wall clock if `zone` is `None`.
+/// An ambiguous time resolves to its first occurrence,
+/// a time skipped by a daylight saving change is moved an hour forward.
+pub fn from_wall_clock(naive: NaiveDateTime, zone: Option<Tz>) -> Option<DateTime<Local>> {
+    let resolve = |naive: &NaiveDateTime| match zone {
+        Some(tz) => tz
+            .from_local_datetime(naive)
+            .earliest()
+            .map(|t| t.with_timezone(&Local)),
+        None => Local.from_local_datetime(naive).earliest(),
+    };
+    resolve(&naive).or_else(|| resolve(&naive.checked_add_signed(Duration::hours(1))?))
+}
@@ -0,0 +1,74 @@
+use crate::{
+    cli::{
+        functions::{check_calmar_dir, check_config},
+        help::{print_usage, print_version},
+        messages::take_failed,
+        parser::dispatch,
+        repl::set_command_line_mode,
+        script::{read_script, run as run_script},
+    },
+    error, CONFIG,
+};
+
+/// Handle command-line arguments passed to calmar and return the exit status.
+///
+/// Options are handled here, anything else is treated as a single command
+/// with its arguments and run through `parser::dispatch`.
+pub fn run(args: &[String]) -> i32 {
+    match args[0].as_str() {
+        "--help" | "-h" => {
+            print_usage();
+            0
+        }
+        "--version" | "-V" => {
+            print_version();
+            0
+        }
+        "--script" | "-s" => script(&args[1..]),
+        option if option.starts_with('-') => {
+            error!("Unknown option: {option}\nTry \"calmar --help\" for more information.");
+            2
+        }
+        _ => {
+            set_command_line_mode();
+            check_calmar_dir();
+            check_config();
+            let split_input: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+            dispatch(&split_input);
+            match take_failed() {
+                true => 1,
+                false => 0,
+            }
+        }
+    }
+}
+
+/// Handle `calmar --script [file] [--continue-on-error]`.
+/// Without a file or with "-", the script is read from stdin.
+fn script(args: &[String]) -> i32 {
+    let mut path = None;
+    let mut continue_on_error = false;
+    for arg in args {
+        match arg.as_str() {
+            "--continue-on-error" | "-c" => continue_on_error = true,
+            "-" => path = None,
+            option if option.starts_with('-') => {
+                error!("Unknown option: {option}\nTry \"calmar --help\" for more information.");
+                return 2;
+            }
+            p => path = Some(p),
+        }
+    }
+
+    set_command_line_mode();
+    check_calmar_dir();
+    check_config();
+    let content = match read_script(path) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("Failed to read script {}.\n{e}", path.unwrap_or("from stdin"));
+            return 1;
+        }
+    };
+    run_script(&content, path.unwrap_or("stdin"), continue_on_error)
+}
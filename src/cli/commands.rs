@@ -1,26 +1,52 @@
 use crate::{
     active_calendar, active_calendar_reference,
     cal::{
-        calendar_index::CalendarIndex, calmar_error::CalmarError, calmar_trait::CalendarDataType,
-        deadline::Deadline, event::Event,
+        calendar::Calendar,
+        calendar_index::CalendarIndex,
+        calmar_error::CalmarError,
+        calmar_trait::CalendarDataType,
+        deadline::{Deadline, DeadlineStatus},
+        event::Event,
+        ics::{deadline_uid, event_uid, from_ics, to_ics},
+        recurrence::days_in_month,
+        timezone::from_wall_clock,
     },
     calendar_index,
     cli::{
         config::Config,
-        display::display_events,
+        display::{
+            display_conflicts, display_deadline_details, display_event_details, display_events,
+            display_found, display_free_slots, display_month, display_week,
+        },
         functions::{
-            add_entry, closest_occurence_start, delete_entry, edit_calendar, edit_event,
-            generate_until, get_new_calendar_reference, get_new_event,
+            add_entry, agenda_calendars, change_deadlines, check_new_conflicts,
+            closest_occurence_start, delete_entry, edit_calendar, edit_deadline_properties,
+            edit_event, find_all_by_keys, find_conflicts, find_free_slots, generate_agenda,
+            generate_until, get_new_calendar_reference, get_new_deadline, get_new_event,
+            get_new_event_at, named_or_active_calendar, occurrences_between, save_calendar,
+            save_index, span_end, WorkingHours,
+        },
+        getdata::{
+            get_valid_calendar_name, get_valid_event_name, parse_into_date, parse_into_duration,
+            parse_month,
         },
-        getdata::{get_valid_calendar_name, get_valid_event_name, parse_into_duration},
+        history::{restore, History},
         messages::print_err_msg,
-        repl::get_input,
-        util::{duration_fmt, get_now_even, round_to_full_day},
-        validator::{get_home_dir, validate_duration},
+        query::Query,
+        reminders::{
+            due_reminders, modification_times, notify, watched_paths, FiredReminders,
+            WATCH_INTERVAL,
+        },
+        repl::{can_prompt, get_input},
+        util::{
+            date_fmt, datetime_fmt, duration_fmt, get_now_even, month_format, month_layout,
+            round_to_full_day, time_layout,
+        },
+        validator::{get_home_dir, validate_date},
     },
-    error, success, warning, CONFIG,
+    error, fail, success, warning, CONFIG,
 };
-use chrono::{Duration, Local};
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
 use colored::Colorize;
 use std::{
     fs::OpenOptions,
@@ -33,7 +59,7 @@ use std::{
 use super::{
     display::colorize_deadline,
     functions::choose_struct_idx,
-    getdata::{get_date, get_priority, get_time},
+    getdata::{get_date, get_time},
     util::select_in_range,
 };
 
@@ -44,26 +70,34 @@ creates a JSON file with the given 'name' under $HOME/.calmar.
 If file named 'name' already exists, it asks the user for confirmation.
  */
 /// Create a new calendar and save it to the calendar index.
-pub fn cal(split_input: &Vec<&str>) {
+pub fn cal(split_input: &[&str]) {
     let mut index = calendar_index!();
 
     if split_input.len() == 1 {
-        let mut new_ref = get_new_calendar_reference(None);
+        let mut new_ref = match get_new_calendar_reference(None) {
+            Ok(r) => r,
+            Err(_) => return,
+        };
         if index.calendars().is_empty() {
             new_ref.set_active()
         }
         if let Err(e) = new_ref.create_file() {
             print_err_msg(e, new_ref.path());
         }
-        add_entry(&mut index, &new_ref);
+        if add_entry(&mut index, &new_ref).is_err() {
+            return;
+        }
         success!("Added {}", new_ref.name());
     } else {
         for n in &split_input[1..] {
             if n.trim().is_empty() {
-                warning!("Calendar name cannot be empty.");
+                fail!("Calendar name cannot be empty.");
                 continue;
             }
-            let mut new_ref = get_new_calendar_reference(Some(n.to_string()));
+            let mut new_ref = match get_new_calendar_reference(Some(n.to_string())) {
+                Ok(r) => r,
+                Err(_) => return,
+            };
             if index.calendars().is_empty() {
                 new_ref.set_active()
             }
@@ -71,82 +105,88 @@ pub fn cal(split_input: &Vec<&str>) {
                 print_err_msg(e, new_ref.path());
                 continue;
             }
-            add_entry(&mut index, &new_ref);
+            if add_entry(&mut index, &new_ref).is_err() {
+                return;
+            }
             success!("Added {}", new_ref.name());
         }
     }
-    if let Err(e) = index.save() {
+    if let Err(e) = save_index(&index) {
         print_err_msg(e, &CONFIG.index_path);
     };
 }
 
 /// Delete a calendar
-pub fn removecal(split_input: &Vec<&str>) {
+pub fn removecal(split_input: &[&str]) {
     let mut index = calendar_index!();
-    let names = index
-        .calendars()
-        .iter()
-        .map(|r| r.name())
-        .collect::<Vec<String>>();
 
-    if split_input.len() == 1 {
-        let name = get_valid_calendar_name();
-        delete_entry(&mut index, name.clone());
-        success!("Removed {name}");
-    } else {
-        split_input[1..].iter().for_each(|n| {
-            if names.contains(&n.to_string()) {
-                delete_entry(&mut index, n.to_string());
-                success!("Removed {n}");
-            } else {
-                warning!("No calendard named {n} found");
-            }
-        })
+    let keys = match split_input.len() {
+        1 => match get_valid_calendar_name() {
+            Ok(name) => vec![name],
+            Err(_) => return,
+        },
+        _ => split_input[1..].iter().map(|n| n.to_string()).collect(),
+    };
+    for key in keys {
+        if let Some(name) = delete_entry(&mut index, &key) {
+            success!("Removed {name}");
+        }
     }
-    if let Err(e) = index.save() {
+    if let Err(e) = save_index(&index) {
         print_err_msg(e, &CONFIG.index_path);
     }
 }
 
 /// Delete events from the active calendar
-pub fn remove(split_input: &Vec<&str>) {
+pub fn remove(split_input: &[&str]) {
     let mut active_calendar = active_calendar!();
     let path = active_calendar_reference!().path();
-    let names = active_calendar
-        .events()
-        .iter()
-        .map(|e| e.name())
-        .collect::<Vec<String>>();
 
-    if split_input.len() == 1 {
-        let name = get_valid_event_name();
-        active_calendar.events_mut().retain(|e| e.name() != name);
-        success!("Removed {name}");
-    } else {
-        split_input[1..].iter().for_each(|n| {
-            if !names.contains(&n.to_string()) {
-                warning!("No event named {n}")
-            } else {
-                success!("Removed {n}");
+    let keys = match split_input.len() {
+        1 => match get_valid_event_name() {
+            Ok(name) => vec![name],
+            Err(_) => return,
+        },
+        _ => split_input[1..].iter().map(|n| n.to_string()).collect(),
+    };
+    // every event named like a key is removed, but an ID has to identify a single one
+    let mut removed: Vec<usize> = vec![];
+    for key in keys {
+        let found = active_calendar.find_events(&key);
+        match found.first() {
+            None => {
+                fail!("No event named {key}");
             }
-        });
-        active_calendar
-            .events_mut()
-            .retain(|e| !split_input[1..].contains(&e.name().as_str()));
+            Some(i) if found.len() > 1 && active_calendar.events()[*i].name() != key => {
+                fail!("Several events have an ID starting with {key}. Give more of the ID");
+            }
+            Some(i) => {
+                success!("Removed {}", active_calendar.events()[*i].name());
+                removed.extend(found);
+            }
+        }
     }
-    if let Err(e) = active_calendar.save(&path) {
+    let mut i = 0;
+    active_calendar.events_mut().retain(|_| {
+        i += 1;
+        !removed.contains(&(i - 1))
+    });
+    if let Err(e) = save_calendar(&active_calendar, &path) {
         print_err_msg(e, &path)
     }
 }
 
 /// Change the active calednar
-pub fn set(split_input: &Vec<&str>) {
+pub fn set(split_input: &[&str]) {
     let mut index = calendar_index!();
     let name = match split_input.len() {
-        1 => get_valid_event_name(),
+        1 => match get_valid_event_name() {
+            Ok(name) => name,
+            Err(_) => return,
+        },
         2 => split_input[1].to_string(),
         _ => {
-            warning!(
+            fail!(
                 "set: Too many arguments provided. Expected: 1 or 2. Got: {}",
                 split_input.len() - 1
             );
@@ -154,28 +194,31 @@ pub fn set(split_input: &Vec<&str>) {
         }
     };
 
-    match index.num_named(&name) {
-        0 => {
-            warning!("No calendars named {name}");
+    let reference = match index.find(&name)[..] {
+        [] => {
+            fail!("No calendars named {name}");
             return;
         }
-        1 => {}
-        x => {
-            warning!("{x} calendars named {name}. There must be only one");
+        [i] => index.calendars()[i].clone(),
+        ref x => {
+            fail!(
+                "{} calendars named {name}. There must be only one, or use the ID of one",
+                x.len()
+            );
             return;
         }
-    }
+    };
 
     match index.number_of_active_calendars() {
         0 | 1 => {
-            index.set_active(name.clone());
-            success!("Set {name} as active");
-            if let Err(e) = index.save() {
+            index.set_active(reference.id());
+            success!("Set {} as active", reference.name());
+            if let Err(e) = save_index(&index) {
                 print_err_msg(e, &CONFIG.index_path);
             };
         }
         _ => {
-            warning!("More than one calendar is set as active. Please correct this and retry.");
+            fail!("More than one calendar is set as active. Please correct this and retry.");
         }
     }
 }
@@ -184,26 +227,39 @@ pub fn set(split_input: &Vec<&str>) {
 Call event creation with name given optionally
  */
 /// Create a new event and save it to the active calednar.
-pub fn add(split_input: &Vec<&str>) {
+pub fn add(split_input: &[&str]) {
     let mut active_calendar = active_calendar!();
+    let before = active_calendar.clone();
+    let reference = active_calendar_reference!();
     if split_input.len() == 1 {
-        let new_event = get_new_event(None);
+        let mut new_event = match get_new_event(None) {
+            Ok(e) => e,
+            Err(_) => return,
+        };
+        new_event.set_timezone(reference.timezone());
         active_calendar.add_event(new_event.clone());
         success!("Added {}", new_event.name());
     } else {
-        split_input[1..].iter().for_each(|n| {
+        for n in &split_input[1..] {
             // inform about what is being currently added when there are at least 2 event names passed
             if split_input.len() > 2 {
                 success!("Adding {n}");
             }
-            let new_event = get_new_event(Some(n.to_string()));
+            let mut new_event = match get_new_event(Some(n.to_string())) {
+                Ok(e) => e,
+                Err(_) => return,
+            };
+            new_event.set_timezone(reference.timezone());
             active_calendar.add_event(new_event.clone());
             success!("Added {}", new_event.name());
-        })
+        }
     }
 
-    let path = active_calendar_reference!().path();
-    if let Err(e) = active_calendar.save(&path) {
+    if !check_new_conflicts(&before, &active_calendar) {
+        return;
+    }
+    let path = reference.path();
+    if let Err(e) = save_calendar(&active_calendar, &path) {
         print_err_msg(e, &path)
     }
 }
@@ -221,33 +277,60 @@ pub fn edit(split_input: &[&str]) {
 /// Display events in the active calendar
 pub fn raw(split_input: &[&str]) {
     let active_calendar = active_calendar!();
-    let names: Vec<String> = active_calendar.events().iter().map(|e| e.name()).collect();
-    split_input[1..].iter().for_each(|a| {
-        if !names.contains(&a.to_string()) {
-            warning!("No event named {a}")
-        }
-    });
+    let found = find_all_by_keys(
+        active_calendar.events().iter().map(|e| (e.name(), e.id())),
+        &split_input[1..],
+        "event",
+    );
     active_calendar
         .events()
         .iter()
-        .filter(|e| {
-            if split_input.len() != 1 {
-                split_input[1..].contains(&e.name().as_str())
-            } else {
-                true
+        .enumerate()
+        .filter(|(i, _)| split_input.len() == 1 || found.contains(i))
+        .for_each(|(_, e)| println!("{e}"))
+}
+
+/// Display everything about the given events and deadlines in the active calendar,
+/// including when the events take place next
+pub fn show(split_input: &[&str]) {
+    if split_input.len() == 1 {
+        fail!("show: Invalid number of arguments. Expected: 1 or more. Got: 0");
+        return;
+    }
+    let active_calendar = active_calendar!();
+    for (n, key) in split_input[1..].iter().enumerate() {
+        if n > 0 {
+            println!();
+        }
+        match choose_struct_idx(active_calendar.events().to_vec(), "Select an event to show", key) {
+            Ok(Some(i)) => {
+                display_event_details(&active_calendar.name(), &active_calendar.events()[i])
             }
-        })
-        .for_each(|e| println!("{e}"))
+            Ok(None) => match choose_struct_idx(
+                active_calendar.deadlines().to_vec(),
+                "Select a deadline to show",
+                key,
+            ) {
+                Ok(Some(i)) => display_deadline_details(
+                    &active_calendar.name(),
+                    &active_calendar.deadlines()[i],
+                ),
+                Ok(None) => fail!("No event or deadline named {key}"),
+                Err(_) => return,
+            },
+            Err(_) => return,
+        }
+    }
 }
 
 /// Clear the screen
-pub fn clear(split_input: &Vec<&str>) {
+pub fn clear(split_input: &[&str]) {
     match split_input.len() {
         1 => {
             println!("\x1b[H\x1b[J");
         }
         _ => {
-            warning!(
+            fail!(
                 "clear: Invalid number of arguments. Expected: 0. Got: {}",
                 split_input.len() - 1
             );
@@ -256,35 +339,29 @@ pub fn clear(split_input: &Vec<&str>) {
 }
 
 // List calendars and their properties
-pub fn listcal(split_input: &Vec<&str>) {
+pub fn listcal(split_input: &[&str]) {
     let index = calendar_index!();
-    let names: Vec<String> = index.calendars().iter().map(|r| r.name()).collect();
-    split_input[1..].iter().for_each(|a| {
-        if !names.contains(&a.to_string()) {
-            warning!("No calendar named {a}");
-        }
-    });
+    let found = find_all_by_keys(
+        index.calendars().iter().map(|r| (r.name(), r.id())),
+        &split_input[1..],
+        "calendar",
+    );
 
     index
         .calendars()
         .iter()
-        .filter(|r| {
-            if split_input.len().ne(&1) {
-                split_input[1..].contains(&r.name().as_str())
-            } else {
-                true
-            }
-        })
-        .for_each(|r| println!("{r}"));
+        .enumerate()
+        .filter(|(i, _)| split_input.len() == 1 || found.contains(i))
+        .for_each(|(_, r)| println!("{r}"));
 }
 
-pub fn sort(split_input: &Vec<&str>) {
+pub fn sort(split_input: &[&str]) {
     let index = calendar_index!();
     let mut active_calendar = active_calendar!(index);
     let active_calendar_reference = active_calendar_reference!(index);
 
     if !(1..=3).contains(&split_input.len()) {
-        warning!(
+        fail!(
             "sort: Invalid number of arguments. Expected: 0 or 1. Got: {}",
             split_input.len() - 1
         );
@@ -306,7 +383,7 @@ pub fn sort(split_input: &Vec<&str>) {
                 "priority" => events_std.sort_by_key(|e| e.priority()),
                 "difficulty" => events_std.sort_by_key(|e| e.difficulty()),
                 _ => {
-                    warning!("sort: {} is not a valid key.", { split_input[1].trim() });
+                    fail!("sort: {} is not a valid key.", { split_input[1].trim() });
                     return;
                 }
             }
@@ -314,122 +391,353 @@ pub fn sort(split_input: &Vec<&str>) {
         }
     }
 
-    match split_input.get(2) {
-        Some(arg) => match arg.trim() {
+    if let Some(arg) = split_input.get(2) {
+        match arg.trim() {
             "ascending" | "asc" | "a" => {}
             "descending" | "desc" | "d" | "rev" | "reverse" => events_std.reverse(),
             _ => {
-                warning!("sort: {} is not a valid ordering argument", split_input[2]);
+                fail!("sort: {} is not a valid ordering argument", split_input[2]);
                 return;
             }
-        },
-        None => {}
+        }
     }
 
     active_calendar.set_events(events_std);
-    if let Err(e) = active_calendar.save(&active_calendar_reference.path()) {
+    if let Err(e) = save_calendar(&active_calendar, &active_calendar_reference.path()) {
         print_err_msg(e, active_calendar_reference.path());
     }
 }
 
-pub fn duration(split_input: &Vec<&str>) {
+pub fn duration(split_input: &[&str]) {
     let active_calendar = active_calendar!();
-    let names: Vec<String> = active_calendar.events().iter().map(|e| e.name()).collect();
-    split_input[1..].iter().for_each(|a| {
-        if !names.contains(&a.to_string()) {
-            warning!("No event named {a}")
-        }
-    });
-    let name_arr = match split_input.len() {
-        1 => {
-            vec![get_valid_event_name()]
-        }
-        _ => split_input[1..].iter().map(|a| a.to_string()).collect(),
+    let name = match split_input.len() {
+        1 => match get_valid_event_name() {
+            Ok(name) => name,
+            Err(_) => return,
+        },
+        _ => String::new(),
+    };
+    let keys = match split_input.len() {
+        1 => vec![name.as_str()],
+        _ => split_input[1..].to_vec(),
     };
+    let found = find_all_by_keys(
+        active_calendar.events().iter().map(|e| (e.name(), e.id())),
+        &keys,
+        "event",
+    );
 
-    active_calendar.events().iter().for_each(|e| {
-        if name_arr.contains(&e.name()) {
-            println!("Duration of {}: {}", e.name(), duration_fmt(e.duration()))
-        }
-    })
+    active_calendar
+        .events()
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| found.contains(i))
+        .for_each(|(_, e)| println!("Duration of {}: {}", e.name(), duration_fmt(e.duration())))
 }
 
-pub fn until(split_input: &Vec<&str>) {
-    let active_calendar = active_calendar!();
-    let names: Vec<String> = active_calendar.events().iter().map(|e| e.name()).collect();
-    split_input[1..].iter().for_each(|a| {
-        if !names.contains(&a.to_string()) {
-            warning!("No event named {a}")
-        }
-    });
-
-    let name_arr = match split_input.len() {
-        1 => {
-            vec![get_valid_event_name()]
-        }
-        _ => split_input[1..].iter().map(|a| a.to_string()).collect(),
+pub fn until(split_input: &[&str]) {
+    let index = calendar_index!();
+    let (calendars, split_input) = match agenda_calendars(&index, split_input) {
+        Some(c) => c,
+        None => return,
     };
-
-    active_calendar.events().iter().for_each(|e| {
-        if name_arr.contains(&e.name()) {
+    let events: Vec<(String, Event)> = calendars
+        .iter()
+        .flat_map(|(name, c)| c.events().iter().map(|e| (name.clone(), e.clone())))
+        .collect();
+    let name = match split_input.len() {
+        1 => match get_valid_event_name() {
+            Ok(name) => name,
+            Err(_) => return,
+        },
+        _ => String::new(),
+    };
+    let keys = match split_input.len() {
+        1 => vec![name.as_str()],
+        _ => split_input[1..].to_vec(),
+    };
+    let found = find_all_by_keys(events.iter().map(|(_, e)| (e.name(), e.id())), &keys, "event");
+
+    events.iter().enumerate().for_each(|(i, (calendar, e))| {
+        if found.contains(&i) {
+            // name the calendar when events of several calendars might be named alike
+            let name = match calendars.len() {
+                1 => e.name(),
+                _ => format!("{} ({calendar})", e.name()),
+            };
             let now = Local::now();
             if now < e.start() {
-                println!("Until {}: {}", e.name(), duration_fmt(e.start() - now))
+                println!("Until {name}: {}", duration_fmt(e.start() - now))
             } else {
-                println!("{} started {} ago", e.name(), duration_fmt((e.start() - now).neg()))
+                println!("{name} started {} ago", duration_fmt((e.start() - now).neg()))
             }
         }
     });
+}
 
-    for name in &name_arr {
-        if !active_calendar
-            .events()
-            .iter()
-            .map(|e| e.name())
-            .any(|x| x == *name)
-        {
-            warning!("No event named {}", name)
-        }
+/// Generate and view
+pub fn list(split_input: &[&str]) {
+    let index = calendar_index!();
+    let (calendars, split_input) = match agenda_calendars(&index, split_input) {
+        Some(c) => c,
+        None => return,
+    };
+
+    if split_input.len() > 2 {
+        fail!(
+            "list: Invalid number of arguments. Expected: 0 or 1. Got: {}",
+            split_input.len() - 1
+        );
+        return;
     }
+    let end_date = match span_end(split_input.get(1).copied()) {
+        Some(end) => end,
+        None => return,
+    };
+
+    display_events(generate_agenda(&calendars, Local::now(), end_date));
 }
 
-/// Generate and view
-pub fn list(split_input: &Vec<&str>) {
-    let mut span = parse_into_duration(&CONFIG.default_calendar_span);
+/// Search events and deadlines in every calendar, see `Query` for the filters
+pub fn find(split_input: &[&str]) {
+    let query = match Query::parse(&split_input[1..]) {
+        Ok(q) => q,
+        Err(e) => {
+            fail!("find: {e}");
+            return;
+        }
+    };
+    let index = calendar_index!();
+    if let Some(key) = query.calendars().iter().find(|k| index.find(k).is_empty()) {
+        fail!("No calendar named {key}");
+        return;
+    }
 
-    let active_calendar = active_calendar!();
+    let range = query.range();
+    let mut events: Vec<(String, Event)> = vec![];
+    let mut deadlines: Vec<(String, Deadline)> = vec![];
+    for reference in index
+        .calendars()
+        .iter()
+        .filter(|r| query.includes_calendar(r))
+    {
+        let calendar = match Calendar::get(&reference.path()) {
+            Ok(c) => c,
+            Err(e) => {
+                print_err_msg(e, reference.path());
+                continue;
+            }
+        };
+        for event in calendar.events().iter().filter(|e| query.matches_event(e)) {
+            match range {
+                Some((start, end)) => events.extend(
+                    occurrences_between(event, start, end)
+                        .into_iter()
+                        .map(|e| (reference.name(), e)),
+                ),
+                None => events.push((reference.name(), event.clone())),
+            }
+        }
+        deadlines.extend(
+            calendar
+                .deadlines()
+                .iter()
+                .filter(|d| query.matches_deadline(d))
+                .map(|d| (reference.name(), d.clone())),
+        );
+    }
 
-    match split_input.len() {
-        1 => {}
-        2 => {
-            if validate_duration(split_input[1]) {
-                span = parse_into_duration(split_input[1]);
-            } else {
-                warning!("{} is not a valid duration input.", split_input[1]);
+    if events.is_empty() && deadlines.is_empty() {
+        println!("No events or deadlines match the query.");
+        return;
+    }
+    events.sort_by(|(_, a), (_, b)| a.cmp(b));
+    deadlines.sort_by_key(|(_, d)| d.date());
+    display_found(&events, &deadlines);
+}
+
+/// Draw a grid of a month with days of events and deadlines marked
+pub fn month(split_input: &[&str]) {
+    let index = calendar_index!();
+    let (calendars, split_input) = match agenda_calendars(&index, split_input) {
+        Some(c) => c,
+        None => return,
+    };
+    if split_input.len() > 2 {
+        fail!(
+            "month: Invalid number of arguments. Expected: 0 or 1. Got: {}",
+            split_input.len() - 1
+        );
+        return;
+    }
+    let (year, month) = match split_input.get(1) {
+        Some(arg) => match parse_month(arg) {
+            Some(m) => m,
+            None => {
+                fail!(
+                    "{arg} is not a valid month. Expected {}, such as {}",
+                    month_format(),
+                    Local::now().format(&month_layout())
+                );
                 return;
             }
+        },
+        None => (Local::now().year(), Local::now().month()),
+    };
+
+    let first = NaiveDate::from_ymd(year, month, 1);
+    let next = first + Duration::days(days_in_month(year, month) as i64);
+    let (start, end) = match (
+        from_wall_clock(first.and_hms(0, 0, 0), None),
+        from_wall_clock(next.and_hms(0, 0, 0), None),
+    ) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return,
+    };
+    let events = generate_agenda(&calendars, start, end);
+    let deadlines: Vec<Deadline> = calendars
+        .iter()
+        .flat_map(|(_, c)| c.deadlines().to_vec())
+        .collect();
+    display_month(year, month, &events, &deadlines);
+}
+
+/// Draw a timetable of the week containing a date
+pub fn week(split_input: &[&str]) {
+    let index = calendar_index!();
+    let (calendars, split_input) = match agenda_calendars(&index, split_input) {
+        Some(c) => c,
+        None => return,
+    };
+    if split_input.len() > 2 {
+        fail!(
+            "week: Invalid number of arguments. Expected: 0 or 1. Got: {}",
+            split_input.len() - 1
+        );
+        return;
+    }
+    let date = match split_input.get(1) {
+        Some(arg) if !validate_date(arg) => {
+            fail!("{arg} is not a valid date.");
+            return;
         }
-        _ => warning!(
-            "list: Invalid number of arguments. Expected: 0 or 1. Got: {}",
+        Some(arg) => parse_into_date(arg).naive_local(),
+        None => Local::now().date_naive(),
+    };
+
+    let first_weekday: Weekday = CONFIG.first_weekday.parse().unwrap_or(Weekday::Mon);
+    let days_since_first =
+        (7 + date.weekday().num_days_from_monday() - first_weekday.num_days_from_monday()) % 7;
+    let first_day = date - Duration::days(days_since_first as i64);
+    let (start, end) = match (
+        from_wall_clock(first_day.and_hms(0, 0, 0), None),
+        from_wall_clock((first_day + Duration::days(7)).and_hms(0, 0, 0), None),
+    ) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return,
+    };
+    display_week(first_day, &generate_agenda(&calendars, start, end));
+}
+
+/// Report all pairs of overlapping events in the given span
+pub fn conflicts(split_input: &[&str]) {
+    let active_calendar = active_calendar!();
+
+    if split_input.len() > 2 {
+        fail!(
+            "conflicts: Invalid number of arguments. Expected: 0 or 1. Got: {}",
             split_input.len() - 1
-        ),
+        );
+        return;
     }
+    let end_date = match span_end(split_input.get(1).copied()) {
+        Some(end) => end,
+        None => return,
+    };
 
-    let re_days = regex::Regex::new("^[0-9]+(d| +d|days| +days)$").unwrap();
+    let conflicts = find_conflicts(&generate_until(&active_calendar, end_date));
+    if conflicts.is_empty() {
+        success!("No overlapping events");
+        return;
+    }
+    display_conflicts(&conflicts);
+    warning!("Found {} overlapping pair(s) of events", conflicts.len());
+}
 
-    // if the user typed something like '3d', round the duration
-    // to full days for convenience
-    let mut end_date = get_now_even() + span;
-    if split_input.len() == 2 && re_days.is_match(split_input[1]) {
-        end_date = round_to_full_day(end_date);
+/// List free periods of at least the given length within working hours
+/// and optionally add an event in one of them
+pub fn free(split_input: &[&str]) {
+    if !(2..=3).contains(&split_input.len()) {
+        fail!(
+            "free: Invalid number of arguments. Expected: 1 or 2. Got: {}",
+            split_input.len() - 1
+        );
+        return;
     }
+    let length = match parse_into_duration(split_input[1]) {
+        Ok(length) if !length.is_zero() => length,
+        Ok(_) => {
+            fail!("free: The length of free time has to be longer than 0.");
+            return;
+        }
+        Err(e) => {
+            fail!("{} is not a valid duration input. {e}.", split_input[1]);
+            return;
+        }
+    };
+    let end_date = match span_end(split_input.get(2).copied()) {
+        Some(end) => end,
+        None => return,
+    };
+    let index = calendar_index!();
+    let mut active_calendar = active_calendar!(index);
+    let reference = active_calendar_reference!(index);
 
     let events = generate_until(&active_calendar, end_date);
-    display_events(events);
+    let slots =
+        find_free_slots(&events, get_now_even(), end_date, length, &WorkingHours::from_config());
+    if slots.is_empty() {
+        warning!("No free time of at least {} found", duration_fmt(length));
+        return;
+    }
+    display_free_slots(&slots);
+
+    if !can_prompt() {
+        return;
+    }
+    let prompt = format!("Add an event in a slot [1-{}], leave empty to skip: ", slots.len());
+    let slot = loop {
+        let input = match get_input(&prompt, None) {
+            Ok(input) => input,
+            Err(_) => return,
+        };
+        if input.trim().is_empty() {
+            return;
+        }
+        match input.trim().parse::<usize>() {
+            Ok(n) if (1..=slots.len()).contains(&n) => break slots[n - 1],
+            _ => warning!("Invalid input. Enter a number from the range or nothing"),
+        }
+    };
+
+    let before = active_calendar.clone();
+    let mut new_event = match get_new_event_at(slot.0, slot.0 + length) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    new_event.set_timezone(reference.timezone());
+    active_calendar.add_event(new_event.clone());
+    success!("Added {}", new_event.name());
+    if !check_new_conflicts(&before, &active_calendar) {
+        return;
+    }
+    if let Err(e) = save_calendar(&active_calendar, &reference.path()) {
+        print_err_msg(e, reference.path())
+    }
 }
 
 /// Generate, output to a file
-pub fn write(split_input: &Vec<&str>) {
+pub fn write(split_input: &[&str]) {
     // write filename - default span
     // write 10h filename
 
@@ -438,15 +746,23 @@ pub fn write(split_input: &Vec<&str>) {
 
     match split_input.len() {
         2 => {
-            span = parse_into_duration(&CONFIG.default_calendar_span);
+            // verified by `check_config` at startup
+            span = parse_into_duration(&CONFIG.default_calendar_span)
+                .unwrap_or_else(|_| Duration::zero());
             filename = split_input[1].to_string();
         }
         3 => {
-            span = parse_into_duration(split_input[1]);
+            span = match parse_into_duration(split_input[1]) {
+                Ok(span) => span,
+                Err(e) => {
+                    fail!("{} is not a valid duration input. {e}.", split_input[1]);
+                    return;
+                }
+            };
             filename = split_input[2].to_string();
         }
         _ => {
-            warning!(
+            fail!(
                 "write: Invalid number of arguments. Expected: 1 or 2. Got: {}",
                 split_input.len() - 1
             );
@@ -494,15 +810,131 @@ pub fn write(split_input: &Vec<&str>) {
             error!("Failed to write to file {filename}.\n{e}");
         }
     });
-    success!("Wrote calendar until {end_date} to {filename}");
+    success!("Wrote calendar until {} to {filename}", datetime_fmt(end_date));
+}
+
+/// Export a calendar to an iCalendar (.ics) file
+pub fn export_ics(split_input: &[&str]) {
+    if split_input.len() > 3 {
+        fail!(
+            "export-ics: Invalid number of arguments. Expected: 0, 1 or 2. Got: {}",
+            split_input.len() - 1
+        );
+        return;
+    }
+    let index = calendar_index!();
+    let (calendar, _) = match named_or_active_calendar(&index, split_input.get(2).copied()) {
+        Some(c) => c,
+        None => return,
+    };
+    let filename = match split_input.get(1) {
+        Some(f) => f.to_string(),
+        None => calendar.name() + ".ics",
+    };
+
+    let current_dir = match std::env::current_dir() {
+        Ok(d) => d,
+        Err(e) => {
+            error!("Failed to get current directory.\n{e}");
+            return;
+        }
+    };
+    let path = current_dir.join(&filename);
+    if let Err(e) = std::fs::write(&path, to_ics(&calendar)) {
+        print_err_msg(CalmarError::WriteFile { e }, path.display());
+        return;
+    }
+    success!(
+        "Exported {} event(s) and {} deadline(s) from {} to {}",
+        calendar.events().len(),
+        calendar.deadlines().len(),
+        calendar.name(),
+        path.display()
+    );
+}
+
+/// Import events and deadlines from an iCalendar (.ics) file
+pub fn import_ics(split_input: &[&str]) {
+    if !(2..=3).contains(&split_input.len()) {
+        fail!(
+            "import-ics: Invalid number of arguments. Expected: 1 or 2. Got: {}",
+            split_input.len() - 1
+        );
+        return;
+    }
+    let filename = split_input[1];
+    let content = match std::fs::read_to_string(filename) {
+        Ok(c) => c,
+        Err(e) => {
+            print_err_msg(CalmarError::ReadFile { e }, filename);
+            return;
+        }
+    };
+    let import = match from_ics(&content) {
+        Ok(i) => i,
+        Err(e) => {
+            print_err_msg(e, filename);
+            return;
+        }
+    };
+    import.problems.iter().for_each(|p| warning!("{p}"));
+
+    let index = calendar_index!();
+    let (mut calendar, path) = match named_or_active_calendar(&index, split_input.get(2).copied()) {
+        Some(c) => c,
+        None => return,
+    };
+
+    // Items already in the calendar are recognized by UID and updated instead of duplicated
+    let name = calendar.name();
+    let (mut added, mut updated) = (0, 0);
+    for event in import.events {
+        match calendar
+            .events()
+            .iter()
+            .position(|e| Some(event_uid(&name, e)) == event.uid())
+        {
+            Some(i) => {
+                calendar.events_mut()[i].update_from(event);
+                updated += 1;
+            }
+            None => {
+                calendar.add_event(event);
+                added += 1;
+            }
+        }
+    }
+    for deadline in import.deadlines {
+        match calendar
+            .deadlines()
+            .iter()
+            .position(|d| Some(deadline_uid(&name, d)) == deadline.uid())
+        {
+            Some(i) => {
+                calendar.deadlines_mut()[i].update_from(deadline);
+                updated += 1;
+            }
+            None => {
+                calendar.add_deadline(deadline);
+                added += 1;
+            }
+        }
+    }
+
+    if let Err(e) = save_calendar(&calendar, &path) {
+        print_err_msg(e, &path);
+        return;
+    }
+    success!("Imported {added} new and updated {updated} existing item(s) into {name}");
 }
 
 pub fn date() {
-    println!("{}", Local::now().date_naive())
+    println!("{}", date_fmt(Local::now().date_naive()))
 }
 
 pub fn time() {
-    println!("{}", Local::now().time().format("%H:%M:%S"))
+    let layout = time_layout().replace("%M", "%M:%S");
+    println!("{}", Local::now().time().format(&layout))
 }
 
 pub fn update() {
@@ -513,27 +945,37 @@ pub fn update() {
 
     let before = active_calendar.events().len();
 
+    // retain only events that will end in the future or recurring events with occurences left.
+    // this retains events currently happening
+    active_calendar
+        .events_mut()
+        .retain(|e| match e.repeat().is_none() {
+            true => e.end() > now,
+            false => closest_occurence_start(e).is_some(),
+        });
+
     // Set time of recurring events to their nearest occurence
     for event in active_calendar.events_mut() {
-        if !event.repeat().is_zero() {
-            let new_start = closest_occurence_start(event);
+        if event.repeat().is_none() {
+            continue;
+        }
+        if let Some(new_start) = closest_occurence_start(event) {
             let duration = event.duration();
+            // occurences before the new start no longer count towards the limit
+            if let Some(count) = event.repeat_count() {
+                let passed = event.occurrences().take_while(|s| *s < new_start).count();
+                event.set_repeat_count(Some(count - passed as u32));
+            }
             event.set_start(&new_start);
             event.set_end(&(new_start + duration));
         }
     }
 
-    // retain only events that are recurring or they will end in the future.
-    // this retains events currently happening
-    active_calendar
-        .events_mut()
-        .retain(|e| !e.repeat().is_zero() || (e.end() > now && e.repeat().is_zero()));
-
     let after = active_calendar.events().len();
     println!("{after} {before}");
     success!("Removed {} old event/s", before - after);
     success!("Brought nearest event occurences up to date");
-    if let Err(e) = active_calendar.save(&path) {
+    if let Err(e) = save_calendar(&active_calendar, &path) {
         print_err_msg(e, &path);
     }
 }
@@ -541,7 +983,11 @@ pub fn update() {
 pub fn mkindex() {
     if PathBuf::from_str(&CONFIG.index_path).unwrap().exists() {
         warning!("This will revert your index.json to its default contents. Proceed?");
-        match get_input("[y/N]: ", None).to_lowercase().trim() {
+        match get_input("[y/N]: ", None)
+            .unwrap_or_default()
+            .to_lowercase()
+            .trim()
+        {
             "yes" | "y" => {}
             _ => return,
         }
@@ -550,7 +996,7 @@ pub fn mkindex() {
     let new_index_json = match serde_json::to_string_pretty(&CalendarIndex::new()) {
         Ok(s) => s,
         Err(e) => {
-            print_err_msg(CalmarError::ToJSON { e }, &"".to_string());
+            print_err_msg(CalmarError::ToJSON { e }, "".to_string());
             return;
         }
     };
@@ -560,7 +1006,7 @@ pub fn mkindex() {
         .truncate(true)
         .write(true)
         .create(true)
-        .open(&path_str)
+        .open(path_str)
     {
         Ok(f) => f,
         Err(e) => {
@@ -579,7 +1025,11 @@ pub fn mkindex() {
 pub fn mkconfig() {
     if get_home_dir().join(".config/calmar/config.json").exists() {
         warning!("This will revert your config.json to its default contents. Proceed?");
-        match get_input("[y/N]: ", None).to_lowercase().trim() {
+        match get_input("[y/N]: ", None)
+            .unwrap_or_default()
+            .to_lowercase()
+            .trim()
+        {
             "yes" | "y" => {}
             _ => return,
         }
@@ -596,7 +1046,7 @@ pub fn mkconfig() {
     {
         Ok(file) => file,
         Err(e) => {
-            print_err_msg(CalmarError::CreateFile { e }, &path.to_str().unwrap().to_string());
+            print_err_msg(CalmarError::CreateFile { e }, path.to_str().unwrap().to_string());
             return;
         }
     };
@@ -604,7 +1054,7 @@ pub fn mkconfig() {
     let new_config_json = match serde_json::to_string_pretty(&new_config) {
         Ok(s) => s,
         Err(e) => {
-            print_err_msg(CalmarError::ToJSON { e }, &"".to_string());
+            print_err_msg(CalmarError::ToJSON { e }, "".to_string());
             return;
         }
     };
@@ -612,7 +1062,7 @@ pub fn mkconfig() {
     success!("Wrote default config to {}", path.display());
 
     if let Err(e) = file.write(new_config_json.as_bytes()) {
-        print_err_msg(CalmarError::WriteFile { e }, &path.to_str().unwrap().to_string())
+        print_err_msg(CalmarError::WriteFile { e }, path.to_str().unwrap().to_string())
     }
 }
 
@@ -624,18 +1074,21 @@ pub fn update_index() {
         .retain(|r| Path::new(&r.path()).exists());
     let after = index.calendars().len();
     success!("Removed {} where the file didn't exist", after - before);
-    if let Err(e) = index.save() {
+    if let Err(e) = save_index(&index) {
         print_err_msg(e, &CONFIG.index_path);
     }
 }
 
-pub fn backup(split_input: &Vec<&str>) {
+pub fn backup(split_input: &[&str]) {
     let index = calendar_index!();
+    let found = find_all_by_keys(
+        index.calendars().iter().map(|r| (r.name(), r.id())),
+        &split_input[1..],
+        "calendar",
+    );
     let mut i = 0;
-    for reference in index.calendars() {
-        if split_input.len() == 1
-            || (split_input[1..].contains(&reference.name().as_str()) && split_input.len() > 1)
-        {
+    for (n, reference) in index.calendars().iter().enumerate() {
+        if split_input.len() == 1 || found.contains(&n) {
             if Path::new(&reference.path()).exists() {
                 let backup_path = reference.path() + ".bak";
                 match OpenOptions::new()
@@ -683,40 +1136,55 @@ pub fn edit_cal(split_input: &[&str]) {
 }
 
 /// Add an exception to a recurring event, for example when it is cancelled on a given day
-pub fn except(split_input: &Vec<&str>) {
+pub fn except(split_input: &[&str]) {
     let index = calendar_index!();
     let mut active_calendar = active_calendar!(index);
     let path = active_calendar_reference!().path();
+    let before = active_calendar.clone();
     let options = ["Add exception", "Remove exception"];
 
-    split_input[1..].iter().for_each(|n| {
-        if active_calendar.events().iter().all(|e| e.name() != **n) {
-            warning!("No event named {n}");
+    for n in &split_input[1..] {
+        if active_calendar.find_events(n).is_empty() {
+            fail!("No event named {n}");
         } else {
             let idx = match choose_struct_idx(
                 active_calendar.events().to_vec(),
                 "Select an event to except",
                 n,
             ) {
-                Some(i) => i,
-                None => return,
+                Ok(Some(i)) => i,
+                Ok(None) => continue,
+                Err(_) => return,
             };
             let edited_event = &mut active_calendar.events_mut()[idx];
             options
                 .iter()
                 .enumerate()
                 .for_each(|(i, o)| println!("{}. {o}", i + 1));
-            let num = select_in_range("Select an option", options.len());
+            let num = match select_in_range("Select an option", options.len()) {
+                Ok(num) => num,
+                Err(_) => return,
+            };
             match num {
                 // add an exception
-                1 => edited_event
-                    .exceptions_mut()
-                    .push(get_date("Date: ").and_time(get_time("Time: ")).unwrap()),
+                1 => {
+                    let date = match get_date("Date: ") {
+                        Ok(d) => d,
+                        Err(_) => return,
+                    };
+                    let time = match get_time("Time: ") {
+                        Ok(t) => t,
+                        Err(_) => return,
+                    };
+                    edited_event
+                        .exceptions_mut()
+                        .push(date.and_time(time).unwrap())
+                }
                 // remove an exception
                 2 => {
                     if edited_event.exceptions().is_empty() {
-                        warning!("No exceptions");
-                        return;
+                        fail!("No exceptions");
+                        continue;
                     }
                     edited_event
                         .exceptions()
@@ -724,57 +1192,195 @@ pub fn except(split_input: &Vec<&str>) {
                         .enumerate()
                         .for_each(|(i, e)| println!("{}. {e}", i + 1));
                     let len = edited_event.exceptions().len();
-                    edited_event
-                        .exceptions_mut()
-                        .remove(select_in_range("Select an exception: ", len) - 1);
+                    match select_in_range("Select an exception: ", len) {
+                        Ok(i) => edited_event.exceptions_mut().remove(i - 1),
+                        Err(_) => return,
+                    };
                 }
                 _ => panic!("Impossible, this should be checked in select_in_range"),
             }
         }
-    });
-    if let Err(e) = active_calendar.save(&path) {
+    }
+    // removing an exception brings back an occurence, which may overlap with other events
+    if !check_new_conflicts(&before, &active_calendar) {
+        return;
+    }
+    if let Err(e) = save_calendar(&active_calendar, &path) {
         print_err_msg(e, path)
     }
 }
 
-pub fn deadline(split_input: &Vec<&str>) {
+pub fn deadline(split_input: &[&str]) {
     let path = active_calendar_reference!().path();
     let mut active_calendar = active_calendar!();
-    split_input[1..].iter().for_each(|n| {
+    for n in &split_input[1..] {
         success!("Adding {n} deadline");
-        active_calendar.add_deadline(Deadline::new(
-            n.to_string(),
-            get_date("Deadline date: ")
-                .and_time(get_time("Deadline time: "))
-                .unwrap(),
-            get_priority(),
-        ))
-    });
-    if let Err(e) = active_calendar.save(&path) {
+        let deadline = match get_new_deadline(n) {
+            Ok(d) => d,
+            Err(_) => return,
+        };
+        active_calendar.add_deadline(deadline)
+    }
+    if let Err(e) = save_calendar(&active_calendar, &path) {
         print_err_msg(e, path);
     }
 }
 
 // temporary solution probably, unitl display improves
-pub fn ls_deadlines(split_input: &Vec<&str>) {
+pub fn ls_deadlines(split_input: &[&str]) {
+    let history = split_input[1..]
+        .iter()
+        .any(|a| matches!(*a, "--history" | "-H"));
+    let keys: Vec<&str> = split_input[1..]
+        .iter()
+        .filter(|a| !matches!(**a, "--history" | "-H"))
+        .copied()
+        .collect();
     let active_calendar = active_calendar!();
-    let len = split_input.len();
+    let found = find_all_by_keys(
+        active_calendar
+            .deadlines()
+            .iter()
+            .map(|d| (d.name(), d.id())),
+        &keys,
+        "deadline",
+    );
     let mut x = active_calendar
         .deadlines()
         .iter()
-        .filter(|a| {
-            if len == 1 {
-                true
-            } else {
-                split_input[1..].contains(&a.name().as_str())
-            }
+        .enumerate()
+        .filter(|(i, d)| match keys.is_empty() {
+            // finished deadlines are only listed on request
+            true => history || !d.is_finished(),
+            false => found.contains(i),
         })
+        .map(|(_, d)| d)
         .collect::<Vec<&Deadline>>();
-    x.sort_by(|d, o| d.date().cmp(&o.date()));
+    x.sort_by_key(|d| d.date());
     x.iter().for_each(|d| println!("{}", colorize_deadline(d)))
 }
 
-pub fn remove_deadline(split_input: &Vec<&str>) {
+/*
+Edit attributes of given deadlines and save them
+*/
+pub fn edit_deadline(split_input: &[&str]) {
+    split_input[1..].iter().for_each(|d| {
+        success!("Editing {d}");
+        change_deadlines(&[d], "Select a deadline to edit", |d| edit_deadline_properties(d).is_ok())
+    })
+}
+
+/// Move a deadline in the active calendar later by a duration
+pub fn postpone(split_input: &[&str]) {
+    if split_input.len() != 3 {
+        fail!(
+            "postpone: Invalid number of arguments. Expected: 2. Got: {}",
+            split_input.len() - 1
+        );
+        return;
+    }
+    let by = match parse_into_duration(split_input[2]) {
+        Ok(by) if by > Duration::zero() => by,
+        Ok(_) => {
+            fail!("postpone: A deadline has to be postponed by more than 0.");
+            return;
+        }
+        Err(e) => {
+            fail!("{} is not a valid duration input. {e}.", split_input[2]);
+            return;
+        }
+    };
+    change_deadlines(&split_input[1..2], "Select a deadline to postpone", |d| {
+        if d.is_finished() {
+            fail!("{} is {}. Reopen it first", d.name(), d.status());
+            return false;
+        }
+        d.postpone(by);
+        success!(
+            "Postponed {} to {}, {} time(s) since its original date {}",
+            d.name(),
+            datetime_fmt(d.date()),
+            d.postponements(),
+            datetime_fmt(d.original_date().unwrap())
+        );
+        true
+    })
+}
+
+/// Mark deadlines in the active calendar as done, or as dropped with `--drop`
+pub fn done(split_input: &[&str]) {
+    let status = match split_input[1..]
+        .iter()
+        .any(|a| matches!(*a, "--drop" | "-d"))
+    {
+        true => DeadlineStatus::Dropped,
+        false => DeadlineStatus::Done,
+    };
+    let keys: Vec<&str> = split_input[1..]
+        .iter()
+        .filter(|a| !matches!(**a, "--drop" | "-d"))
+        .copied()
+        .collect();
+    if keys.is_empty() {
+        fail!("done: Invalid number of arguments. Expected: 1 or more. Got: 0");
+        return;
+    }
+    change_deadlines(&keys, "Select a deadline to mark", |d| {
+        if d.status() == status {
+            fail!("{} is already {status}", d.name());
+            return false;
+        }
+        d.finish(status, Local::now());
+        success!("Marked {} as {status}", d.name());
+        true
+    })
+}
+
+/// Mark done or dropped deadlines in the active calendar as open again
+pub fn reopen(split_input: &[&str]) {
+    if split_input.len() == 1 {
+        fail!("reopen: Invalid number of arguments. Expected: 1 or more. Got: 0");
+        return;
+    }
+    change_deadlines(&split_input[1..], "Select a deadline to reopen", |d| {
+        if !d.is_finished() {
+            fail!("{} is not done or dropped", d.name());
+            return false;
+        }
+        d.reopen();
+        success!("Reopened {}, which is {}", d.name(), d.status_fmt());
+        true
+    })
+}
+
+/// Set the percentage of work done towards a deadline in the active calendar
+pub fn progress(split_input: &[&str]) {
+    if split_input.len() != 3 {
+        fail!(
+            "progress: Invalid number of arguments. Expected: 2. Got: {}",
+            split_input.len() - 1
+        );
+        return;
+    }
+    let percent = match split_input[2].trim_end_matches('%').parse::<u8>() {
+        Ok(p) if p <= 100 => p,
+        _ => {
+            fail!("{} is not a valid percentage. Expected a number from 0 to 100", split_input[2]);
+            return;
+        }
+    };
+    change_deadlines(&split_input[1..2], "Select a deadline", |d| {
+        if d.is_finished() {
+            fail!("{} is {}. Reopen it first", d.name(), d.status());
+            return false;
+        }
+        d.set_progress(Some(percent));
+        success!("{} is {}", d.name(), d.status_fmt());
+        true
+    })
+}
+
+pub fn remove_deadline(split_input: &[&str]) {
     let mut active_calendar = active_calendar!();
     let path = &active_calendar_reference!().path();
     for a in split_input[1..].iter() {
@@ -783,58 +1389,241 @@ pub fn remove_deadline(split_input: &Vec<&str>) {
             "Select a deadline to remove",
             a,
         ) {
-            Some(i) => i,
-            None => {
-                warning!("No deadline named {a}");
+            Ok(Some(i)) => i,
+            Ok(None) => {
+                fail!("No deadline named {a}");
                 continue;
             }
+            Err(_) => return,
         };
         active_calendar.deadlines_mut().remove(idx);
     }
-    if let Err(e) = active_calendar.save(path) {
+    if let Err(e) = save_calendar(&active_calendar, path) {
         print_err_msg(e, path)
     }
 }
 
-pub fn briefing() {
-    let cal = active_calendar!();
-    let gen = generate_until(&cal, round_to_full_day(Local::now() + Duration::days(7)));
+pub fn briefing(split_input: &[&str]) {
+    let index = calendar_index!();
+    let (calendars, split_input) = match agenda_calendars(&index, split_input) {
+        Some(c) => c,
+        None => return,
+    };
+    let history = split_input[1..]
+        .iter()
+        .any(|a| matches!(*a, "--history" | "-H"));
+    if split_input.len() > 1 + history as usize {
+        fail!(
+            "briefing: Invalid number of arguments. Expected: 0. Got: {}",
+            split_input.len() - 1
+        );
+        return;
+    }
+    let gen: Vec<Event> = generate_agenda(
+        &calendars,
+        Local::now(),
+        round_to_full_day(Local::now() + Duration::days(7)),
+    )
+    .into_iter()
+    .map(|(_, e)| e)
+    .collect();
+    let (finished, deadlines): (Vec<&Deadline>, Vec<&Deadline>) = calendars
+        .iter()
+        .flat_map(|(_, c)| c.deadlines())
+        .partition(|d| d.is_finished());
     println!(
-        "{}",
+        "{} and {}. {} in the next 7 days and {} in the next 14.",
         format!(
-            "{} and {}. {} in the next 7 days and {} in the next 14.",
-            format!(
-                "{:?} event(s) left today",
-                gen.iter()
-                    .filter(|e| e.is_happening_on(Local::now()) || e.will_happen_today())
-                    .count()
-            )
-            .bold(),
-            format!(
-                "{} tomorrow",
-                gen.iter()
-                    .filter(|e| e.start().date() == (Local::now() + Duration::days(1)).date())
-                    .count()
-            )
-            .bold(),
-            format!(
-                "{} deadline(s)",
-                cal.deadlines()
-                    .iter()
-                    .filter(|d| d.date().date()
-                        < round_to_full_day(Local::now() + Duration::days(7)).date())
-                    .count()
-            )
-            .bold(),
-            format!(
-                "{}",
-                cal.deadlines()
-                    .iter()
-                    .filter(|d| d.date().date()
-                        < round_to_full_day(Local::now() + Duration::days(14)).date())
-                    .count()
+            "{:?} event(s) left today",
+            gen.iter()
+                .filter(|e| e.is_happening_on(Local::now()) || e.will_happen_today())
+                .count()
+        )
+        .bold(),
+        format!(
+            "{} tomorrow",
+            gen.iter()
+                .filter(|e| e.start().date() == (Local::now() + Duration::days(1)).date())
+                .count()
+        )
+        .bold(),
+        format!(
+            "{} deadline(s)",
+            deadlines
+                .iter()
+                .filter(|d| d.date().date()
+                    < round_to_full_day(Local::now() + Duration::days(7)).date())
+                .count()
+        )
+        .bold(),
+        deadlines
+            .iter()
+            .filter(
+                |d| d.date().date() < round_to_full_day(Local::now() + Duration::days(14)).date()
             )
+            .count()
+            .to_string()
             .bold()
-        )
-    )
+    );
+
+    // recurring events that are about to end
+    let week_end = round_to_full_day(Local::now() + Duration::days(7));
+    for event in calendars
+        .iter()
+        .flat_map(|(_, c)| c.events())
+        .filter(|e| !e.repeat().is_none())
+    {
+        if let Some(last) = event.last_occurrence() {
+            if Local::now() < last + event.duration() && last < week_end {
+                println!("{} ends on {}.", event.name().bold(), date_fmt(last.date_naive()).bold());
+            }
+        }
+    }
+
+    if history {
+        let week_ago = Local::now() - Duration::days(7);
+        let recent: Vec<String> = finished
+            .iter()
+            .filter(|d| d.completed().is_some_and(|c| c > week_ago))
+            .map(|d| format!("{} ({})", d.name(), d.status()))
+            .collect();
+        println!(
+            "{} finished in the last 7 days{}",
+            format!("{} deadline(s)", recent.len()).bold(),
+            match recent.is_empty() {
+                true => ".".to_string(),
+                false => format!(": {}.", recent.join(", ")),
+            }
+        );
+    }
+}
+
+/// Give the reminders of events and deadlines in the agenda calendars as they become due,
+/// until interrupted. Calendars are read again whenever their files change.
+pub fn watch(split_input: &[&str]) {
+    let index = calendar_index!();
+    let (mut calendars, args) = match agenda_calendars(&index, split_input) {
+        Some(c) => c,
+        None => return,
+    };
+    let once = args[1..].iter().any(|a| matches!(*a, "--once" | "-1"));
+    if args.len() > 1 + once as usize {
+        fail!("watch: Invalid number of arguments. Expected: 0. Got: {}", args.len() - 1);
+        return;
+    }
+    let mut fired = match FiredReminders::get() {
+        Ok(f) => f,
+        Err(e) => {
+            print_err_msg(e, FiredReminders::path());
+            return;
+        }
+    };
+    let mut paths = watched_paths(&index);
+    let mut times = modification_times(&paths);
+    if !once {
+        println!(
+            "Watching {} for reminders. Press Ctrl+C to stop.",
+            calendars
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<String>>()
+                .join(", ")
+                .bold()
+        );
+    }
+
+    loop {
+        let now = Local::now();
+        let mut changed = fired.prune(now);
+        for reminder in due_reminders(&calendars, now) {
+            if fired.contains(&reminder) {
+                continue;
+            }
+            if let Err(e) = notify(&reminder) {
+                error!("{e}");
+            }
+            fired.insert(&reminder);
+            changed = true;
+        }
+        if changed {
+            if let Err(e) = fired.save() {
+                print_err_msg(e, FiredReminders::path());
+            }
+        }
+        if once {
+            return;
+        }
+
+        std::thread::sleep(std::time::Duration::from_secs(WATCH_INTERVAL));
+        let current = modification_times(&paths);
+        if current == times {
+            continue;
+        }
+        // keep the calendars read before if the changed files cannot be read
+        times = current;
+        let index = match CalendarIndex::get() {
+            Ok(i) => i,
+            Err(e) => {
+                print_err_msg(e, &CONFIG.index_path);
+                continue;
+            }
+        };
+        if let Some((c, _)) = agenda_calendars(&index, split_input) {
+            calendars = c;
+            paths = watched_paths(&index);
+            times = modification_times(&paths);
+            println!("Calendars changed. Reminders are now given from the current ones.");
+        }
+    }
+}
+
+/// Revert the most recent change to the calendars
+pub fn undo() {
+    step_history(false)
+}
+
+/// Reapply the most recently undone change
+pub fn redo() {
+    step_history(true)
+}
+
+fn step_history(forward: bool) {
+    let history_path = get_home_dir().join(".config/calmar/history.json");
+    let mut history = match History::get() {
+        Ok(h) => h,
+        Err(e) => {
+            print_err_msg(e, history_path.display());
+            return;
+        }
+    };
+    let stack = match forward {
+        true => &mut history.redo,
+        false => &mut history.undo,
+    };
+    let entry = match stack.pop() {
+        Some(entry) => entry,
+        None => {
+            fail!("Nothing to {}", if forward { "redo" } else { "undo" });
+            return;
+        }
+    };
+    if let Err(e) = restore(&entry, forward) {
+        error!("Cannot {} \"{}\". {e}", if forward { "redo" } else { "undo" }, entry.command);
+        return;
+    }
+
+    let (verb, other) = match forward {
+        true => ("Redid", &mut history.undo),
+        false => ("Undid", &mut history.redo),
+    };
+    success!(
+        "{verb} \"{}\" from {} ({})",
+        entry.command,
+        datetime_fmt(entry.time),
+        entry.file_names()
+    );
+    other.push(entry);
+    if let Err(e) = history.save() {
+        print_err_msg(e, history_path.display());
+    }
 }
@@ -1,11 +1,14 @@
-use crate::cli::validator::get_home_dir;
+use crate::cli::{hooks::Hooks, validator::get_home_dir};
 use colored::Colorize;
 use serde_derive::{Deserialize, Serialize};
 use std::fs::read_to_string;
 
 #[derive(Deserialize, Serialize)]
+#[serde(default)]
 pub struct Config {
+    /// How dates are typed and listed, one of `DATE_FORMATS`, such as "YYYY-MM-DD".
     pub date_format: String,
+    /// How times are listed, one of `TIME_FORMATS`: "HH:MM" or "hh:mm AM/PM".
     pub time_format: String,
     pub default_path: String,
     pub index_path: String,
@@ -18,10 +21,39 @@ pub struct Config {
     pub prompt_bold: bool,
     pub prompt_italic: bool,
     pub prompt_underline: bool,
+    /// IANA name of a time zone event times are also listed in, such as "America/New_York".
+    /// Empty if only local time is listed.
+    pub second_timezone: String,
+    /// What happens when a change creates overlapping events: "warn" or "block".
+    pub overlap_policy: String,
+    /// Working hours in HH:MM, the only time of day `free` looks for free time in.
+    pub work_day_start: String,
+    pub work_day_end: String,
+    /// Days of the week `free` looks for free time on, such as "mon tue wed thu fri".
+    pub work_days: String,
+    /// Time kept free before and after every event by `free`, such as "15m". Empty for none.
+    pub buffer_before: String,
+    pub buffer_after: String,
+    /// Names of calendars whose events `list`, `briefing` and `until` show along with the active calendar.
+    pub visible_calendars: Vec<String>,
+    /// Day the weeks of `month` start on, such as "mon" or "sun".
+    pub first_weekday: String,
+    /// What the days of `month` show besides their number: "none", "count" of events or "name" of the first one.
+    pub month_cell: String,
+    /// First hour and the hour after the last one shown by `week`, from 0 to 24.
+    pub week_start_hour: u32,
+    pub week_end_hour: u32,
+    /// Number of commands `undo` can revert. 0 turns off the history.
+    pub undo_limit: usize,
+    /// Command `watch` gives reminders with, run through sh with the reminder as its last argument,
+    /// such as "notify-send calmar". Empty to print reminders to the terminal.
+    pub notify_command: String,
+    /// Commands run when calendars change, see `hooks::Hooks`.
+    pub hooks: Hooks,
 }
 
-impl Config {
-    pub fn default() -> Self {
+impl Default for Config {
+    fn default() -> Self {
         Config {
             date_format: "DD/MM/YYYY".to_string(),
             time_format: "HH:MM".to_string(),
@@ -52,6 +84,21 @@ impl Config {
             prompt_bold: true,
             prompt_italic: false,
             prompt_underline: false,
+            second_timezone: "".to_string(),
+            overlap_policy: "warn".to_string(),
+            work_day_start: "08:00".to_string(),
+            work_day_end: "18:00".to_string(),
+            work_days: "mon tue wed thu fri".to_string(),
+            buffer_before: "".to_string(),
+            buffer_after: "".to_string(),
+            visible_calendars: vec![],
+            first_weekday: "mon".to_string(),
+            month_cell: "none".to_string(),
+            week_start_hour: 8,
+            week_end_hour: 20,
+            undo_limit: 50,
+            notify_command: "".to_string(),
+            hooks: Hooks::default(),
         }
     }
 }
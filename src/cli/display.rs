@@ -1,51 +1,111 @@
 use crate::{
     cal::{
-        calendar_ref::CalendarReference, calmar_trait::CalendarDataType, deadline::Deadline,
+        calmar_trait::CalendarDataType,
+        deadline::Deadline,
+        details::Details,
         event::Event,
+        id::short_id,
+        recurrence::{days_in_month, Recurrence},
+        timezone::parse_timezone,
+    },
+    cli::{
+        functions::assign_lanes,
+        util::{
+            date_fmt, datetime_fmt, day_month_layout, duration_fmt, overlap_fmt, reminders_fmt,
+            time_fmt,
+        },
     },
-    cli::util::duration_fmt,
     error, CONFIG,
 };
-use chrono::{Datelike, Duration};
-use colored::Colorize;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike, Weekday};
+use colored::{ColoredString, Colorize};
 use tabled::{Disable, Style, Table, Tabled};
+use terminal_size::{terminal_size, Width};
 
 #[derive(Tabled, Debug, Clone)]
 pub struct DetailedEvent {
     #[tabled(rename = "Name")]
     name: String,
+    #[tabled(rename = "Calendar")]
+    calendar: String,
     #[tabled(rename = "Start Time")]
     start_time: String,
     #[tabled(rename = "End Time")]
     end_time: String,
-    #[tabled(rename = "Repeat Every")]
+    #[tabled(rename = "Repeat")]
     repeat: String,
     #[tabled(rename = "Priority")]
     priority: u8,
     #[tabled(rename = "Difficulty")]
     difficulty: u8,
+    #[tabled(rename = "Time Zone")]
+    zone: String,
+    #[tabled(rename = "Second Zone")]
+    second_zone: String,
+    #[tabled(rename = "Location")]
+    location: String,
+    #[tabled(rename = "Tags")]
+    tags: String,
+    #[tabled(rename = "ID")]
+    id: String,
 }
 
-impl From<&Event> for DetailedEvent {
-    fn from(event: &Event) -> DetailedEvent {
+/// Time of `event` in the time zone set as `second_timezone` in the config,
+/// or an empty string if there is none.
+fn second_zone_time(event: &Event) -> String {
+    match parse_timezone(&CONFIG.second_timezone) {
+        Some(tz) => {
+            let (start, end) = (event.start().with_timezone(&tz), event.end().with_timezone(&tz));
+            format!(
+                "{} {} - {} {}",
+                start.format("%a"),
+                time_fmt(start.time()),
+                time_fmt(end.time()),
+                end.format("%Z")
+            )
+        }
+        None => "".to_string(),
+    }
+}
+
+impl From<&(String, Event)> for DetailedEvent {
+    fn from((calendar, event): &(String, Event)) -> DetailedEvent {
         DetailedEvent {
             name: event.name(),
-            start_time: format!(
-                "{} {}",
-                event.start().date_naive(),
-                event.start().time().format("%H:%M")
-            ),
-            end_time: format!(
-                "{} {}",
-                event.end().date_naive(),
-                event.end().time().format("%H:%M")
-            ),
-            repeat: match event.repeat().is_zero() {
-                true => "None".to_string(),
-                false => duration_fmt(event.repeat()),
-            },
+            calendar: calendar.clone(),
+            start_time: datetime_fmt(event.start()),
+            end_time: datetime_fmt(event.end()),
+            repeat: repeat_fmt(event),
             priority: event.priority(),
             difficulty: event.difficulty(),
+            zone: match event.timezone() {
+                Some(tz) => tz.name().to_string(),
+                None => "Local".to_string(),
+            },
+            second_zone: second_zone_time(event),
+            location: event.details().location().unwrap_or("").to_string(),
+            tags: event.details().tags_fmt(),
+            id: short_id(event.id()).to_string(),
+        }
+    }
+}
+
+/// How `event` repeats, such as "every 7 days until 31/01/2023", or "None".
+fn repeat_fmt(event: &Event) -> String {
+    match event.repeat() {
+        r if r.is_none() => "None".to_string(),
+        r => {
+            let mut repeat = match r {
+                Recurrence::Interval(d) => format!("every {}", duration_fmt(*d)),
+                Recurrence::Rule(rule) => rule.to_string(),
+            };
+            if let Some(until) = event.repeat_until() {
+                repeat += &format!(" until {}", date_fmt(until));
+            }
+            if let Some(count) = event.repeat_count() {
+                repeat += &format!(", {count} time(s)");
+            }
+            repeat
         }
     }
 }
@@ -56,108 +116,530 @@ pub struct SimpleEvent {
     time: String,
     #[tabled(rename = "Event")]
     name: String,
+    #[tabled(rename = "Calendar")]
+    calendar: String,
     #[tabled(rename = "Priority")]
     priority: u8,
     #[tabled(rename = "Difficulty")]
     difficulty: u8,
+    #[tabled(rename = "Second Zone")]
+    second_zone: String,
+    #[tabled(rename = "ID")]
+    id: String,
 }
 
-impl From<&Event> for SimpleEvent {
-    fn from(event: &Event) -> SimpleEvent {
+impl From<&(String, Event)> for SimpleEvent {
+    fn from((calendar, event): &(String, Event)) -> SimpleEvent {
         SimpleEvent {
             name: event.name(),
-            time: format!(
-                "{} - {}",
-                event.start().format("%H:%M"),
-                event.end().time().format("%H:%M")
-            ),
+            calendar: calendar.clone(),
+            time: format!("{} - {}", time_fmt(event.start().time()), time_fmt(event.end().time())),
             priority: event.priority(),
             difficulty: event.difficulty(),
+            second_zone: second_zone_time(event),
+            id: short_id(event.id()).to_string(),
         }
     }
 }
 
 #[derive(Tabled, Debug)]
-pub struct DisplayedCalendarReference {
+pub struct DisplayedDeadline {
     #[tabled(rename = "Name")]
     name: String,
-    #[tabled(rename = "Path")]
-    path: String,
-    #[tabled(rename = "Is Active?")]
-    active: bool,
-}
-
-impl From<&CalendarReference> for DisplayedCalendarReference {
-    fn from(r: &CalendarReference) -> DisplayedCalendarReference {
-        DisplayedCalendarReference {
-            name: r.name(),
-            path: r.path(),
-            active: r.active(),
+    #[tabled(rename = "Calendar")]
+    calendar: String,
+    #[tabled(rename = "Date")]
+    date: String,
+    #[tabled(rename = "Priority")]
+    priority: u8,
+    #[tabled(rename = "Due")]
+    due: String,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "Tags")]
+    tags: String,
+    #[tabled(rename = "ID")]
+    id: String,
+}
+
+impl From<&(String, Deadline)> for DisplayedDeadline {
+    fn from((calendar, deadline): &(String, Deadline)) -> DisplayedDeadline {
+        DisplayedDeadline {
+            name: deadline.name(),
+            calendar: calendar.clone(),
+            date: datetime_fmt(deadline.date()),
+            priority: deadline.priority(),
+            due: deadline.due(),
+            status: deadline.status_fmt(),
+            tags: deadline.details().tags_fmt(),
+            id: short_id(deadline.id()).to_string(),
+        }
+    }
+}
+
+#[derive(Tabled, Debug)]
+pub struct DisplayedConflict {
+    #[tabled(rename = "Event")]
+    first: String,
+    #[tabled(rename = "Overlaps With")]
+    second: String,
+    #[tabled(rename = "Overlap")]
+    overlap: String,
+}
+
+impl From<&(Event, Event)> for DisplayedConflict {
+    fn from((a, b): &(Event, Event)) -> DisplayedConflict {
+        DisplayedConflict {
+            first: a.name(),
+            second: b.name(),
+            overlap: overlap_fmt(a, b),
+        }
+    }
+}
+
+#[derive(Tabled, Debug)]
+pub struct DisplayedSlot {
+    #[tabled(rename = "No.")]
+    number: usize,
+    #[tabled(rename = "Day")]
+    day: String,
+    #[tabled(rename = "Free Time")]
+    time: String,
+    #[tabled(rename = "Length")]
+    length: String,
+}
+
+impl DisplayedSlot {
+    fn new(number: usize, (start, end): &(DateTime<Local>, DateTime<Local>)) -> DisplayedSlot {
+        DisplayedSlot {
+            number,
+            day: format!("{}, {}", date_fmt(start.date_naive()), start.weekday()),
+            time: format!("{} - {}", time_fmt(start.time()), time_fmt(end.time())),
+            length: duration_fmt(*end - *start),
         }
     }
 }
 
-pub fn display_simple_events(events: Vec<Event>) {
-    let displayed_events: Vec<SimpleEvent> = events.iter().map(|e| SimpleEvent::from(e)).collect();
-    let table = Table::new(displayed_events).with(Style::modern());
+/// Whether events of more than one calendar are displayed, in which case the calendar of each is shown.
+fn several_calendars(events: &[(String, Event)]) -> bool {
+    events.iter().any(|(c, _)| *c != events[0].0)
+}
+
+pub fn display_simple_events(events: Vec<(String, Event)>) {
+    let displayed_events: Vec<SimpleEvent> = events.iter().map(SimpleEvent::from).collect();
+    let mut table = Table::new(&displayed_events);
+    if CONFIG.second_timezone.is_empty() {
+        table = table.with(Disable::Column(5..6));
+    }
+    if !several_calendars(&events) {
+        table = table.with(Disable::Column(2..3));
+    }
+    println!("{}", table.with(Style::modern()));
+}
+
+pub fn display_conflicts(conflicts: &[(Event, Event)]) {
+    let displayed: Vec<DisplayedConflict> = conflicts.iter().map(DisplayedConflict::from).collect();
+    let table = Table::new(displayed).with(Style::modern());
+    println!("{table}");
+}
+
+/// Displays free periods numbered from 1, so that one can be selected.
+pub fn display_free_slots(slots: &[(DateTime<Local>, DateTime<Local>)]) {
+    let displayed: Vec<DisplayedSlot> = slots
+        .iter()
+        .enumerate()
+        .map(|(i, slot)| DisplayedSlot::new(i + 1, slot))
+        .collect();
+    let table = Table::new(displayed).with(Style::modern());
     println!("{table}");
 }
 
-pub fn display_detailed_events(events: Vec<Event>) {
+/// A table of `displayed_events` without the columns no event has a value in.
+/// The calendar column is only kept if `several_calendars` is set.
+fn detailed_table(displayed_events: &[DetailedEvent], several_calendars: bool) -> Table {
+    let mut table = Table::new(displayed_events);
+    // columns are disabled from the last one, so that indices of the others stay the same
+    if displayed_events.iter().all(|e| e.tags.is_empty()) {
+        table = table.with(Disable::Column(10..11));
+    }
+    if displayed_events.iter().all(|e| e.location.is_empty()) {
+        table = table.with(Disable::Column(9..10));
+    }
+    if CONFIG.second_timezone.is_empty() {
+        table = table.with(Disable::Column(8..9));
+    }
+    if displayed_events.iter().all(|e| e.zone == "Local") {
+        table = table.with(Disable::Column(7..8));
+    }
+    if displayed_events.iter().all(|e| e.repeat == "None") {
+        table = table.with(Disable::Column(4..5));
+    }
+    if !several_calendars {
+        table = table.with(Disable::Column(1..2));
+    }
+    // the style is applied last, so that the borders of disabled columns are not left over
+    table.with(Style::modern())
+}
+
+/// Displays `events`, each with the name of its calendar, which have to be sorted by start.
+pub fn display_detailed_events(events: Vec<(String, Event)>) {
     // at this point, the events vector is guaranteed to not be empty.
-    let date_range = events[0].start().date()..events.iter().last().unwrap().start().date();
-    let mut current_date = date_range.start;
-    loop {
+    let last_date = events.iter().last().unwrap().1.start().date();
+    let several_calendars = several_calendars(&events);
+    let mut current_date = events[0].1.start().date();
+    while current_date <= last_date {
         let displayed_events = events
             .iter()
-            .filter(|e| e.start().date() == current_date)
+            .filter(|(_, e)| e.start().date() == current_date)
             .map(DetailedEvent::from)
             .collect::<Vec<DetailedEvent>>();
         println!(
             "{}, {}",
-            current_date.naive_local().to_string().bold(),
+            date_fmt(current_date.naive_local()).bold(),
             current_date.weekday().to_string().bold()
         );
-        let mut table = Table::new(&displayed_events).with(Style::modern());
-        if displayed_events.iter().all(|e| e.repeat == "None") {
-            table = table.with(Disable::Column(3..4));
-        }
-        println!("{table}");
+        println!("{}", detailed_table(&displayed_events, several_calendars));
         current_date += Duration::days(1);
-        if current_date == date_range.end {
-            break;
+    }
+}
+
+/// Displays events and deadlines found by a search in one table each, along with the names of their calendars.
+pub fn display_found(events: &[(String, Event)], deadlines: &[(String, Deadline)]) {
+    if !events.is_empty() {
+        let displayed: Vec<DetailedEvent> = events.iter().map(DetailedEvent::from).collect();
+        println!("{}", "Events".bold());
+        println!("{}", detailed_table(&displayed, true));
+    }
+    if !deadlines.is_empty() {
+        let displayed: Vec<DisplayedDeadline> =
+            deadlines.iter().map(DisplayedDeadline::from).collect();
+        let mut table = Table::new(&displayed);
+        if displayed.iter().all(|d| d.tags.is_empty()) {
+            table = table.with(Disable::Column(6..7));
         }
+        println!("{}", "Deadlines".bold());
+        println!("{}", table.with(Style::modern()));
     }
 }
 
-pub fn display_events(events: Vec<Event>) {
-    let date = match events.get(0) {
-        Some(e) => e,
+/// Displays `events`, each with the name of its calendar, which have to be sorted by start.
+/// The calendar column is only shown if the events come from more than one calendar.
+pub fn display_events(events: Vec<(String, Event)>) {
+    let date = match events.first() {
+        Some((_, e)) => e,
         None => return,
     }
     .start();
-    if events.iter().any(|e| e.start() != date) {
+    if events.iter().any(|(_, e)| e.start() != date) {
         display_detailed_events(events);
         return;
     }
     display_simple_events(events);
 }
 
+/// Number of upcoming occurrences of an event listed by `display_event_details`.
+const SHOWN_OCCURRENCES: usize = 5;
+
+/// Prints `value` after a bold `label`, unless it is empty.
+fn print_field(label: &str, value: &str) {
+    if !value.is_empty() {
+        println!("{} {value}", format!("{:<12}", format!("{label}:")).bold());
+    }
+}
+
+fn print_details(details: &Details) {
+    print_field("Location", details.location().unwrap_or(""));
+    print_field("URL", details.url().unwrap_or(""));
+    print_field("Tags", &details.tags_fmt());
+    print_field("Reminders", &reminders_fmt(details.reminders()));
+    print_field("Description", details.description().unwrap_or(""));
+}
+
+/// Prints all properties of `event` from `calendar` and when it takes place next.
+pub fn display_event_details(calendar: &str, event: &Event) {
+    print_field("Name", &event.name());
+    print_field("ID", event.id());
+    print_field("Calendar", calendar);
+    print_field("Start", &datetime_fmt(event.start()));
+    print_field("End", &datetime_fmt(event.end()));
+    print_field("Duration", &duration_fmt(event.duration()));
+    print_field("Repeat", &repeat_fmt(event));
+    print_field("Priority", &event.priority().to_string());
+    print_field("Difficulty", &event.difficulty().to_string());
+    print_field("Time Zone", event.timezone().map_or("Local", |tz| tz.name()));
+    print_field("Second Zone", &second_zone_time(event));
+    print_details(event.details());
+    print_field(
+        "Exceptions",
+        &event
+            .exceptions()
+            .iter()
+            .map(|e| datetime_fmt(*e))
+            .collect::<Vec<String>>()
+            .join(", "),
+    );
+
+    let now = Local::now();
+    let next: Vec<DateTime<Local>> = event
+        .occurrences()
+        .filter(|start| !event.exceptions().contains(start))
+        .skip_while(|start| *start + event.duration() <= now)
+        .take(SHOWN_OCCURRENCES)
+        .collect();
+    if next.is_empty() {
+        print_field("Next", "None, the event is over");
+        return;
+    }
+    println!("{}", "Next:".bold());
+    for start in next {
+        let end = start + event.duration();
+        println!(
+            "  {} {} {} - {}",
+            date_fmt(start.date_naive()),
+            start.format("%a"),
+            time_fmt(start.time()),
+            match end.date_naive() == start.date_naive() {
+                true => time_fmt(end.time()),
+                false => datetime_fmt(end),
+            }
+        );
+    }
+}
+
+/// Prints all properties of `deadline` from `calendar`.
+pub fn display_deadline_details(calendar: &str, deadline: &Deadline) {
+    print_field("Name", &deadline.name());
+    print_field("ID", deadline.id());
+    print_field("Calendar", calendar);
+    print_field("Date", &datetime_fmt(deadline.date()));
+    print_field("Due", &deadline.due());
+    print_field("Priority", &deadline.priority().to_string());
+    print_field("Status", &deadline.status_fmt());
+    if let Some(original) = deadline.original_date() {
+        print_field(
+            "Postponed",
+            &format!("{} time(s), from {}", deadline.postponements(), datetime_fmt(original)),
+        );
+    }
+    print_details(deadline.details());
+}
+
+/// Colors `s` like deadlines of the given priority.
+fn colorize_priority(s: &str, priority: u8) -> ColoredString {
+    match priority {
+        0..=5 => s.green(),
+        6..=8 => s.yellow(),
+        _ => s.red(),
+    }
+}
+
+/// Draws a grid of the days of a month like cal(1), with weeks starting on `first_weekday` from the config.
+/// Days with events are colored after the highest priority among them
+/// and days with deadlines are marked with '!' colored the same way. Today is highlighted.
+/// Depending on `month_cell` in the config, days also show the number of their events or the name of the first one.
+pub fn display_month(year: i32, month: u32, events: &[(String, Event)], deadlines: &[Deadline]) {
+    let first_weekday = CONFIG.first_weekday.parse().unwrap_or(Weekday::Mon);
+    let width = match CONFIG.month_cell.as_str() {
+        "count" => 7,
+        "name" => 10,
+        _ => 3,
+    };
+    let first = NaiveDate::from_ymd(year, month, 1);
+    let today = Local::now().date_naive();
+
+    let title = first.format("%B %Y").to_string();
+    println!("{}", format!("{title:^0$}", 7 * width + 6).trim_end().bold());
+    let mut weekday = first_weekday;
+    let mut header = vec![];
+    for _ in 0..7 {
+        header.push(format!("{:<width$}", &weekday.to_string()[..2]));
+        weekday = weekday.succ();
+    }
+    println!("{}", header.join(" ").trim_end());
+
+    let offset =
+        (7 + first.weekday().num_days_from_monday() - first_weekday.num_days_from_monday()) % 7;
+    let mut cells = vec![" ".repeat(width); offset as usize];
+    for day in 1..=days_in_month(year, month) {
+        let date = NaiveDate::from_ymd(year, month, day);
+        let day_events: Vec<&Event> = events
+            .iter()
+            .map(|(_, e)| e)
+            .filter(|e| e.start().date_naive() == date)
+            .collect();
+        let event_priority = day_events.iter().map(|e| e.priority()).max();
+        let deadline_priority = deadlines
+            .iter()
+            .filter(|d| d.date().date_naive() == date)
+            .map(|d| d.priority())
+            .max();
+
+        let details = match (CONFIG.month_cell.as_str(), day_events.first()) {
+            ("count", Some(_)) => format!(" ({})", day_events.len()),
+            // names too long for the cell are cut short
+            ("name", Some(e)) if e.name().chars().count() > width - 4 => {
+                format!(" {}…", e.name().chars().take(width - 5).collect::<String>())
+            }
+            ("name", Some(e)) => format!(" {}", e.name()),
+            _ => "".to_string(),
+        };
+        let length = 3 + details.chars().count();
+
+        let mut number = format!("{day:>2}").normal();
+        let mut details = details.normal();
+        if let Some(p) = event_priority {
+            number = colorize_priority(&number, p).bold();
+            if !details.is_empty() {
+                details = colorize_priority(&details, p);
+            }
+        }
+        if date == today {
+            number = number.reversed();
+        }
+        let marker = match deadline_priority {
+            Some(p) => colorize_priority("!", p).bold(),
+            None => " ".normal(),
+        };
+        cells.push(format!("{number}{marker}{details}{}", " ".repeat(width - length)));
+    }
+    for week in cells.chunks(7) {
+        println!("{}", week.join(" ").trim_end());
+    }
+}
+
+/// Rows of a timetable of `date` taken up by `event`, as the first row and the row after the last one,
+/// or `None` if the event does not take place on that day within `hours`.
+fn timetable_rows(event: &Event, date: NaiveDate, hours: (u32, u32)) -> Option<(u32, u32)> {
+    let (start, end) = (event.start().naive_local(), event.end().naive_local());
+    if start.date() > date || end <= date.and_hms(0, 0, 0) {
+        return None;
+    }
+    let first = match start.date() < date {
+        true => hours.0,
+        false => start.hour().max(hours.0),
+    };
+    let last = match end.date() > date {
+        true => hours.1,
+        // an event ending in the middle of an hour takes up its row as well
+        false => (end.hour() + (end.minute() > 0) as u32)
+            .max(first + 1)
+            .min(hours.1),
+    };
+    match first < last {
+        true => Some((first, last)),
+        false => None,
+    }
+}
+
+/// Draws a timetable of the 7 days from `first_day`, with a row for every hour
+/// from `week_start_hour` to `week_end_hour` in the config.
+/// Events are colored after their priority and overlapping events are placed side by side.
+/// The timetable is as wide as the terminal.
+pub fn display_week(first_day: NaiveDate, events: &[(String, Event)]) {
+    let hours = (CONFIG.week_start_hour, CONFIG.week_end_hour);
+    let terminal_width = match terminal_size() {
+        Some((Width(w), _)) => w as usize,
+        None => 80,
+    };
+    // 5 characters for the hours and a border before every day and after the last one
+    let width = ((terminal_width.saturating_sub(6)) / 7)
+        .saturating_sub(1)
+        .max(3);
+    let today = Local::now().date_naive();
+
+    let day_month = format!("%a {}", day_month_layout());
+    let mut header = " ".repeat(5);
+    let mut separator = "─".repeat(5);
+    // events of every day, with the rows they take up and their place among the overlapping ones
+    let mut days = vec![];
+    for i in 0..7 {
+        let date = first_day + Duration::days(i);
+        let label = format!("{:<width$}", date.format(&day_month).to_string());
+        let label: String = label.chars().take(width).collect();
+        header += &format!(
+            "│{}",
+            match date == today {
+                true => label.reversed(),
+                false => label.bold(),
+            }
+        );
+        separator += &format!("┼{}", "─".repeat(width));
+
+        let mut day: Vec<(&Event, (u32, u32))> = events
+            .iter()
+            .filter_map(|(_, e)| timetable_rows(e, date, hours).map(|rows| (e, rows)))
+            .collect();
+        day.sort_by_key(|(_, rows)| *rows);
+        let spans: Vec<(u32, u32)> = day.iter().map(|(_, rows)| *rows).collect();
+        let lanes = assign_lanes(&spans);
+        days.push((day, lanes));
+    }
+    println!("{header}│");
+    println!("{separator}┤");
+
+    for hour in hours.0..hours.1 {
+        let mut line = format!("{hour:02}:00");
+        for (day, lanes) in &days {
+            line += "│";
+            let mut cells = vec![];
+            let mut used = 0;
+            for ((event, rows), (lane, group_lanes)) in day.iter().zip(lanes) {
+                if !(rows.0..rows.1).contains(&hour) {
+                    continue;
+                }
+                // every group of overlapping events divides the whole width of the day
+                let lane_width = width / group_lanes + (*lane < width % group_lanes) as usize;
+                let offset = (0..*lane)
+                    .map(|l| width / group_lanes + (l < width % group_lanes) as usize)
+                    .sum::<usize>();
+                // the name continues in the following rows of the event
+                let text = format!("{} {}", time_fmt(event.start().time()), event.name());
+                let text_width = lane_width.saturating_sub(1);
+                let segment: String = text
+                    .chars()
+                    .skip((hour - rows.0) as usize * text_width)
+                    .take(text_width)
+                    .collect();
+                let cell = format!("┃{segment:<text_width$}");
+                let cell: String = cell.chars().take(lane_width).collect();
+                cells.push((offset, lane_width, colorize_priority(&cell, event.priority())));
+            }
+            cells.sort_by_key(|(offset, _, _)| *offset);
+            for (offset, lane_width, cell) in cells {
+                if offset < used {
+                    continue;
+                }
+                line += &" ".repeat(offset - used);
+                line += &cell.to_string();
+                used = offset + lane_width;
+            }
+            line += &" ".repeat(width - used);
+        }
+        println!("{line}│");
+    }
+}
+
 pub fn colorize_deadline(d: &Deadline) -> String {
     let s = d.to_string();
     let split = s.split('\t').collect::<Vec<&str>>();
     // bold name, colorized everything
-    let out = vec![
+    let out = [
         split[0].bold(),
         split[1].clear(),
         split[2].clear(),
         split[3].clear(),
         split[4].clear(),
+        split[5].clear(),
+        split[6].clear(),
     ]
     .iter()
     .map(|s| s.to_string())
     .collect::<Vec<String>>();
     let x = out.join("\t");
+    // finished deadlines are history and need no attention
+    if d.is_finished() {
+        return x.dimmed().to_string();
+    }
     match d.priority() {
         0..=5 => x.green(),
         6..=8 => x.yellow(),
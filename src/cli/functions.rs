@@ -1,77 +1,122 @@
 use crate::{
     active_calendar, active_calendar_reference,
     cal::{
-        calendar::Calendar, calendar_index::CalendarIndex, calendar_ref::CalendarReference,
-        calmar_error::CalmarError, calmar_trait::CalendarDataType, event::Event,
+        calendar::Calendar,
+        calendar_index::CalendarIndex,
+        calendar_ref::CalendarReference,
+        calmar_error::CalmarError,
+        calmar_trait::CalendarDataType,
+        deadline::Deadline,
+        details::parse_tags,
+        event::Event,
+        id::find_by_key,
+        timezone::{from_wall_clock, parse_timezone},
     },
     calendar_index,
     cli::{
         getdata::{
-            get_date, get_difficulty, get_dir_path, get_duration, get_end_date, get_end_time,
-            get_priority, get_repeat, get_time, get_valid_event_name,
+            get_date, get_details, get_difficulty, get_dir_path, get_duration, get_end_date,
+            get_end_time, get_priority, get_reminders, get_repeat, get_repeat_end, get_time,
+            get_timezone, get_url, get_valid_event_name, parse_into_duration, parse_into_time,
+            parse_weekdays,
         },
+        history::track,
         messages::print_err_msg,
-        repl::get_input,
+        repl::{can_prompt, get_input},
+        script::PromptRejected,
         util::{
-            default_or_custom_save_path, levenshtein_distance, select_in_range,
-            uppercase_first_letter,
+            default_or_custom_save_path, get_now_even, levenshtein_distance, overlap_fmt,
+            reminders_fmt, round_to_full_day, select_in_range, uppercase_first_letter,
+            DATE_FORMATS, TIME_FORMATS,
         },
-        validator::{get_home_dir, validate_duration},
+        validator::{get_home_dir, validate_time},
     },
-    error, warning, CONFIG,
+    error, fail, warning, CONFIG,
 };
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, Weekday};
+use std::{fmt::Display, path::PathBuf, str::FromStr};
 use std::{
-    collections::HashMap,
     sync::{Arc, Mutex},
     thread,
 };
-use std::{fmt::Display, fs::read_to_string, path::PathBuf, str::FromStr};
 
 use super::getdata::get_valid_calendar_name;
 
 /// Create a new event and return it.
-pub fn get_new_event(name: Option<String>) -> Event {
+pub fn get_new_event(name: Option<String>) -> Result<Event, PromptRejected> {
     let name = match name {
         Some(name) => name,
-        None => get_valid_event_name(),
+        None => get_valid_event_name()?,
     };
 
-    let start_date = get_date("Start Date: ");
+    let start_date = get_date("Start Date: ")?;
 
-    let start_time = get_time("Start Time: ");
+    let start_time = get_time("Start Time: ")?;
 
-    let duration = get_duration();
+    let duration = get_duration()?;
 
     let end_date;
     let end_time;
     if duration.is_zero() {
-        end_date = get_end_date(&start_date);
+        end_date = get_end_date(&start_date)?;
 
-        end_time = get_end_time(&start_date, &start_time, &end_date);
+        end_time = get_end_time(&start_date, &start_time, &end_date)?;
     } else {
         let end_timedate = start_date.and_time(start_time).unwrap() + duration;
         end_date = end_timedate.date();
         end_time = end_timedate.time();
     }
 
-    let repeat = get_repeat();
-
-    let difficulty = get_difficulty();
-
-    let priority = get_priority();
-
-    Event::new(
+    get_event_details(
         name,
         start_date.and_time(start_time).unwrap(),
         end_date.and_time(end_time).unwrap(),
-        repeat,
-        priority,
-        difficulty,
-        vec![],
     )
 }
 
+/// Create a new event taking place from `start` to `end` and return it.
+/// Only the name and the properties other than time are asked for.
+pub fn get_new_event_at(
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Result<Event, PromptRejected> {
+    get_event_details(get_valid_event_name()?, start, end)
+}
+
+fn get_event_details(
+    name: String,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Result<Event, PromptRejected> {
+    let repeat = get_repeat(&start)?;
+    let (repeat_until, repeat_count) = match repeat.is_none() {
+        true => (None, None),
+        false => get_repeat_end(&start.date())?,
+    };
+
+    let difficulty = get_difficulty()?;
+
+    let priority = get_priority()?;
+
+    let details = get_details()?;
+
+    let mut event = Event::new(name, start, end, repeat, priority, difficulty, vec![]);
+    event.set_repeat_until(repeat_until);
+    event.set_repeat_count(repeat_count);
+    *event.details_mut() = details;
+    Ok(event)
+}
+
+/// Create a new deadline named `name` and return it.
+pub fn get_new_deadline(name: &str) -> Result<Deadline, PromptRejected> {
+    let date = get_date("Deadline date: ")?
+        .and_time(get_time("Deadline time: ")?)
+        .unwrap();
+    let mut deadline = Deadline::new(name.to_string(), date, get_priority()?);
+    *deadline.details_mut() = get_details()?;
+    Ok(deadline)
+}
+
 pub fn edit_event(event_name: &str) {
     let path = active_calendar_reference!().path();
     let mut active_calendar = active_calendar!();
@@ -80,54 +125,73 @@ pub fn edit_event(event_name: &str) {
         "Select an event to edit",
         event_name,
     ) {
-        Some(i) => i,
-        None => return,
+        Ok(Some(i)) => i,
+        _ => return,
     };
-    let edited_event = &mut active_calendar.events_mut()[idx];
+    let before = active_calendar.clone();
+    if edit_event_properties(&mut active_calendar.events_mut()[idx]).is_err() {
+        return;
+    }
+
+    if !check_new_conflicts(&before, &active_calendar) {
+        return;
+    }
+    if let Err(e) = save_calendar(&active_calendar, &path) {
+        print_err_msg(e, &path)
+    }
+}
 
+/// Asks which property of `edited_event` to change and changes it.
+fn edit_event_properties(edited_event: &mut Event) -> Result<(), PromptRejected> {
     // Choose a property to be edited
     let fields = Event::FIELD_NAMES_AS_ARRAY.to_vec();
     let mut fields_list: Vec<String> = fields.into_iter().map(uppercase_first_letter).collect();
     // Duration is not a struct property, but it still should be easily editable
     fields_list.insert(2, "Duration".to_string());
+    // Details are stored together, but edited one by one
+    fields_list.extend(
+        ["Description", "Location", "URL", "Tags", "Reminders"]
+            .iter()
+            .map(|f| f.to_string()),
+    );
 
     fields_list
         .iter()
         .enumerate()
         .for_each(|(i, field)| println!("{}. {field}", i + 1));
 
-    let num: usize = select_in_range("Select what to edit", fields_list.len());
+    let num: usize = select_in_range("Select what to edit", fields_list.len())?;
 
     match num {
         // Edit name
         1 => {
             print!("Name: ");
-            edited_event.set_name(&get_valid_event_name());
+            edited_event.set_name(&get_valid_event_name()?);
         }
         // Edit start timedate
         2 => {
             println!("1. Start date\n2. Start time\n3. Start datetime");
-            let num = select_in_range("Select what to edit", 3);
+            let num = select_in_range("Select what to edit", 3)?;
             let current_end = edited_event.end();
             let current_start = edited_event.start();
 
             if num == 1 || num == 3 {
                 print!("Start date: ");
-                let mut new_start_date = get_date("Start Date: ");
+                let mut new_start_date = get_date("Start Date: ")?;
                 while new_start_date.and_time(current_start.time()).unwrap() > current_end {
                     println!("Start timedate cannot be after end timedate");
                     print!("Start date: ");
-                    new_start_date = get_date("Start Date: ");
+                    new_start_date = get_date("Start Date: ")?;
                 }
                 edited_event.set_start(&new_start_date.and_time(current_start.time()).unwrap())
             }
             if num == 2 || num == 3 {
                 print!("Start time: ");
-                let mut new_start_time = get_time("Start Time: ");
+                let mut new_start_time = get_time("Start Time: ")?;
                 while current_start.date().and_time(new_start_time).unwrap() > current_end {
                     println!("Start timedate cannot be after end timedate");
                     print!("Start date: ");
-                    new_start_time = get_time("Start Time: ");
+                    new_start_time = get_time("Start Time: ")?;
                 }
                 edited_event.set_start(&current_start.date().and_time(new_start_time).unwrap())
             }
@@ -135,32 +199,35 @@ pub fn edit_event(event_name: &str) {
         // Edit duration
         3 => {
             print!("Duration: ");
-            let new_duration = get_duration();
+            let new_duration = get_duration()?;
             let start = edited_event.start();
             edited_event.set_end(&(start + new_duration));
         }
         // Edit end datetime
         4 => {
             println!("1. End date\n2. End time\n3. End datetime");
-            let num: usize = select_in_range("Select what to edit", 3);
+            let num: usize = select_in_range("Select what to edit", 3)?;
             let mut current_end = edited_event.end();
             let current_start = edited_event.start();
 
             if num == 1 || num == 3 {
                 print!("End date: ");
-                let mut new_end_date = get_end_date(&current_start.date());
+                let mut new_end_date = get_end_date(&current_start.date())?;
                 while new_end_date.and_time(current_end.time()).unwrap() < current_start {
                     println!("End timedate cannot be before start timedate");
                     print!("End date: ");
-                    new_end_date = get_end_date(&current_start.date());
+                    new_end_date = get_end_date(&current_start.date())?;
                 }
                 edited_event.set_end(&new_end_date.and_time(current_end.time()).unwrap());
             }
             if num == 2 || num == 3 {
                 current_end = edited_event.end();
                 print!("End time: ");
-                let mut new_end_time =
-                    get_end_time(&current_start.date(), &current_start.time(), &current_end.date());
+                let mut new_end_time = get_end_time(
+                    &current_start.date(),
+                    &current_start.time(),
+                    &current_end.date(),
+                )?;
                 while current_end.date().and_time(new_end_time).unwrap() < current_start {
                     println!("End timedate cannot be before start timedate");
                     print!("End date: ");
@@ -168,7 +235,7 @@ pub fn edit_event(event_name: &str) {
                         &current_start.date(),
                         &current_start.time(),
                         &edited_event.end().date(),
-                    );
+                    )?;
                 }
                 edited_event.set_end(&current_end.date().and_time(new_end_time).unwrap());
             }
@@ -176,35 +243,132 @@ pub fn edit_event(event_name: &str) {
         // Edit repeat
         5 => {
             print!("Repeat: ");
-            edited_event.set_repeat(&get_repeat())
+            let start = edited_event.start();
+            let repeat = get_repeat(&start)?;
+            let (until, count) = match repeat.is_none() {
+                true => (None, None),
+                false => get_repeat_end(&start.date())?,
+            };
+            edited_event.set_repeat(&repeat);
+            edited_event.set_repeat_until(until);
+            edited_event.set_repeat_count(count);
         }
         // Edit priority
         6 => {
             print!("Priority: ");
-            edited_event.set_priority(get_priority())
+            edited_event.set_priority(get_priority()?)
         }
         // Edit difficulty
         7 => {
             print!("Difficulty: ");
-            edited_event.set_difficulty(get_difficulty())
+            edited_event.set_difficulty(get_difficulty()?)
+        }
+        // Edit time zone
+        9 => edited_event.set_timezone(get_timezone()?),
+        // Edit details, starting from their current values
+        10 => {
+            let current = edited_event.details().description().map(str::to_string);
+            let description = get_input("Description: ", current.as_deref())?;
+            edited_event.details_mut().set_description(&description)
+        }
+        11 => {
+            let current = edited_event.details().location().map(str::to_string);
+            let location = get_input("Location: ", current.as_deref())?;
+            edited_event.details_mut().set_location(&location)
+        }
+        12 => {
+            let current = edited_event.details().url().map(str::to_string);
+            let url = get_url("URL: ", current.as_deref())?;
+            edited_event.details_mut().set_url(&url)
+        }
+        13 => {
+            let current = edited_event.details().tags_fmt();
+            let tags = get_input("Tags (separated by commas): ", Some(&current))?;
+            edited_event.details_mut().set_tags(parse_tags(&tags))
+        }
+        14 => {
+            let current = reminders_fmt(edited_event.details().reminders());
+            let reminders = get_reminders("Reminders (such as 15m, 1d): ", Some(&current))?;
+            edited_event.details_mut().set_reminders(reminders)
         }
         _ => panic!("Impossible"),
     }
+    Ok(())
+}
 
-    if let Err(e) = active_calendar.save(&path) {
-        print_err_msg(e, &path)
+/// Asks which property of `deadline` to change and changes it.
+pub fn edit_deadline_properties(deadline: &mut Deadline) -> Result<(), PromptRejected> {
+    let fields = [
+        "Name",
+        "Date",
+        "Time",
+        "Priority",
+        "Description",
+        "Location",
+        "URL",
+        "Tags",
+        "Reminders",
+    ];
+    fields
+        .iter()
+        .enumerate()
+        .for_each(|(i, field)| println!("{}. {field}", i + 1));
+
+    let num: usize = select_in_range("Select what to edit", fields.len())?;
+    let date = deadline.date();
+    match num {
+        1 => {
+            let mut name = get_input("Name: ", Some(&deadline.name()))?;
+            while name.trim().is_empty() {
+                warning!("Deadline name cannot be an empty string");
+                name = get_input("Name: ", None)?;
+            }
+            deadline.set_name(&name)
+        }
+        2 => deadline.set_date(get_date("Deadline date: ")?.and_time(date.time()).unwrap()),
+        3 => deadline.set_date(date.date().and_time(get_time("Deadline time: ")?).unwrap()),
+        4 => deadline.set_priority(get_priority()?),
+        5 => {
+            let current = deadline.details().description().map(str::to_string);
+            let description = get_input("Description: ", current.as_deref())?;
+            deadline.details_mut().set_description(&description)
+        }
+        6 => {
+            let current = deadline.details().location().map(str::to_string);
+            let location = get_input("Location: ", current.as_deref())?;
+            deadline.details_mut().set_location(&location)
+        }
+        7 => {
+            let current = deadline.details().url().map(str::to_string);
+            let url = get_url("URL: ", current.as_deref())?;
+            deadline.details_mut().set_url(&url)
+        }
+        8 => {
+            let current = deadline.details().tags_fmt();
+            let tags = get_input("Tags (separated by commas): ", Some(&current))?;
+            deadline.details_mut().set_tags(parse_tags(&tags))
+        }
+        9 => {
+            let current = reminders_fmt(deadline.details().reminders());
+            let reminders = get_reminders("Reminders (such as 15m, 1d): ", Some(&current))?;
+            deadline.details_mut().set_reminders(reminders)
+        }
+        _ => panic!("Impossible"),
     }
+    Ok(())
 }
 
 /// Create a calendar reference and return it.
-pub fn get_new_calendar_reference(name: Option<String>) -> CalendarReference {
+pub fn get_new_calendar_reference(
+    name: Option<String>,
+) -> Result<CalendarReference, PromptRejected> {
     let name = match name {
         Some(name) => name,
-        None => get_input("Calendar Name: ", None),
+        None => get_input("Calendar Name: ", None)?,
     };
 
     //    print!("Path: ");
-    let path = default_or_custom_save_path(get_dir_path());
+    let path = default_or_custom_save_path(get_dir_path()?);
     let mut path_to_calendar = PathBuf::from(path).join(&name);
     path_to_calendar.set_extension("json");
     let path_to_calendar_string = match path_to_calendar.to_str() {
@@ -214,20 +378,31 @@ pub fn get_new_calendar_reference(name: Option<String>) -> CalendarReference {
             std::process::exit(1);
         }
     };
-    CalendarReference::new(name, path_to_calendar_string.to_owned(), false)
+    Ok(CalendarReference::new(name, path_to_calendar_string.to_owned(), false))
 }
 
 pub fn generate_until(calendar: &Calendar, end: DateTime<Local>) -> Vec<Event> {
+    generate_between(calendar, Local::now(), end)
+}
+
+/// Returns the occurrences of recurring events that start before `end`
+/// and have not ended by `start`, along with every event that does not repeat, sorted.
+pub fn generate_between(
+    calendar: &Calendar,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Vec<Event> {
     let event_vec = Arc::new(Mutex::new(vec![]));
     let mut threads = vec![];
     let events = calendar.events().to_vec();
+    let names: Vec<String> = events.iter().map(|e| e.name()).collect();
 
     for event in events {
         threads.push(thread::spawn({
             let clone = Arc::clone(&event_vec);
             move || {
                 // If the event is not recurring, just push its only occurrence and return
-                if event.repeat().is_zero() {
+                if event.repeat().is_none() {
                     let mut v = clone.lock().unwrap();
                     if event.exceptions().contains(&event.start()) {
                         return;
@@ -235,31 +410,31 @@ pub fn generate_until(calendar: &Calendar, end: DateTime<Local>) -> Vec<Event> {
                     v.push(event);
                     return;
                 }
+                let duration = event.duration();
                 let mut temp_vec = vec![];
-                let now = Local::now();
-                let mut e_to_push = event.to_owned();
-                let mut new_start = e_to_push.start();
-                let mut new_end = new_start + e_to_push.duration();
-                while new_start < end {
-                    let mut e = e_to_push.clone();
-                    e.set_end(&new_end);
-                    if (e.start() >= now || e.is_happening_on(now))
+                for occurrence in event.occurrences() {
+                    if occurrence >= end {
+                        break;
+                    }
+                    let mut e = event.clone();
+                    e.set_start(&occurrence);
+                    e.set_end(&(occurrence + duration));
+                    if (e.start() >= start || e.is_happening_on(start))
                         && !event.exceptions().contains(&e.start())
                     {
                         temp_vec.push(e);
                     }
-                    new_start += e_to_push.repeat();
-                    new_end = new_start + e_to_push.duration();
-                    e_to_push.set_start(&new_start);
-                    e_to_push.set_end(&new_end);
                 }
                 let mut v = clone.lock().unwrap();
                 v.append(&mut temp_vec);
             }
         }))
     }
-    for t in threads {
-        t.join().unwrap()
+    // an event that cannot be expanded is left out instead of failing the whole command
+    for (name, t) in names.iter().zip(threads) {
+        if t.join().is_err() {
+            warning!("Failed to list the occurrences of {name}.");
+        }
     }
 
     // get the Vec<Event> out of Arc and Mutex
@@ -272,24 +447,221 @@ pub fn generate_until(calendar: &Calendar, end: DateTime<Local>) -> Vec<Event> {
     out
 }
 
+/// Returns the end of the time span `arg` counted from now,
+/// or of the default span from the config if `arg` is `None`.
+/// If the user typed something like '3d', the end is rounded to a full day for convenience.
+/// Prints a warning and returns `None` if `arg` is not a valid duration.
+pub fn span_end(arg: Option<&str>) -> Option<DateTime<Local>> {
+    let span = arg.unwrap_or(&CONFIG.default_calendar_span);
+    let end = match parse_into_duration(span) {
+        Ok(span) => get_now_even() + span,
+        Err(e) => {
+            fail!("{span} is not a valid duration input. {e}.");
+            return None;
+        }
+    };
+    let re_days = regex::Regex::new("^[0-9]+ *(d|days?|w|wks?|weeks?)$").unwrap();
+    match arg.is_some() && re_days.is_match(span) {
+        true => Some(round_to_full_day(end)),
+        false => Some(end),
+    }
+}
+
+/// Copies of `event` moved to each of its occurrences that start before `end` and have not ended by `start`.
+/// Exceptions are left out.
+pub fn occurrences_between(
+    event: &Event,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Vec<Event> {
+    let duration = event.duration();
+    event
+        .occurrences()
+        .skip_while(|s| *s + duration <= start)
+        .take_while(|s| *s < end)
+        .filter(|s| !event.exceptions().contains(s))
+        .map(|s| {
+            let mut e = event.clone();
+            e.set_start(&s);
+            e.set_end(&(s + duration));
+            e
+        })
+        .collect()
+}
+
+/// Returns every pair of overlapping events in `events`, which have to be sorted by start.
+pub fn find_conflicts(events: &[Event]) -> Vec<(Event, Event)> {
+    let mut conflicts = vec![];
+    for (i, a) in events.iter().enumerate() {
+        for b in events[i + 1..].iter().take_while(|b| b.start() < a.end()) {
+            if a.start() < b.end() {
+                conflicts.push((a.clone(), b.clone()));
+            }
+        }
+    }
+    conflicts
+}
+
+/// The part of the week free time is looked for in, read from the config.
+pub struct WorkingHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub days: Vec<Weekday>,
+    pub buffer_before: Duration,
+    pub buffer_after: Duration,
+}
+
+impl WorkingHours {
+    /// Working hours set in the config. The values are verified by `check_config` at startup.
+    pub fn from_config() -> Self {
+        WorkingHours {
+            start: parse_into_time(&CONFIG.work_day_start),
+            end: parse_into_time(&CONFIG.work_day_end),
+            days: parse_weekdays(&CONFIG.work_days).unwrap_or_default(),
+            buffer_before: parse_into_duration(&CONFIG.buffer_before)
+                .unwrap_or_else(|_| Duration::zero()),
+            buffer_after: parse_into_duration(&CONFIG.buffer_after)
+                .unwrap_or_else(|_| Duration::zero()),
+        }
+    }
+}
+
+/// Returns the periods between `from` and `until` at least `length` long
+/// in which none of `events` take place, limited to working hours.
+/// Events are surrounded by the buffers of `hours`, so a period never starts right after an event.
+pub fn find_free_slots(
+    events: &[Event],
+    from: DateTime<Local>,
+    until: DateTime<Local>,
+    length: Duration,
+    hours: &WorkingHours,
+) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+    let mut busy: Vec<(DateTime<Local>, DateTime<Local>)> = events
+        .iter()
+        .map(|e| (e.start() - hours.buffer_before, e.end() + hours.buffer_after))
+        .collect();
+    busy.sort();
+
+    let mut slots = vec![];
+    let mut date = from.date_naive();
+    while date <= until.date_naive() {
+        let day = (
+            from_wall_clock(date.and_time(hours.start), None),
+            from_wall_clock(date.and_time(hours.end), None),
+        );
+        if let (true, (Some(day_start), Some(day_end))) =
+            (hours.days.contains(&date.weekday()), day)
+        {
+            let day_start = day_start.max(from);
+            let day_end = day_end.min(until);
+            let mut free_from = day_start;
+            for (start, end) in busy.iter().filter(|(s, e)| *e > day_start && *s < day_end) {
+                if *start - free_from >= length {
+                    slots.push((free_from, *start));
+                }
+                free_from = free_from.max(*end);
+            }
+            if day_end - free_from >= length {
+                slots.push((free_from, day_end));
+            }
+        }
+        date = date.succ();
+    }
+    slots
+}
+
+/// Places spans of rows, such as the hours events take up in a timetable, side by side.
+/// `spans` are pairs of the first row and the row after the last one, sorted by the first row.
+/// Returns the column of every span and the number of columns of the group of overlapping spans it belongs to.
+pub fn assign_lanes(spans: &[(u32, u32)]) -> Vec<(usize, usize)> {
+    let mut lanes: Vec<(usize, usize)> = vec![];
+    // row after the last span in every column of the current group
+    let mut ends: Vec<u32> = vec![];
+    let mut group_start = 0;
+    for (i, (first, last)) in spans.iter().enumerate() {
+        // a span starting after every column has ended begins a new group
+        if ends.iter().all(|end| end <= first) {
+            for lane in &mut lanes[group_start..] {
+                *lane = (lane.0, ends.len());
+            }
+            ends.clear();
+            group_start = i;
+        }
+        match ends.iter().position(|end| end <= first) {
+            Some(lane) => {
+                ends[lane] = *last;
+                lanes.push((lane, 0));
+            }
+            None => {
+                ends.push(*last);
+                lanes.push((ends.len() - 1, 0));
+            }
+        }
+    }
+    for lane in &mut lanes[group_start..] {
+        *lane = (lane.0, ends.len());
+    }
+    lanes
+}
+
+/// Warns about overlaps within the default span that `after` has and `before` does not.
+/// Returns `false` if such overlaps are blocked by `overlap_policy` in the config,
+/// in which case `after` should not be saved.
+pub fn check_new_conflicts(before: &Calendar, after: &Calendar) -> bool {
+    let end = match span_end(None) {
+        Some(end) => end,
+        None => return true,
+    };
+    let old_conflicts = find_conflicts(&generate_until(before, end));
+    let new_conflicts: Vec<(Event, Event)> = find_conflicts(&generate_until(after, end))
+        .into_iter()
+        .filter(|c| !old_conflicts.contains(c))
+        .collect();
+    for (a, b) in &new_conflicts {
+        warning!("{} overlaps with {} ({}).", a.name(), b.name(), overlap_fmt(a, b));
+    }
+    if !new_conflicts.is_empty() && CONFIG.overlap_policy == "block" {
+        error!("Overlapping events are not allowed by overlap_policy in the config. Changes were not saved.");
+        return false;
+    }
+    true
+}
+
 pub fn handle_unknown_command(s: &str) {
     // command shortcuts such as `ls` might be added here later
     let command_list = [
         "add",
+        "briefing",
         "cal",
         "clear",
+        "conflicts",
+        "done",
         "duration",
         "edit",
+        "edit-deadline",
+        "export-ics",
+        "find",
+        "free",
         "help",
+        "import-ics",
         "list",
         "listcal",
+        "month",
+        "postpone",
+        "progress",
         "raw",
+        "redo",
         "remove",
         "removecal",
+        "reopen",
         "set",
+        "show",
         "sort",
+        "undo",
         "until",
         "quit",
+        "watch",
+        "week",
         "write",
     ];
 
@@ -308,30 +680,21 @@ pub fn handle_unknown_command(s: &str) {
     // If the match would be somewhat helpful
     // (distance has to be small, hence 0.8 multiplier) print the suggestion
     if (min_distance as f32) < (s.len() as f32) {
-        warning!("Unknown command: {}. Did you mean '{}'?", s.trim(), best_match);
+        fail!("Unknown command: {}. Did you mean '{}'?", s.trim(), best_match);
         return;
     }
-    warning!("Unknown command: {}", s.trim())
+    fail!("Unknown command: {}", s.trim());
 }
 
-pub fn closest_occurence_start(event: &Event) -> DateTime<Local> {
+pub fn closest_occurence_start(event: &Event) -> Option<DateTime<Local>> {
     // Searches for the closest occurence of an event.
     // if the event is currently happening, return start datetime of the current occurence.
     // if it's not, return start datetime of the next occurence
+    // if the event has no more occurences, return None
 
-    let mut start = event.start();
     let now = Local::now();
-
-    let is_happenning =
-        |event: &Event| event.start() < now && event.start() + event.duration() > now;
-
-    while start < now {
-        start += event.repeat();
-    }
-    if is_happenning(event) {
-        start -= event.repeat()
-    }
-    start
+    let duration = event.duration();
+    event.occurrences().find(|start| *start + duration > now)
 }
 
 pub fn check_calmar_dir() {
@@ -340,7 +703,11 @@ pub fn check_calmar_dir() {
         return;
     }
     error!("{} doesn't exist. Do you want to create it?", path.display());
-    match get_input("[Y/n]: ", None).to_lowercase().trim() {
+    match get_input("[Y/n]: ", None)
+        .unwrap_or_default()
+        .to_lowercase()
+        .trim()
+    {
         "yes" | "y" => warning!(
             "Use the \"mkindex\" command to generate an empty index.json in the created directory."
         ),
@@ -353,8 +720,10 @@ pub fn check_calmar_dir() {
 
 // Verify config values
 pub fn check_config() {
-    let permitted_date_formats = ["DD/MM/YYYY"];
-    let permitted_time_formats = ["HH:MM"];
+    let permitted_date_formats = DATE_FORMATS.map(|(format, _)| format);
+    let permitted_time_formats = TIME_FORMATS.map(|(format, _)| format);
+    let permitted_overlap_policies = ["warn", "block"];
+    let permitted_month_cells = ["none", "count", "name"];
     let mut permitted_colors = [
         "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
     ]
@@ -375,9 +744,9 @@ pub fn check_config() {
         error!("{warning}{} is not a valid time format.\nSupported formats: {permitted_time_formats:?}", &CONFIG.time_format);
         std::process::exit(1);
     }
-    if !validate_duration(&CONFIG.default_calendar_span) {
+    if let Err(e) = parse_into_duration(&CONFIG.default_calendar_span) {
         error!(
-            "{warning}{} is not a valid duration.\nExamples of valid durations: '7d', '10h', '15m'",
+            "{warning}{} is not a valid duration. {e}.\nExamples of valid durations: '7d', '10h', '1h 30m'",
             CONFIG.default_calendar_span
         );
         std::process::exit(1);
@@ -389,19 +758,87 @@ pub fn check_config() {
         );
         std::process::exit(1);
     }
+    if !permitted_overlap_policies.contains(&CONFIG.overlap_policy.as_str()) {
+        error!(
+            "{warning}{} is not a valid overlap policy.\nValid policies: {permitted_overlap_policies:?}",
+            CONFIG.overlap_policy
+        );
+        std::process::exit(1);
+    }
+    if !CONFIG.second_timezone.is_empty() && parse_timezone(&CONFIG.second_timezone).is_none() {
+        error!(
+            "{warning}{} is not a valid time zone.\nExamples of valid time zones: 'Europe/Warsaw', 'America/New_York'",
+            CONFIG.second_timezone
+        );
+        std::process::exit(1);
+    }
+    for time in [&CONFIG.work_day_start, &CONFIG.work_day_end] {
+        if time.trim().is_empty() || !validate_time(time) {
+            error!(
+                "{warning}{time} is not a valid time.\nExamples of valid times: '08:00', '17:30'"
+            );
+            std::process::exit(1);
+        }
+    }
+    if parse_into_time(&CONFIG.work_day_start) >= parse_into_time(&CONFIG.work_day_end) {
+        error!(
+            "{warning}Working hours have to end after they start. Got: {} - {}",
+            CONFIG.work_day_start, CONFIG.work_day_end
+        );
+        std::process::exit(1);
+    }
+    if parse_weekdays(&CONFIG.work_days).is_none_or(|days| days.is_empty()) {
+        error!(
+            "{warning}{} is not a valid list of days.\nExample of a valid list: 'mon tue wed thu fri'",
+            CONFIG.work_days
+        );
+        std::process::exit(1);
+    }
+    if CONFIG.first_weekday.parse::<Weekday>().is_err() {
+        error!(
+            "{warning}{} is not a valid day of the week.\nExamples of valid days: 'mon', 'sun'",
+            CONFIG.first_weekday
+        );
+        std::process::exit(1);
+    }
+    if !permitted_month_cells.contains(&CONFIG.month_cell.as_str()) {
+        error!(
+            "{warning}{} is not a valid month cell content.\nValid contents: {permitted_month_cells:?}",
+            CONFIG.month_cell
+        );
+        std::process::exit(1);
+    }
+    if CONFIG.week_start_hour >= CONFIG.week_end_hour || CONFIG.week_end_hour > 24 {
+        error!(
+            "{warning}{} - {} are not valid hours of a week view.\nThe first hour has to be lower than the last one, which is at most 24.",
+            CONFIG.week_start_hour, CONFIG.week_end_hour
+        );
+        std::process::exit(1);
+    }
+    for buffer in [&CONFIG.buffer_before, &CONFIG.buffer_after] {
+        if let Err(e) = parse_into_duration(buffer) {
+            error!(
+                "{warning}{buffer} is not a valid duration. {e}.\nExamples of valid durations: '10m', '1h'"
+            );
+            std::process::exit(1);
+        }
+    }
 }
 
 /// Adds a new `CalendarReference` to `self.calendars`.
 ///
 /// # Executed steps
 /// * Check for `CalendarReference`s with calendars named like the new one.
-/// Remove those entries and associated files if the user agrees.
+///   Remove those entries and associated files if the user agrees.
 ///
 /// * Check for `CalendarReference`s with a path like the new one.
-/// Remove those entries and associated files if the user agrees.
+///   Remove those entries and associated files if the user agrees.
 ///
 /// * Push the new `CalendarReference` to the `self.calendars`.
-pub fn add_entry(i: &mut CalendarIndex, new_calendar: &CalendarReference) {
+pub fn add_entry(
+    i: &mut CalendarIndex,
+    new_calendar: &CalendarReference,
+) -> Result<(), PromptRejected> {
     if i.calendars()
         .iter()
         .map(|r| r.name())
@@ -414,18 +851,19 @@ pub fn add_entry(i: &mut CalendarIndex, new_calendar: &CalendarReference) {
             )
             .as_str(),
             None,
-        )
+        )?
         .to_lowercase()
         .as_str()
         {
             "y" | "yes" => {}
-            _ => return,
+            _ => return Ok(()),
         }
 
         // Remove all calendar files with the same name
         for reference in i.calendars() {
             if reference.name() == new_calendar.name() {
-                if let Err(e) = std::fs::remove_file(&reference.path()) {
+                track(&reference.path());
+                if let Err(e) = std::fs::remove_file(reference.path()) {
                     error!("Failed to delete file {}.\n{}", reference.path(), e);
                     std::process::exit(1);
                 }
@@ -448,16 +886,17 @@ pub fn add_entry(i: &mut CalendarIndex, new_calendar: &CalendarReference) {
             )
             .as_str(),
             None,
-        )
+        )?
         .as_str()
         {
             "y" | "yes" => {}
-            _ => return,
+            _ => return Ok(()),
         }
         // Remove all calendar files with the same path
         for reference in i.calendars() {
             if reference.path() == new_calendar.path() {
-                if let Err(e) = std::fs::remove_file(&reference.path()) {
+                track(&reference.path());
+                if let Err(e) = std::fs::remove_file(reference.path()) {
                     error!("Failed to delete file {}.\n{}", reference.path(), e);
                     std::process::exit(1);
                 }
@@ -469,63 +908,51 @@ pub fn add_entry(i: &mut CalendarIndex, new_calendar: &CalendarReference) {
     }
     // Now the index is cleaned of any calendars named like the new one and the files are deleted.
     i.calendars_mut().push(new_calendar.clone());
+    Ok(())
 }
 
-/// Deletes an entry from index by name.
+/// Deletes an entry from index by name or ID and returns the name of the deleted calendar.
 /// Disallows unambigous situations where the number of `CalendarReference`s
-/// named `name` is not equal to one
-pub fn delete_entry(i: &mut CalendarIndex, name: String) {
-    let mut tmp_reference_vec = i.calendars().clone();
-    tmp_reference_vec.retain(|r| r.name() == name);
-
-    match tmp_reference_vec.len() {
-        0 => {
-            warning!("No calendar named {} found.", name);
-            return;
+/// `key` refers to is not equal to one
+pub fn delete_entry(i: &mut CalendarIndex, key: &str) -> Option<String> {
+    let idx = match i.find(key)[..] {
+        [] => {
+            fail!("No calendar named {} found.", key);
+            return None;
         }
-        1 => match std::fs::remove_file(&tmp_reference_vec[0].path()) {
-            Ok(_) => (),
-            Err(e) => {
-                error!(
-                    "Failed to remove file {}. Removing reference from index.\n{}",
-                    tmp_reference_vec[0].path(),
-                    e
-                );
-            }
-        },
+        [idx] => idx,
         _ => {
-            error!("Multiple calendars named {} found. Please fix index.json before proceeding. Calendars must have unique names.", name);
-            return;
+            error!("Multiple calendars named {} found. Use the ID of one, or fix index.json before proceeding. Calendars must have unique names.", key);
+            return None;
         }
+    };
+    let reference = i.calendars_mut().remove(idx);
+    track(&reference.path());
+    if let Err(e) = std::fs::remove_file(reference.path()) {
+        error!(
+            "Failed to remove file {}. Removing reference from index.\n{}",
+            reference.path(),
+            e
+        );
     }
-    i.calendars_mut().retain(|r| r.name() != name);
+    Some(reference.name())
 }
 
 pub fn edit_calendar(name: &str) {
     let mut index = calendar_index!();
-    match index
-        .calendars()
-        .iter()
-        .filter(|r| r.name() == name)
-        .count()
-    {
-        0 => {
-            warning!("No calendar named {name}");
+    let idx = match index.find(name)[..] {
+        [] => {
+            fail!("No calendar named {name}");
             return;
         }
-        1 => {}
-        x => {
-            warning!("{x} calendars named {name}. There should be only one. Please fix index.json and retry.");
+        [idx] => idx,
+        ref x => {
+            fail!("{} calendars named {name}. There should be only one. Use the ID of one, or fix index.json and retry.", x.len());
             return;
         }
-    }
+    };
 
-    let mut edited_ref = &mut CalendarReference::new("".to_string(), "".to_string(), false);
-    for r in index.calendars_mut() {
-        if r.name() == name {
-            edited_ref = r;
-        }
-    }
+    let edited_ref = &mut index.calendars_mut()[idx];
     println!("{edited_ref}");
 
     let fields = CalendarReference::FIELD_NAMES_AS_ARRAY.to_vec();
@@ -534,23 +961,22 @@ pub fn edit_calendar(name: &str) {
         .iter()
         .enumerate()
         .for_each(|(i, f)| println!("{}. {f}", i + 1));
-    let num: usize = select_in_range("Select what to edit", fields_list.len());
+    let num: usize = match select_in_range("Select what to edit", fields_list.len()) {
+        Ok(n) => n,
+        Err(_) => return,
+    };
 
     match num {
         1 => {
-            let new_name = get_valid_calendar_name();
-            edited_ref.set_name(new_name.clone());
-            let cal_str = match read_to_string(edited_ref.path()) {
-                Ok(s) => s,
-                Err(e) => {
-                    error!("Failed to read {}.\n{}", edited_ref.path(), e);
-                    return;
-                }
+            let new_name = match get_valid_calendar_name() {
+                Ok(n) => n,
+                Err(_) => return,
             };
-            let mut cal: Calendar = match serde_json::from_str(&cal_str) {
+            edited_ref.set_name(new_name.clone());
+            let mut cal = match Calendar::get(&edited_ref.path()) {
                 Ok(c) => c,
                 Err(e) => {
-                    print_err_msg(CalmarError::ParseJSON { e }, &"".to_string());
+                    print_err_msg(e, edited_ref.path());
                     return;
                 }
             };
@@ -559,6 +985,8 @@ pub fn edit_calendar(name: &str) {
             new_filename.pop();
             let new_filename = new_filename.join(new_name.clone() + ".json");
             cal.set_name(new_name);
+            track(&edited_ref.path());
+            track(&new_filename.to_string_lossy());
             if let Err(e) = std::fs::rename(edited_ref.path(), &new_filename) {
                 error!(
                     "Failed to rename {} to {}.\n{e}",
@@ -568,13 +996,18 @@ pub fn edit_calendar(name: &str) {
                 return;
             }
             edited_ref.set_path(new_filename.to_str().unwrap().to_string());
-            if let Err(e) = cal.save(&new_filename.to_str().unwrap().to_string()) {
+            if let Err(e) = cal.save(new_filename.to_str().unwrap()) {
                 print_err_msg(e, edited_ref.path());
             }
         }
         2 => {
-            let new_path = get_dir_path() + "/" + &edited_ref.name() + ".json";
+            let new_path = match get_dir_path() {
+                Ok(dir) => dir + "/" + &edited_ref.name() + ".json",
+                Err(_) => return,
+            };
 
+            track(&edited_ref.path());
+            track(&new_path);
             if let Err(e) = std::fs::copy(edited_ref.path(), &new_path) {
                 error!("Failed to copy from {} to {new_path}.\n{e}", edited_ref.path());
                 return;
@@ -592,52 +1025,258 @@ pub fn edit_calendar(name: &str) {
                 edited_ref.set_inactive()
             }
         }
+        4 => match get_timezone() {
+            Ok(tz) => edited_ref.set_timezone(tz),
+            Err(_) => return,
+        },
         _ => {
             println!("Impossible")
         }
     }
-    if let Err(e) = index.save() {
+    if let Err(e) = save_index(&index) {
         print_err_msg(e, &CONFIG.index_path);
     }
 }
 
-pub fn choose_struct_idx<T: CalendarDataType + Clone + Display, S: Display>(
+/// Asks whether to overwrite `path`, which was changed by another program since it was read.
+fn confirm_overwrite(path: &str) -> bool {
+    if !can_prompt() {
+        return false;
+    }
+    matches!(
+        get_input(
+            format!("{path} was changed by another program since it was read. Overwrite it with this change? [y/N]: ").as_str(),
+            None,
+        )
+        .unwrap_or_default()
+        .to_lowercase()
+        .as_str(),
+        "y" | "yes"
+    )
+}
+
+/// Saves `calendar` to `path`. If the file was changed by another program since it was read,
+/// asks whether to overwrite it. Otherwise the change is dropped and `CalmarError::ChangedOnDisk` returned,
+/// so that the command can be run again on the current contents.
+pub fn save_calendar(calendar: &Calendar, path: &str) -> Result<(), CalmarError> {
+    match calendar.save(path) {
+        Err(CalmarError::ChangedOnDisk { path }) if confirm_overwrite(&path) => {
+            calendar.overwrite(&path)
+        }
+        result => result,
+    }
+}
+
+/// Saves `index` like `save_calendar` saves a calendar.
+pub fn save_index(index: &CalendarIndex) -> Result<(), CalmarError> {
+    match index.save() {
+        Err(CalmarError::ChangedOnDisk { path }) if confirm_overwrite(&path) => index.overwrite(),
+        result => result,
+    }
+}
+
+/// Returns the calendar named `name` or the active calendar if `name` is `None`,
+/// along with the path to its file. On error, prints a message and returns `None`.
+pub fn named_or_active_calendar(
+    index: &CalendarIndex,
+    name: Option<&str>,
+) -> Option<(Calendar, String)> {
+    let reference = match name {
+        Some(name) => match index.reference_named(name) {
+            Some(r) => r.clone(),
+            None => {
+                fail!("No calendar named {name}, or more than one");
+                return None;
+            }
+        },
+        None => match index.active_calendar_reference() {
+            Ok(r) => r,
+            Err(e) => {
+                print_err_msg(e, &CONFIG.index_path);
+                return None;
+            }
+        },
+    };
+    match Calendar::get(&reference.path()) {
+        Ok(c) => Some((c, reference.path())),
+        Err(e) => {
+            print_err_msg(e, reference.path());
+            None
+        }
+    }
+}
+
+/// Calendars read together, each with the name of its reference.
+pub type NamedCalendars = Vec<(String, Calendar)>;
+
+/// Returns the calendars a command showing events of several calendars reads, each with its name,
+/// and the arguments left after removing the options that select them.
+/// # Options
+/// * `--all`, `-a`: every calendar in the index.
+/// * `--calendars`, `-c` followed by comma separated names: the calendars named so.
+///
+/// Without them, the active calendar and the calendars in `visible_calendars` from the config are read.
+/// On error, prints a message and returns `None`.
+pub fn agenda_calendars<'a>(
+    index: &CalendarIndex,
+    split_input: &[&'a str],
+) -> Option<(NamedCalendars, Vec<&'a str>)> {
+    let mut args = vec![];
+    let mut all = false;
+    let mut names: Option<Vec<&str>> = None;
+    let mut iter = split_input.iter();
+    while let Some(arg) = iter.next() {
+        match *arg {
+            "--all" | "-a" => all = true,
+            "--calendars" | "-c" => match iter.next() {
+                Some(list) => names
+                    .get_or_insert_with(Vec::new)
+                    .extend(list.split(',').map(str::trim).filter(|n| !n.is_empty())),
+                None => {
+                    fail!("{arg}: Expected a comma separated list of calendar names");
+                    return None;
+                }
+            },
+            _ => args.push(*arg),
+        }
+    }
+
+    let mut references: Vec<CalendarReference> = vec![];
+    if all {
+        references = index.calendars().clone();
+    } else if let Some(names) = names {
+        for name in names {
+            match index.reference_named(name) {
+                Some(r) => references.push(r.clone()),
+                None => {
+                    fail!("No calendar named {name}, or more than one");
+                    return None;
+                }
+            }
+        }
+    } else {
+        match index.active_calendar_reference() {
+            Ok(r) => references.push(r),
+            Err(e) => {
+                print_err_msg(e, &CONFIG.index_path);
+                return None;
+            }
+        }
+        for name in &CONFIG.visible_calendars {
+            match index.reference_named(name) {
+                Some(r) => references.push(r.clone()),
+                None => warning!("visible_calendars: No calendar named {name}, or more than one"),
+            }
+        }
+    }
+
+    let mut calendars = vec![];
+    let mut paths = vec![];
+    for reference in references {
+        if paths.contains(&reference.path()) {
+            continue;
+        }
+        match Calendar::get(&reference.path()) {
+            Ok(c) => calendars.push((reference.name(), c)),
+            Err(e) => {
+                print_err_msg(e, reference.path());
+                return None;
+            }
+        }
+        paths.push(reference.path());
+    }
+    Some((calendars, args))
+}
+
+/// Expands the events of every calendar in `calendars` like `generate_between`
+/// and returns them sorted, each with the name of its calendar.
+pub fn generate_agenda(
+    calendars: &[(String, Calendar)],
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Vec<(String, Event)> {
+    let mut events: Vec<(String, Event)> = calendars
+        .iter()
+        .flat_map(|(name, calendar)| {
+            generate_between(calendar, start, end)
+                .into_iter()
+                .map(|e| (name.clone(), e))
+        })
+        .collect();
+    events.sort_by(|(_, a), (_, b)| a.cmp(b));
+    events
+}
+
+/// Returns the index of the item in `vec` that `key` refers to by name or ID, see `find_by_key`.
+/// If it refers to several, asks which one with `prompt`.
+pub fn choose_struct_idx<T: CalendarDataType + Display, S: Display>(
     vec: Vec<T>,
     prompt: S,
-    name: S,
-) -> Option<usize>
-where
-    String: PartialEq<S>,
-{
-    let mut index_map = HashMap::<usize, usize>::with_capacity(vec.len());
-
-    let mut i = 0;
-    vec.iter().enumerate().for_each(|(n, e)| {
-        if e.name() == name {
-            index_map.insert(i, n);
-            i += 1;
-        }
-    });
-
-    let structs_named_like_arg = vec
-        .clone()
-        .into_iter()
-        .filter(|s| s.name() == name)
-        .collect::<Vec<T>>();
+    key: &str,
+) -> Result<Option<usize>, PromptRejected> {
+    let found = find_by_key(vec.iter().map(|s| (s.name(), s.id())), key);
 
-    if structs_named_like_arg.is_empty() {
-        return None;
+    if found.is_empty() {
+        return Ok(None);
     }
 
-    if structs_named_like_arg.len() != 1 {
-        structs_named_like_arg
+    if found.len() != 1 {
+        found
             .iter()
             .enumerate()
-            .for_each(|(i, s)| println!("{}. {s}", i + 1));
+            .for_each(|(i, n)| println!("{}. {}", i + 1, vec[*n]));
     }
-    let index_to_select = match structs_named_like_arg.len() {
+    let index_to_select = match found.len() {
         1 => 0,
-        _ => select_in_range(prompt, structs_named_like_arg.len()) - 1,
+        _ => select_in_range(prompt, found.len())? - 1,
     };
-    return Some(index_map[&index_to_select]);
+    Ok(Some(found[index_to_select]))
+}
+
+/// Applies `change` to the deadlines in the active calendar the `keys` refer to by name or ID,
+/// asking with `prompt` which one is meant if a key refers to several, and saves the calendar.
+/// `change` returns whether it changed the deadline.
+pub fn change_deadlines(
+    keys: &[&str],
+    prompt: &str,
+    mut change: impl FnMut(&mut Deadline) -> bool,
+) {
+    let path = active_calendar_reference!().path();
+    let mut active_calendar = active_calendar!();
+    let mut changed = false;
+    for key in keys {
+        let idx = match choose_struct_idx(active_calendar.deadlines().to_vec(), prompt, key) {
+            Ok(Some(i)) => i,
+            Ok(None) => {
+                fail!("No deadline named {key}");
+                continue;
+            }
+            Err(_) => return,
+        };
+        changed |= change(&mut active_calendar.deadlines_mut()[idx]);
+    }
+    if !changed {
+        return;
+    }
+    if let Err(e) = save_calendar(&active_calendar, &path) {
+        print_err_msg(e, &path)
+    }
+}
+
+/// Indices of the items any of the `keys` refers to by name or ID, see `find_by_key`.
+/// Warns about keys that refer to no item, calling the items `what`.
+pub fn find_all_by_keys<'a>(
+    items: impl Iterator<Item = (String, &'a str)> + Clone,
+    keys: &[&str],
+    what: &str,
+) -> Vec<usize> {
+    let mut found = vec![];
+    for key in keys {
+        let matching = find_by_key(items.clone(), key);
+        if matching.is_empty() {
+            fail!("No {what} named {key}");
+        }
+        found.extend(matching);
+    }
+    found
 }
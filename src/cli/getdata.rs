@@ -1,59 +1,96 @@
 use crate::{
-    cli::{repl::get_input, util::get_now_even, validator::*},
+    cal::{
+        details::{parse_tags, Details},
+        recurrence::{days_in_month, Frequency, NthWeekday, Recurrence, RecurrenceRule},
+        timezone::parse_timezone,
+    },
+    cli::{
+        repl::{can_prompt, get_input},
+        script::PromptRejected,
+        util::{date_fmt, date_layout, get_now_even, month_layout, time_fmt},
+        validator::*,
+    },
     warning, CONFIG,
 };
-use chrono::{Date, Duration, Local, NaiveTime, TimeZone};
+use chrono::{Date, DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Weekday};
+use chrono_tz::Tz;
+use colored::Colorize;
+use std::collections::BTreeSet;
+
+/// Prints the date `input` resolved to, such as the date of "next fri", so that it can be checked.
+/// Nothing is printed if `input` already is that date.
+fn echo_date(input: &str, date: &Date<Local>) {
+    let formatted = date_fmt(date.naive_local());
+    if input.trim() != formatted {
+        println!("{}", format!("→ {} {formatted}", date.format("%a")).dimmed());
+    }
+}
+
+/// Prints the time `input` resolved to, like `echo_date`.
+fn echo_time(input: &str, time: &NaiveTime) {
+    let formatted = time_fmt(*time);
+    if input.trim() != formatted {
+        println!("{}", format!("→ {formatted}").dimmed());
+    }
+}
 
 /*
 Return a valid date
 */
-pub fn get_date<T: ToString>(prompt: T) -> Date<Local> {
-    let mut input = get_input(&prompt.to_string(), None);
+pub fn get_date<T: ToString>(prompt: T) -> Result<Date<Local>, PromptRejected> {
+    let mut input = get_input(&prompt.to_string(), None)?;
     while !validate_date(&input) {
         warning!("{input} is not a valid date.");
-        input = get_input(&prompt.to_string(), None);
+        input = get_input(&prompt.to_string(), None)?;
     }
-    parse_into_date(input.as_str())
+    let date = parse_into_date(input.as_str());
+    echo_date(&input, &date);
+    Ok(date)
 }
 
 /*
 Return a valid time
 */
-pub fn get_time<T: ToString>(prompt: T) -> NaiveTime {
-    let mut input = get_input(&prompt.to_string(), None);
+pub fn get_time<T: ToString>(prompt: T) -> Result<NaiveTime, PromptRejected> {
+    let mut input = get_input(&prompt.to_string(), None)?;
     while !validate_time(&input) {
         warning!("{input} is not a valid time input.");
-        input = get_input(&prompt.to_string(), None);
+        input = get_input(&prompt.to_string(), None)?;
     }
-    parse_into_time(input.as_str())
+    let time = parse_into_time(input.as_str());
+    echo_time(&input, &time);
+    Ok(time)
 }
 
 /*
 Return a valid duration
 */
-pub fn get_duration() -> Duration {
+pub fn get_duration() -> Result<Duration, PromptRejected> {
     let prompt: &str = "Duration: ";
-    let mut input = get_input(prompt, None);
-    while !validate_duration(&input) {
-        warning!("{input} is not a valid duration input");
-        input = get_input(prompt, None);
+    loop {
+        let input = get_input(prompt, None)?;
+        match parse_into_duration(&input) {
+            Ok(duration) => return Ok(duration),
+            Err(e) => warning!("{input} is not a valid duration input. {e}."),
+        }
     }
-    parse_into_duration(input.as_str())
 }
 
 /*
 Return a valid date equal or greater than start date
 TODO: Different errors depending on error type (match expression)
 */
-pub fn get_end_date(start_date: &Date<Local>) -> Date<Local> {
+pub fn get_end_date(start_date: &Date<Local>) -> Result<Date<Local>, PromptRejected> {
     let prompt: &str = "End Date: ";
-    let mut input = get_input(prompt, None);
+    let mut input = get_input(prompt, None)?;
     while !validate_date(&input) || &parse_into_date(&input) < start_date {
         warning!("{input} is not a valid date input.");
         // we have to handle errors differently if the second condition is false
-        input = get_input(prompt, None);
+        input = get_input(prompt, None)?;
     }
-    parse_into_date(input.as_str())
+    let date = parse_into_date(input.as_str());
+    echo_date(&input, &date);
+    Ok(date)
 }
 
 /*
@@ -64,145 +101,603 @@ pub fn get_end_time(
     start_date: &Date<Local>,
     start_time: &NaiveTime,
     end_date: &Date<Local>,
-) -> NaiveTime {
+) -> Result<NaiveTime, PromptRejected> {
     let prompt: &str = "End Time: ";
-    let mut input = get_input(prompt, None);
+    let mut input = get_input(prompt, None)?;
     while !validate_time(&input)
         || ((start_date == end_date) && (&parse_into_time(&input) <= start_time))
     {
         warning!("{input} is not a valid time input.");
-        input = get_input(prompt, None);
+        input = get_input(prompt, None)?;
     }
-    parse_into_time(input.as_str())
+    let time = parse_into_time(input.as_str());
+    echo_time(&input, &time);
+    Ok(time)
 }
 
-pub fn get_repeat() -> Duration {
+/*
+Return a valid recurrence of an event starting at `start`.
+Accepts a fixed interval such as '7d' or one of the words accepted by `parse_frequency`,
+which is followed by questions about the interval and the days the event repeats on.
+*/
+pub fn get_repeat(start: &DateTime<Local>) -> Result<Recurrence, PromptRejected> {
     let prompt: &str = "Repeat: ";
-    let mut input = get_input(prompt, None);
-    while !validate_duration(&input) {
-        warning!("{input} is not a valid duration input");
-        input = get_input(prompt, None);
+    let mut input = get_input(prompt, None)?;
+    while !validate_repeat(&input) {
+        warning!("{input} is not a valid duration or repetition rule.");
+        input = get_input(prompt, None)?;
+    }
+    let mut rule = match parse_frequency(&input) {
+        Some(rule) => rule,
+        None => {
+            return Ok(Recurrence::Interval(
+                parse_into_duration(&input).unwrap_or_else(|_| Duration::zero()),
+            ))
+        }
+    };
+    // every weekday needs no further questions
+    if !rule.by_weekday.is_empty() {
+        return Ok(Recurrence::Rule(rule));
+    }
+
+    let unit = match rule.frequency {
+        Frequency::Daily => "days",
+        Frequency::Weekly => "weeks",
+        Frequency::Monthly => "months",
+        Frequency::Yearly => "years",
+    };
+    let prompt = format!("Repeat every how many {unit}: ");
+    let mut input = get_input(&prompt, Some("1"))?;
+    while !(input.trim().is_empty() || input.trim().parse::<u32>().is_ok_and(|i| i > 0)) {
+        warning!("{input} is not a positive number.");
+        input = get_input(&prompt, Some("1"))?;
+    }
+    rule.interval = input.trim().parse().unwrap_or(1);
+
+    match rule.frequency {
+        Frequency::Daily => {}
+        Frequency::Weekly => {
+            let prompt: &str = "On weekdays: ";
+            let initial = start.weekday().to_string();
+            let mut input = get_input(prompt, Some(&initial))?;
+            while parse_weekdays(&input).is_none() {
+                warning!("{input} is not a valid list of weekdays, such as 'mon, wed'.");
+                input = get_input(prompt, Some(&initial))?;
+            }
+            rule.by_weekday = parse_weekdays(&input).unwrap();
+            if rule.by_weekday.is_empty() {
+                rule.by_weekday.push(start.weekday());
+            }
+        }
+        Frequency::Monthly | Frequency::Yearly => {
+            let prompt: &str = "On days of the month: ";
+            let initial = start.day().to_string();
+            let mut input = get_input(prompt, Some(&initial))?;
+            while parse_month_days(&input).is_none() {
+                warning!("{input} is not a valid list of days, such as '15, -1, 1mon, -1fri'.");
+                input = get_input(prompt, Some(&initial))?;
+            }
+            (rule.by_month_day, rule.by_nth_weekday) = parse_month_days(&input).unwrap();
+            if rule.by_month_day.is_empty() && rule.by_nth_weekday.is_empty() {
+                rule.by_month_day.push(start.day() as i8);
+            }
+            if rule.by_month_day.iter().any(|d| *d > 28) {
+                let input = get_input("Move to the last day of shorter months? [Y/n]: ", None)?;
+                rule.clamp_month_end = !matches!(input.trim().to_lowercase().as_str(), "n" | "no");
+            }
+        }
+    }
+    Ok(Recurrence::Rule(rule))
+}
+
+/*
+Return the optional bounds of a recurring event starting on `start_date`:
+the last date an occurrence may start on and the number of occurrences.
+*/
+pub fn get_repeat_end(
+    start_date: &Date<Local>,
+) -> Result<(Option<NaiveDate>, Option<u32>), PromptRejected> {
+    let prompt: &str = "Repeat until (empty for no end date): ";
+    let mut input = get_input(prompt, None)?;
+    while !input.trim().is_empty()
+        && (!validate_date(&input) || &parse_into_date(&input) < start_date)
+    {
+        warning!("{input} is not a valid date on or after the start date.");
+        input = get_input(prompt, None)?;
+    }
+    let until = match input.trim().is_empty() {
+        true => None,
+        false => {
+            let date = parse_into_date(&input);
+            echo_date(&input, &date);
+            Some(date.naive_local())
+        }
+    };
+
+    let prompt: &str = "Number of occurrences (empty for no limit): ";
+    let mut input = get_input(prompt, None)?;
+    while !(input.trim().is_empty() || input.trim().parse::<u32>().is_ok_and(|c| c > 0)) {
+        warning!("{input} is not a positive number.");
+        input = get_input(prompt, None)?;
     }
-    parse_into_duration(input.as_str())
+    Ok((until, input.trim().parse().ok()))
 }
 
 /*
 Return a valid difficulty
 */
-pub fn get_difficulty() -> u8 {
+pub fn get_difficulty() -> Result<u8, PromptRejected> {
     let prompt: &str = "Difficulty: ";
-    let mut input = get_input(prompt, None);
+    let mut input = get_input(prompt, None)?;
     while !validate_difficulty(&input) {
         warning!("{input} is not a valid difficulty input.");
-        input = get_input(prompt, None);
+        input = get_input(prompt, None)?;
     }
-    input.parse::<u8>().unwrap()
+    Ok(input.parse::<u8>().unwrap())
 }
 
 /*
 Return a valid priority
 */
-pub fn get_priority() -> u8 {
+pub fn get_priority() -> Result<u8, PromptRejected> {
     let prompt: &str = "Priority: ";
-    let mut input = get_input(prompt, None);
+    let mut input = get_input(prompt, None)?;
     while !validate_priority(&input) {
         warning!("Entered priority is not valid.");
-        input = get_input(prompt, None);
+        input = get_input(prompt, None)?;
     }
-    input.parse::<u8>().unwrap()
+    Ok(input.parse::<u8>().unwrap())
 }
 
-pub fn get_dir_path() -> String {
+/*
+Return a valid time zone, or `None` for floating local time
+*/
+pub fn get_timezone() -> Result<Option<Tz>, PromptRejected> {
+    let prompt: &str = "Time zone (such as Europe/Warsaw, empty for local time): ";
+    let mut input = get_input(prompt, None)?;
+    while !validate_timezone(&input) {
+        warning!("{input} is not a valid IANA time zone name.");
+        input = get_input(prompt, None)?;
+    }
+    Ok(parse_timezone(&input))
+}
+
+/*
+Return a valid URL, or an empty string for none
+*/
+pub fn get_url(prompt: &str, initial: Option<&str>) -> Result<String, PromptRejected> {
+    let mut input = get_input(prompt, initial)?;
+    while !validate_url(&input) {
+        warning!("{input} is not a valid URL. Include the scheme, such as https://");
+        input = get_input(prompt, None)?;
+    }
+    Ok(input)
+}
+
+/// Answer to a prompt that may be skipped, or an empty string if it cannot be answered,
+/// such as in a script without an answer line for it.
+fn get_optional_input(prompt: &str) -> Result<String, PromptRejected> {
+    match can_prompt() {
+        true => get_input(prompt, None),
+        false => Ok(String::new()),
+    }
+}
+
+/// Optional description, location, URL and tags of a new event or deadline.
+/// Empty answers leave a detail unset.
+pub fn get_details() -> Result<Details, PromptRejected> {
+    let mut details = Details::default();
+    details.set_description(&get_optional_input("Description (optional): ")?);
+    details.set_location(&get_optional_input("Location (optional): ")?);
+    if can_prompt() {
+        details.set_url(&get_url("URL (optional): ", None)?);
+    }
+    details.set_tags(parse_tags(&get_optional_input("Tags (optional, separated by commas): ")?));
+    if can_prompt() {
+        details.set_reminders(get_reminders("Reminders (optional, such as 15m, 1d): ", None)?);
+    }
+    Ok(details)
+}
+
+/*
+Return valid reminder offsets in minutes, none for an empty input
+*/
+pub fn get_reminders(prompt: &str, initial: Option<&str>) -> Result<BTreeSet<u32>, PromptRejected> {
+    let mut input = get_input(prompt, initial)?;
+    loop {
+        match parse_reminders(&input) {
+            Some(reminders) => return Ok(reminders),
+            None => {
+                warning!("{input} is not a valid list of durations, such as 15m, 1d");
+                input = get_input(prompt, None)?;
+            }
+        }
+    }
+}
+
+pub fn get_dir_path() -> Result<String, PromptRejected> {
     let prompt: &str = "Path: ";
-    let mut input = get_input(prompt, get_home_dir().to_str());
+    let mut input = get_input(prompt, get_home_dir().to_str())?;
     while !validate_dir_path(&input) {
         warning!("{input} is not a valid directory path");
-        input = get_input(prompt, get_home_dir().to_str());
+        input = get_input(prompt, get_home_dir().to_str())?;
     }
-    input
+    Ok(input)
 }
 
-pub fn get_valid_calendar_name() -> String {
+pub fn get_valid_calendar_name() -> Result<String, PromptRejected> {
     let prompt: &str = "Calendar Name: ";
-    let mut input = get_input(prompt, None);
+    let mut input = get_input(prompt, None)?;
     while input.is_empty() {
         warning!("Calendar name cannot be an empty string.");
-        input = get_input(prompt, None);
+        input = get_input(prompt, None)?;
     }
-    input
+    Ok(input)
 }
 
-pub fn get_valid_event_name() -> String {
+pub fn get_valid_event_name() -> Result<String, PromptRejected> {
     let prompt: &str = "Event Name: ";
-    let mut input = get_input(prompt, None);
+    let mut input = get_input(prompt, None)?;
     while input.is_empty() {
         warning!("Event name cannot be an empty string");
-        input = get_input(prompt, None);
+        input = get_input(prompt, None)?;
     }
-    input
+    Ok(input)
 }
 
 pub fn parse_into_date(input: &str) -> Date<Local> {
     if input.trim().is_empty() {
         return Local::now().date();
     }
+    if let Some(date) = parse_relative_date(input, Local::now().date_naive()) {
+        return Local.ymd(date.year(), date.month(), date.day());
+    }
 
-    let split_string: Vec<&str> = input.split('/').collect();
+    let date = parse_formatted_date(input, date_layout()).expect("Valid date was given");
+    Local.ymd(date.year(), date.month(), date.day())
+}
 
-    Local.ymd(
-        split_string[2].parse().expect("A number was given as year"),
-        split_string[1]
-            .parse()
-            .expect("A number was given as month"),
-        split_string[0].parse().expect("A number was given as day"),
-    )
+/// Parses `input` written in the `chrono` `layout` of a date format, such as "%d/%m/%Y".
+pub fn parse_formatted_date(input: &str, layout: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(input.trim(), layout).ok()
 }
 
 pub fn parse_into_time(input: &str) -> NaiveTime {
     if input.trim().is_empty() {
         return get_now_even().time();
     }
+    parse_clock_time(input).expect("Valid time was given")
+}
+
+/// Moves `date` by `months`, to the last day of the month if it has fewer days.
+fn add_months(date: NaiveDate, months: i32) -> Option<NaiveDate> {
+    let total = date.year() * 12 + date.month0() as i32 + months;
+    let (year, month) = (total.div_euclid(12), total.rem_euclid(12) as u32 + 1);
+    NaiveDate::from_ymd_opt(year, month, date.day().min(days_in_month(year, month)))
+}
+
+/// Moves `date` by an offset such as "3d", "2weeks" or "-1m", in days, weeks, months or years.
+fn add_offset(date: NaiveDate, offset: &str) -> Option<NaiveDate> {
+    let (sign, offset) = match offset.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, offset.strip_prefix('+').unwrap_or(offset)),
+    };
+    let split = offset.find(|c: char| !c.is_ascii_digit())?;
+    let amount = sign * offset[..split].parse::<i32>().ok()?;
+    match &offset[split..] {
+        "d" | "day" | "days" => date.checked_add_signed(Duration::days(amount as i64)),
+        "w" | "week" | "weeks" => date.checked_add_signed(Duration::weeks(amount as i64)),
+        "m" | "mo" | "month" | "months" => add_months(date, amount),
+        "y" | "year" | "years" => add_months(date, amount * 12),
+        _ => None,
+    }
+}
+
+/// Returns the first `weekday` after `today`.
+fn next_weekday(weekday: Weekday, today: NaiveDate) -> NaiveDate {
+    let days = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    today + Duration::days(if days == 0 { 7 } else { days as i64 })
+}
+
+/// Last day of the week, month or year `today` is in.
+/// Weeks start on `first_weekday` from the config.
+fn end_of(period: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match period {
+        "week" => {
+            let first: Weekday = CONFIG.first_weekday.parse().unwrap_or(Weekday::Mon);
+            Some(next_weekday(first.pred(), today - Duration::days(1)))
+        }
+        "month" => NaiveDate::from_ymd_opt(
+            today.year(),
+            today.month(),
+            days_in_month(today.year(), today.month()),
+        ),
+        "year" => NaiveDate::from_ymd_opt(today.year(), 12, 31),
+        _ => None,
+    }
+}
 
-    let split_string: Vec<&str> = input.split(':').collect();
-    NaiveTime::from_hms(
-        split_string[0].parse().expect("A number was given as hour"),
-        split_string[1]
-            .parse()
-            .expect("A number was given as minute"),
-        0,
-    )
+/*
+Return the date a word or a relative date refers to, counting from `today`.
+Accepted are 'today', 'tomorrow', 'yesterday', weekdays such as 'fri' or 'next friday'
+meaning the first one after today, offsets such as '+3d', '-1w' or 'in 2 weeks'
+in days, weeks, months or years, and 'end of week', 'end of month' and 'end of year'.
+*/
+pub fn parse_relative_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase();
+    let words: Vec<&str> = input.split_whitespace().collect();
+    match words[..] {
+        ["today"] => Some(today),
+        ["tomorrow"] => Some(today + Duration::days(1)),
+        ["yesterday"] => Some(today - Duration::days(1)),
+        ["end", "of", period] => end_of(period, today),
+        ["in", ..] => add_offset(today, &words[1..].concat()),
+        ["next", day] => Some(next_weekday(day.parse().ok()?, today)),
+        [first, ..] if first.starts_with(['+', '-']) => add_offset(today, &words.concat()),
+        [day] => Some(next_weekday(day.parse().ok()?, today)),
+        _ => None,
+    }
 }
 
 /*
-As of now, this only accepts input such as '3d', '40min' or '3h'
-Eventually, support for a format like '1:20h' should be added.
+Return the time of a 24 or 12-hour clock time or of a word.
+Accepted are times such as '14:30', '9:05', '14', '2pm' and '2:30 pm', and 'noon' and 'midnight'.
 */
-pub fn parse_into_duration(input: &str) -> Duration {
-    if input.trim().is_empty() {
-        return Duration::zero();
+pub fn parse_clock_time(input: &str) -> Option<NaiveTime> {
+    let input = input.trim().to_lowercase().replace(' ', "");
+    match input.as_str() {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
     }
+    let (clock, pm) = match (input.strip_suffix("am"), input.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, Some(false)),
+        (_, Some(clock)) => (clock, Some(true)),
+        _ => (input.as_str(), None),
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute),
+        Some(_) => return None,
+        None => (clock, "00"),
+    };
+    let number =
+        |s: &str| match !s.is_empty() && s.len() <= 2 && s.chars().all(|c| c.is_ascii_digit()) {
+            true => s.parse::<u32>().ok(),
+            false => None,
+        };
+    let (hour, minute) = (number(hour)?, number(minute)?);
+    let hour = match pm {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
 
-    let input_lower = &input.to_lowercase();
+/// Units a duration can be typed in, each with its names and length in seconds.
+const DURATION_UNITS: [(&[&str], i64); 5] = [
+    (&["w", "wk", "wks", "week", "weeks"], 7 * 24 * 3600),
+    (&["d", "day", "days"], 24 * 3600),
+    (&["h", "hr", "hrs", "hour", "hours"], 3600),
+    (&["m", "min", "mins", "minute", "minutes"], 60),
+    (&["s", "sec", "secs", "second", "seconds"], 1),
+];
 
-    match (input_lower.contains('d'), input_lower.contains('h'), input_lower.contains('m')) {
-        (true, false, false) => Duration::days(
-            input_lower.split('d').collect::<Vec<&str>>()[0]
-                .trim()
-                .parse()
-                .expect("Valid duration was given"),
-        ),
-        (false, true, false) => Duration::hours(
-            input_lower.split('h').collect::<Vec<&str>>()[0]
-                .trim()
-                .parse()
-                .expect("Valid duration was given"),
-        ),
-        (false, false, true) => Duration::minutes(
-            input_lower.split('m').collect::<Vec<&str>>()[0]
-                .trim()
-                .parse()
-                .expect("Valid duration was given"),
-        ),
-        (_, _, _) => panic!("Error parsing duration. This error should be unreachable"),
+/// Longest duration accepted, 100 years in seconds.
+const MAX_DURATION_SECONDS: f64 = 36525.0 * 24.0 * 3600.0;
+
+/// Splits `input` such as "1h 30m" or "1.5h" into numbers and the units following them.
+fn duration_components(input: &str) -> Result<Vec<(f64, String)>, String> {
+    let mut components = vec![];
+    let mut chars = input.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return Ok(components);
+        }
+        let mut number = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+            number.push(c);
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut unit = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_alphabetic()) {
+            unit.push(c);
+        }
+        let number: f64 = match number.parse() {
+            Ok(n) => n,
+            Err(_) if number.is_empty() => {
+                return Err(match unit.is_empty() {
+                    true => format!("Unexpected character in {input}"),
+                    false => format!("A number is missing before {unit}"),
+                })
+            }
+            Err(_) => return Err(format!("{number} is not a number")),
+        };
+        if unit.is_empty() {
+            return Err(format!(
+                "A unit is missing after {number}, such as {number}m or {number}h"
+            ));
+        }
+        components.push((number, unit));
+    }
+}
+
+/// Adds up `components` of a duration, whose units have to be among `units`, each used at most once.
+fn sum_components(components: Vec<(f64, String)>, units: &[&str]) -> Result<Duration, String> {
+    if components.is_empty() {
+        return Err("No duration was given".to_string());
+    }
+    let mut used = vec![];
+    let mut seconds = 0.0;
+    for (number, unit) in components {
+        let length = match DURATION_UNITS
+            .iter()
+            .find(|(names, _)| names.contains(&unit.as_str()))
+            .filter(|(names, _)| units.contains(&names[0]))
+        {
+            Some((names, length)) => {
+                if used.contains(&names[0]) {
+                    return Err(format!("{unit} is given more than once"));
+                }
+                used.push(names[0]);
+                length
+            }
+            None => return Err(format!("{unit} is not a unit of time")),
+        };
+        seconds += number * *length as f64;
+    }
+    if seconds > MAX_DURATION_SECONDS {
+        return Err("A duration cannot be longer than 100 years".to_string());
+    }
+    Ok(Duration::seconds(seconds.round() as i64))
+}
+
+/// Parses an ISO 8601 duration without its leading 'P', such as "T1H30M" or "2W".
+fn parse_iso_duration(input: &str) -> Result<Duration, String> {
+    let (date, time) = match input.split_once('t') {
+        Some((_, "")) => return Err("The time part is missing after T".to_string()),
+        Some((date, time)) => (date, time),
+        None => (input, ""),
+    };
+    let mut components = duration_components(date)?;
+    // M stands for months before T, which have no fixed length
+    if components
+        .iter()
+        .any(|(_, unit)| !["w", "d"].contains(&unit.as_str()))
+    {
+        return Err("Only weeks and days can come before T".to_string());
+    }
+    components.extend(duration_components(time)?);
+    sum_components(components, &["w", "d", "h", "m", "s"])
+}
+
+/*
+Parse a duration such as '3d', '40min', '1h 30m', '1.5h', '2w', '1:20' or 'PT1H30M'.
+Returns a message explaining what is wrong with invalid input.
+*/
+pub fn parse_into_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return Ok(Duration::zero());
+    }
+    // hours and minutes such as 1:20 or 1:20h
+    if let Some((hours, minutes)) = input.trim_end_matches('h').split_once(':') {
+        let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+        return match (hours.parse::<i64>(), minutes.parse::<i64>()) {
+            (Ok(h), Ok(m)) if digits(hours) && minutes.len() == 2 && digits(minutes) && m < 60 => {
+                sum_components(
+                    vec![(h as f64, "h".to_string()), (m as f64, "m".to_string())],
+                    &["h", "m"],
+                )
+            }
+            _ => Err(format!("{input} is not a number of hours and minutes such as 1:20")),
+        };
+    }
+    match input.strip_prefix('p') {
+        Some(iso) => parse_iso_duration(iso),
+        None => {
+            sum_components(duration_components(&input)?, &DURATION_UNITS.map(|(names, _)| names[0]))
+        }
+    }
+}
+
+/// Minutes of every duration in a comma separated list such as "15m, 1d".
+/// `None` if any of them is not a valid duration.
+pub fn parse_reminders(input: &str) -> Option<BTreeSet<u32>> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| match parse_into_duration(d) {
+            Ok(d) => u32::try_from(d.num_minutes()).ok(),
+            Err(_) => None,
+        })
+        .collect()
+}
+
+/*
+Parse a month in the date format from the config without the day, such as '10/2026',
+into its year and number.
+*/
+pub fn parse_month(input: &str) -> Option<(i32, u32)> {
+    parse_formatted_month(input, &month_layout())
+}
+
+/// Parses a month written in the `chrono` `layout` of a date format without the day, such as "%m/%Y".
+pub fn parse_formatted_month(input: &str, layout: &str) -> Option<(i32, u32)> {
+    // a date cannot be parsed without a day, so the first of the month is added
+    let date = parse_formatted_date(&format!("{} 1", input.trim()), &format!("{layout} %d"))?;
+    match (1970..=9999).contains(&date.year()) {
+        true => Some((date.year(), date.month())),
+        false => None,
+    }
+}
+
+/*
+Parse the kind of a repetition rule.
+'daily', 'weekly', 'monthly' and 'yearly' repeat every day, week, month or year,
+'weekdays' repeats from Monday to Friday.
+*/
+pub fn parse_frequency(input: &str) -> Option<RecurrenceRule> {
+    let frequency = match input.trim().to_lowercase().as_str() {
+        "daily" => Frequency::Daily,
+        "weekly" => Frequency::Weekly,
+        "monthly" => Frequency::Monthly,
+        "yearly" | "annually" => Frequency::Yearly,
+        "weekdays" => {
+            let mut rule = RecurrenceRule::new(Frequency::Daily, 1);
+            rule.by_weekday = vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ];
+            return Some(rule);
+        }
+        _ => return None,
+    };
+    Some(RecurrenceRule::new(frequency, 1))
+}
+
+/*
+Parse a list of weekdays such as 'mon, wed' or 'Tuesday Friday'.
+*/
+pub fn parse_weekdays(input: &str) -> Option<Vec<Weekday>> {
+    let mut weekdays = vec![];
+    for day in input.split([',', ' ']).filter(|d| !d.is_empty()) {
+        let weekday = day.parse::<Weekday>().ok()?;
+        if !weekdays.contains(&weekday) {
+            weekdays.push(weekday);
+        }
+    }
+    Some(weekdays)
+}
+
+/*
+Parse a list of days of the month.
+'15' is the 15th, '-1' the last day of the month, '1mon' the first Monday
+and '-1fri' the last Friday.
+*/
+pub fn parse_month_days(input: &str) -> Option<(Vec<i8>, Vec<NthWeekday>)> {
+    let mut days = vec![];
+    let mut nth_weekdays = vec![];
+    for day in input.split([',', ' ']).filter(|d| !d.is_empty()) {
+        let split = day.find(|c: char| c.is_alphabetic()).unwrap_or(day.len());
+        let n = day[..split].parse::<i8>().ok()?;
+        if split == day.len() {
+            if n == 0 || !(-31..=31).contains(&n) {
+                return None;
+            }
+            days.push(n);
+        } else {
+            if n == 0 || !(-5..=5).contains(&n) {
+                return None;
+            }
+            let weekday = day[split..].parse::<Weekday>().ok()?;
+            nth_weekdays.push(NthWeekday { n, weekday });
+        }
     }
+    Some((days, nth_weekdays))
 }
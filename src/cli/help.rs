@@ -1,4 +1,8 @@
-use crate::{warning, CONFIG, EDITOR_CONFIG};
+use crate::{
+    cli::util::{month_format, month_layout},
+    fail, CONFIG, EDITOR_CONFIG,
+};
+use chrono::NaiveDate;
 use colored::*;
 
 pub fn print_startup_message() {
@@ -33,7 +37,46 @@ There is NO WARRANTY, to the extent permitted by law.",
     println!("{str}");
 }
 
-pub fn print_help(split_input: &Vec<&str>) {
+pub fn print_usage() {
+    let str = format!(
+        "{}
+> calmar
+> calmar {} [{}]...
+> calmar --script [{}] [--continue-on-error]
+> calmar --help
+> calmar --version
+
+Without a command, calmar starts an interactive session.
+Otherwise the command is run once and calmar exits without printing the startup message.
+The exit status is 0 on success, 1 if the command failed and 2 on invalid options.
+
+{}
+Run every line of {} (or stdin if it is omitted or \"-\") as a command.
+Lines starting with '#' are comments. Lines starting with '>' answer the prompts
+of the command above them, in order. A prompt without an answer line fails the command.
+The script stops at the first failing line, unless {} ({}) is given.
+
+{}
+> calmar list 3d
+> calmar set work
+> calmar add \"Linear Algebra\"
+> calmar --script semester.calmar
+
+Type \"calmar help\" for the list of commands.",
+        "Usage".bold(),
+        "command".italic(),
+        "argument".italic(),
+        "file".italic(),
+        "Scripts".bold(),
+        "file".italic(),
+        "--continue-on-error".italic(),
+        "-c".italic(),
+        "Examples".bold()
+    );
+    println!("{str}");
+}
+
+pub fn print_help(split_input: &[&str]) {
     let add_doc = format!(
         "
 {}
@@ -41,6 +84,27 @@ pub fn print_help(split_input: &Vec<&str>) {
 Your calendars consist of events. `add` lets you add a new event to the calendar
 set as active in your index.json file.
 
+Dates are entered in {} from the config file, and can also be entered as {}, {},
+weekdays such as {}, offsets such as {} or {}, and {}. Times can be entered as {}, {} or {}.
+The date or time such an input stands for is printed.
+Durations can combine units and fractions, such as {}, {}, {}, {} or {}.
+
+An event may repeat at a fixed interval, such as {}, or on calendar dates:
+{}, {}, {}, {} or {}.
+Weekly events can repeat on several weekdays, such as {}.
+Monthly and yearly events can repeat on days of the month such as {}, where {} is the last day
+of the month, or on weekdays of the month such as {} (first Monday) and {} (last Friday).
+A recurring event can end after a date or a number of occurences, exceptions included.
+
+New events get the time zone of the calendar, which can be set with {}.
+They repeat at the same time of day on the wall clock of that zone, across daylight saving changes.
+Events in a calendar without a time zone follow local time, wherever you are.
+Times are always entered and listed in local time.
+
+Finally, an event can be given a description, a location, a URL, tags separated by commas
+and reminders, such as {}, given by {} that long before it starts.
+These are optional and are skipped by leaving the answer empty.
+
 {}
 > add
 > add {}
@@ -50,6 +114,35 @@ set as active in your index.json file.
 Defined in {}
 ",
         "add, a".bold(),
+        "date_format".italic(),
+        "today".italic(),
+        "tomorrow".italic(),
+        "next fri".italic(),
+        "+3d".italic(),
+        "in 2 weeks".italic(),
+        "end of month".italic(),
+        "2pm".italic(),
+        "14".italic(),
+        "noon".italic(),
+        "1h 30m".italic(),
+        "1:20".italic(),
+        "2w".italic(),
+        "1.5h".italic(),
+        "PT1H30M".italic(),
+        "7d".italic(),
+        "daily".italic(),
+        "weekly".italic(),
+        "monthly".italic(),
+        "yearly".italic(),
+        "weekdays".italic(),
+        "mon, wed".italic(),
+        "15, -1".italic(),
+        "-1".italic(),
+        "1mon".italic(),
+        "-1fri".italic(),
+        "edit-calendar".italic(),
+        "15m, 1d".italic(),
+        "watch".italic(),
         "Syntax".bold(),
         "event_name".italic(),
         "event_name".italic(),
@@ -74,6 +167,36 @@ The backups are created in the same directory where the calendar file is.
         "calendar_name".italic()
     );
 
+    let briefing_doc = format!(
+        "
+{}
+
+Print the number of events left today and tomorrow, and of deadlines in the next 7 and 14 days.
+Recurring events ending within a week are named.
+Done and dropped deadlines are not counted. With {}, the ones finished in the last 7 days are named.
+Calendars are chosen like in {}.
+
+{}
+> briefing [{}] [{} | {} {}]
+> br [{}] [{} | {} {}]
+
+Defined in {}
+",
+        "briefing, br".bold(),
+        "--history".italic(),
+        "list".italic(),
+        "Syntax".bold(),
+        "--history".italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+        "-H".italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
     let cal_doc = format!(
         "
 {}
@@ -112,19 +235,76 @@ Defined in {}
         "src/cli/commands.rs".italic()
     );
 
+    let conflicts_doc = format!(
+        "
+{}
+
+Find events in the active calendar that overlap with each other and list every overlapping pair.
+Occurences removed with {} are not taken into account.
+By default, the span from the config is searched. Otherwise, the duration passed in as an argument.
+Finding any overlap counts as a failure, so a script can check a calendar with this command.
+
+{}, {} and removing exceptions with {} warn when events would overlap within the default span.
+Setting overlap_policy in the config to \"block\" refuses such changes instead.
+
+{}
+> conflicts
+> conflicts {}
+> co
+> co {}
+
+Defined in {}
+",
+        "conflicts, co".bold(),
+        "except".italic(),
+        "add".italic(),
+        "edit".italic(),
+        "except".italic(),
+        "Syntax".bold(),
+        "duration".italic(),
+        "duration".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
     let date_doc = format!(
         "
 {}
 
-Print current date.
+Print current date in {} from the config file.
 
 {}
 > date
 ",
         "date, D".bold(),
+        "date_format".italic(),
         "Syntax".bold()
     );
 
+    let done_doc = format!(
+        "
+{}
+
+Mark deadlines in the active calendar as done, or as dropped if {} is given.
+Finished deadlines are kept with the time they were finished, but hidden from {} and {}.
+
+{}
+> done [{}] [{}]...
+> dn [{}] [{}]...
+
+Defined in {}
+",
+        "done, dn".bold(),
+        "--drop".italic(),
+        "list-deadlines".italic(),
+        "briefing".italic(),
+        "Syntax".bold(),
+        "--drop".italic(),
+        "deadline_name".italic(),
+        "-d".italic(),
+        "deadline_name".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
     let duration_doc = format!(
         "
 {}
@@ -152,7 +332,7 @@ Defined in {}
 {}
 
 Edit a property of all events passed in as arguments. If no arguments are given,
-the user is prompted for input.
+the user is prompted for input. Details such as the location can be cleared by erasing them.
 
 {}
 > edit
@@ -169,6 +349,156 @@ Defined in {}
         "src/cli/commands.rs".italic()
     );
 
+    let edit_deadline_doc = format!(
+        "
+{}
+
+Edit the name, date, time, priority or details of deadlines in the active calendar.
+To move a deadline because it slipped, use {} instead, which keeps track of the original date.
+
+{}
+> edit-deadline [{}]...
+> ed [{}]...
+
+Defined in {}
+",
+        "edit-deadline, ed".bold(),
+        "postpone".italic(),
+        "Syntax".bold(),
+        "deadline_name".italic(),
+        "deadline_name".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
+    let export_ics_doc = format!(
+        "
+{}
+
+Export a calendar to an iCalendar (RFC 5545) file, which other calendar programs can import.
+Events become VEVENTs, repeating events get an RRULE and their exceptions EXDATEs.
+Events repeating on days an RRULE cannot describe get the dates of the next year as RDATEs,
+and keep their rule for importing the file back into calmar.
+Deadlines become VTODOs.
+By default, the active calendar is written to {} in the current directory.
+
+{}
+> export-ics
+> export-ics {}
+> export-ics {} {}
+> ei
+> ei {}
+> ei {} {}
+
+Defined in {}
+",
+        "export-ics, ei".bold(),
+        "calendar_name.ics".italic(),
+        "Syntax".bold(),
+        "filename".italic(),
+        "filename".italic(),
+        "calendar_name".italic(),
+        "filename".italic(),
+        "filename".italic(),
+        "calendar_name".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
+    // months are typed in the date format without the day
+    let month_example = NaiveDate::from_ymd(2026, 11, 1)
+        .format(&month_layout())
+        .to_string();
+    let find_doc = format!(
+        "
+{}
+
+Search events and deadlines in every calendar. Only items meeting all of the filters are listed.
+
+{}
+{} -- events and deadlines whose name contains the text, ignoring case
+{} -- names matching a regular expression, as in {}
+{} -- priority equal to the number, or compared with {}, {}, {} or {} instead of {}, as in {}
+{} -- difficulty, compared the same way. Deadlines have no difficulty
+{} -- items with the tag
+{} -- items in the calendars named so, separated by commas
+{}, {} -- only events or only deadlines
+{}, {} -- items that repeat or not
+{} -- items between the start of one period and the end of another, or within one
+A period is a date, a month such as {} or one of {}, {}, {}, {}, {} and {}.
+With a period, events are listed once for every occurrence in it.
+
+{}
+> find [{}]...
+> f [{}]...
+> find exam tag:math {} {}
+
+Defined in {}
+",
+        "find, f".bold(),
+        "Filters".bold(),
+        "text".italic(),
+        "name:/regex/".italic(),
+        "\"name:/^lab [0-9]+$/\"".italic(),
+        "priority=number, p=number".italic(),
+        "<".italic(),
+        "<=".italic(),
+        ">=".italic(),
+        ">".italic(),
+        "=".italic(),
+        "p>=7".italic(),
+        "difficulty=number, d=number".italic(),
+        "tag:name".italic(),
+        "cal:names".italic(),
+        "is:event".italic(),
+        "is:deadline".italic(),
+        "is:recurring".italic(),
+        "is:once".italic(),
+        "from:period to:period, date:period".italic(),
+        month_example.italic(),
+        "today".italic(),
+        "tomorrow".italic(),
+        "week".italic(),
+        "next-week".italic(),
+        "month".italic(),
+        "next-month".italic(),
+        "Syntax".bold(),
+        "filter".italic(),
+        "filter".italic(),
+        "p>=7".italic(),
+        "date:next-month".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
+    let free_doc = format!(
+        "
+{}
+
+Find periods of at least the given duration without any events in the active calendar.
+By default, the span from the config is searched. Otherwise, the span passed in as the second argument.
+Only working hours are searched, set with work_day_start, work_day_end and work_days in the config.
+To keep some time free around events, set buffer_before and buffer_after, such as \"15m\".
+
+The periods are numbered. Selecting one starts adding an event at its beginning, lasting the given duration.
+Leave the prompt empty to only list them.
+
+{}
+> free {}
+> free {} {}
+> fr {}
+> fr {} {}
+
+Defined in {}
+",
+        "free, fr".bold(),
+        "Syntax".bold(),
+        "duration".italic(),
+        "duration".italic(),
+        "span".italic(),
+        "duration".italic(),
+        "duration".italic(),
+        "span".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
     let help_doc = format!(
         "
 {}
@@ -191,26 +521,91 @@ Defined in {}
         "src/cli/help.rs".italic()
     );
 
+    let import_ics_doc = format!(
+        "
+{}
+
+Import events (VEVENT) and deadlines (VTODO) from an iCalendar (RFC 5545) file
+into the active calendar or the calendar passed in as the second argument.
+Recurrence rules calmar can represent are kept, EXDATEs become exceptions.
+Components that cannot be represented are skipped and reported.
+Items imported before are recognized by their UID and updated instead of duplicated.
+Their reminders and the status, progress and postponements of deadlines are kept.
+
+{}
+> import-ics {}
+> import-ics {} {}
+> ii {}
+> ii {} {}
+
+Defined in {}
+",
+        "import-ics, ii".bold(),
+        "Syntax".bold(),
+        "filename".italic(),
+        "filename".italic(),
+        "calendar_name".italic(),
+        "filename".italic(),
+        "filename".italic(),
+        "calendar_name".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
     let list_doc = format!(
         "
 {}
 
-List all events in the active calendar that match one of the arguments passed in.
-If no arguments have been passed, list all events in the active calendar.
+List events in the active calendar, starting now.
+By default, the span from the config is listed. Otherwise, the duration passed in as an argument.
+
+Events of other calendars are listed along with the active calendar if they are named in visible_calendars in the config.
+{} lists events of all calendars instead, {} followed by comma separated names lists events of the calendars named so.
+Events of more than one calendar are listed with the name of their calendar.
 
 {}
-> list
-> list [{}]...
-> ls
-> ls [{}]...
-> l
-> l [{}]...
+> list [{}] [{} | {} {}]
+> ls [{}] [{} | {} {}]
+> l [{}] [{} | {} {}]
 ",
         "list, ls, l".bold(),
+        "--all, -a".italic(),
+        "--calendars, -c".italic(),
         "Syntax".bold(),
-        "event_name".italic(),
-        "event_name".italic(),
-        "event_name".italic()
+        "duration".italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+        "duration".italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+        "duration".italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+    );
+
+    let list_deadlines_doc = format!(
+        "
+{}
+
+List deadlines in the active calendar, or the ones passed in, sorted by date.
+Done and dropped deadlines are only listed with {}.
+
+{}
+> list-deadlines [{}] [{}]...
+> ld [{}] [{}]...
+
+Defined in {}
+",
+        "list-deadlines, ld".bold(),
+        "--history".italic(),
+        "Syntax".bold(),
+        "--history".italic(),
+        "deadline_name".italic(),
+        "-H".italic(),
+        "deadline_name".italic(),
+        "src/cli/commands.rs".italic()
     );
 
     let listcal_doc = format!(
@@ -229,6 +624,38 @@ Currently, the index file is {}.
         "Syntax".bold()
     );
 
+    let month_doc = format!(
+        "
+{}
+
+Draw a calendar grid of a month. By default, the current month.
+Days with events are colored after the highest priority among their events, days with deadlines are marked with '!'.
+Today is highlighted.
+Calendars are chosen like in {}.
+
+Weeks start on first_weekday from the config, such as \"sun\".
+Setting month_cell in the config to \"count\" shows the number of events on each day, \"name\" the name of the first one.
+
+{}
+> month [{}] [{} | {} {}]
+> mo [{}] [{} | {} {}]
+
+Defined in {}
+",
+        "month, mo".bold(),
+        "list".italic(),
+        "Syntax".bold(),
+        month_format().italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+        month_format().italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
     let raw_doc = format!(
         "
 {}
@@ -255,6 +682,7 @@ If no arguments are passed, print the entire raw calendar.
 
 Remove events from the active calendar.
 If no event names are passed in, the user is asked for input.
+All events with a given name are removed. To remove only one of them, pass its ID.
 
 {}
 > remove
@@ -323,6 +751,26 @@ Defined in {}
         "src/cli/commands.rs".italic()
     );
 
+    let show_doc = format!(
+        "
+{}
+
+Print everything about events and deadlines in the active calendar, including their description,
+location, URL and tags. For events, the next 5 occurrences that have not ended yet are listed as well.
+
+{}
+> show [{}]...
+> sh [{}]...
+
+Defined in {}
+",
+        "show, sh".bold(),
+        "Syntax".bold(),
+        "name".italic(),
+        "name".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
     let sort_doc = format!(
         "
 {}
@@ -361,12 +809,13 @@ Defined in {}
         "
 {}
 
-Print current time.
+Print current time in {} from the config file.
 
 {}
 > time
 ",
         "time, T".bold(),
+        "time_format".italic(),
         "Syntax".bold()
     );
 
@@ -376,15 +825,23 @@ Print current time.
 
 Print time until the start of an event.
 Multiple event names can be provided.
+Calendars are chosen like in {}, so events of other calendars can be named as well.
 
 {}
-> until [{}]...
-> u [{}]...
+> until [{}]... [{} | {} {}]
+> u [{}]... [{} | {} {}]
 ",
         "until, u".bold(),
+        "list".italic(),
         "Syntax".bold(),
         "event_name".italic(),
-        "event_name".italic()
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+        "event_name".italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
     );
 
     let update_doc = format!(
@@ -393,6 +850,7 @@ Multiple event names can be provided.
 
 Update the active calendar.
 An event is removed if it is not recurring and already happended.
+A recurring event is removed if its last occurence (see {}) already happened.
 Start and end timedates of recurring events are updated to their occurence closest future in time to current timedate.
 If a recurring event is currently happening, its start and end timedates are set to timedates of the current occurence.
 
@@ -401,6 +859,7 @@ If a recurring event is currently happening, its start and end timedates are set
 > U
 ",
 	"update, U".bold(),
+	"add".italic(),
 	"Syntax".bold(),
     );
 
@@ -420,6 +879,109 @@ Remove all calendar references where their path no longer points to an existing
         "Syntax".bold()
     );
 
+    let undo_doc = format!(
+        "
+{}
+
+Revert the most recent command that changed calendars or the calendar index, such as {}, {} or {}.
+As many commands as undo_limit in the config can be undone, even after calmar is restarted.
+Older commands are dropped sooner if their changes take up more than 1 MiB.
+A command cannot be undone if the files it changed were modified since by other means.
+
+{}
+> undo
+> z
+",
+        "undo, z".bold(),
+        "add".italic(),
+        "remove".italic(),
+        "removecal".italic(),
+        "Syntax".bold()
+    );
+
+    let redo_doc = format!(
+        "
+{}
+
+Reapply the most recent command reverted by {}.
+Commands can no longer be redone once another change is made.
+
+{}
+> redo
+> Z
+",
+        "redo, Z".bold(),
+        "undo".italic(),
+        "Syntax".bold()
+    );
+
+    let postpone_doc = format!(
+        "
+{}
+
+Move a deadline in the active calendar later by a duration, such as {}.
+The date it had before it was first postponed and the number of times it was postponed are kept
+and printed by {}.
+
+{}
+> postpone {} {}
+> pp {} {}
+
+Defined in {}
+",
+        "postpone, pp".bold(),
+        "3d".italic(),
+        "show".italic(),
+        "Syntax".bold(),
+        "deadline_name".italic(),
+        "duration".italic(),
+        "deadline_name".italic(),
+        "duration".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
+    let progress_doc = format!(
+        "
+{}
+
+Set how much of the work towards a deadline in the active calendar is done, in percent.
+A deadline with some progress is in progress until it is marked with {}.
+
+{}
+> progress {} {}
+> pg {} {}
+
+Defined in {}
+",
+        "progress, pg".bold(),
+        "done".italic(),
+        "Syntax".bold(),
+        "deadline_name".italic(),
+        "percent".italic(),
+        "deadline_name".italic(),
+        "percent".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
+    let reopen_doc = format!(
+        "
+{}
+
+Mark done or dropped deadlines in the active calendar as unfinished again.
+
+{}
+> reopen [{}]...
+> ro [{}]...
+
+Defined in {}
+",
+        "reopen, ro".bold(),
+        "Syntax".bold(),
+        "deadline_name".italic(),
+        "deadline_name".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
     let quit_doc = format!(
         "
 {}
@@ -447,6 +1009,73 @@ Print version information.
         "Syntax".bold()
     );
 
+    let watch_doc = format!(
+        "
+{}
+
+Keep running and give the reminders of events and deadlines as they become due.
+Reminders are set when adding an event or a deadline, or with {} and {}, such as {} for
+15 minutes and a day before an event starts or a deadline is due.
+A reminder is printed, or passed as the last argument to notify_command from the config, such as {}.
+Reminders due while calmar was not watching are given when it starts, if their event is still ahead.
+Each reminder is given once, also across restarts, and calendars are read again when their files change.
+Calendars are chosen like in {}. With {}, due reminders are given once and calmar stops, as from cron.
+Press Ctrl+C to stop watching.
+
+{}
+> watch [{}] [{} | {} {}]
+> wa [{}] [{} | {} {}]
+
+Defined in {}
+",
+        "watch, wa".bold(),
+        "edit".italic(),
+        "edit-deadline".italic(),
+        "15m, 1d".italic(),
+        "notify-send calmar".italic(),
+        "list".italic(),
+        "--once".italic(),
+        "Syntax".bold(),
+        "--once".italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+        "-1".italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
+    let week_doc = format!(
+        "
+{}
+
+Draw a timetable of the week containing a date. By default, the current week.
+Every day is a column and every hour a row, from week_start_hour to week_end_hour in the config.
+Events are colored after their priority and overlapping events are placed side by side.
+Weeks start on first_weekday from the config. Calendars are chosen like in {}.
+
+{}
+> week [{}] [{} | {} {}]
+> wk [{}] [{} | {} {}]
+
+Defined in {}
+",
+        "week, wk".bold(),
+        "list".italic(),
+        "Syntax".bold(),
+        "date".italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+        "date".italic(),
+        "-a".italic(),
+        "-c".italic(),
+        "calendar_names".italic(),
+        "src/cli/commands.rs".italic()
+    );
+
     let write_doc = format!(
         "
 {}
@@ -476,28 +1105,48 @@ Generate a calendar for a given duration and write it to a new file.
 The list of available commands:
 
 {}, {} -- add an event
+{}, {} -- summarize the coming days
 {}, {} -- add a calendar
 {} -- clear the screen
+{}, {} -- list overlapping events
 {}, {} -- print current date
+{}, {} -- mark deadlines as done
 {}, {} -- print the duration of an event
 {}, {} -- edit an event
+{}, {} -- edit a deadline
+{}, {} -- export a calendar to an iCalendar file
+{}, {} -- search events and deadlines in all calendars
+{}, {} -- find free time
 {}, {} -- print this information or command documentation
+{}, {} -- import an iCalendar file
 {}, {} -- list events
 {}, {} -- list calendars
+{}, {} -- list deadlines
+{}, {} -- draw a month
+{}, {} -- move a deadline later
+{}, {} -- set the progress of a deadline
 {}, {} -- print a raw calendar
+{}, {} -- redo an undone change
 {}, {} -- remove events
 {}, {} -- remove calendars
+{}, {} -- mark finished deadlines as unfinished
 {}, {} -- set the active calendar
+{}, {} -- print all details of events and deadlines
 {}, {} -- sort events
 {}, {} -- print current time
+{}, {} -- undo the last change
 {}, {} -- print time remaining until an event
 {}, {} -- update the active calendar
 {}, {} -- update the calendar index
 {}, {} -- exit the program
 {}, {} -- print version information
+{}, {} -- give reminders as they become due
+{}, {} -- draw a week
 {}, {} -- write calendar to a file
 
 Type \"help\" followed by command name for full documentation.
+Events, deadlines and calendars can be named by their ID instead, or its first 4 or more characters.
+IDs are shown by raw, list, list-deadlines and listcal.
 
 Your current keymap is \"{:#?}\".
 
@@ -507,33 +1156,65 @@ Keyboard shortcut lists:
         ",
                 "add".bold(),
                 "a".dimmed(),
+                "briefing".bold(),
+                "br".dimmed(),
                 "cal".bold(),
                 "c".dimmed(),
                 "clear".bold(),
+                "conflicts".bold(),
+                "co".dimmed(),
                 "date".bold(),
                 "D".dimmed(),
+                "done".bold(),
+                "dn".dimmed(),
                 "duration".bold(),
                 "d".dimmed(),
                 "edit".bold(),
                 "e".dimmed(),
+                "edit-deadline".bold(),
+                "ed".dimmed(),
+                "export-ics".bold(),
+                "ei".dimmed(),
+                "find".bold(),
+                "f".dimmed(),
+                "free".bold(),
+                "fr".dimmed(),
                 "help".bold(),
                 "h".dimmed(),
+                "import-ics".bold(),
+                "ii".dimmed(),
                 "list".bold(),
                 "ls, l".dimmed(),
                 "listcal".bold(),
                 "lc".dimmed(),
+                "list-deadlines".bold(),
+                "ld".dimmed(),
+                "month".bold(),
+                "mo".dimmed(),
+                "postpone".bold(),
+                "pp".dimmed(),
+                "progress".bold(),
+                "pg".dimmed(),
                 "raw".bold(),
                 "R".dimmed(),
+                "redo".bold(),
+                "Z".dimmed(),
                 "remove".bold(),
                 "rm, r".dimmed(),
                 "removecal".bold(),
                 "rmcal, rc".dimmed(),
+                "reopen".bold(),
+                "ro".dimmed(),
                 "set".bold(),
                 "s".dimmed(),
+                "show".bold(),
+                "sh".dimmed(),
                 "sort".bold(),
                 "S".dimmed(),
                 "time".bold(),
                 "T".dimmed(),
+                "undo".bold(),
+                "z".dimmed(),
                 "until".bold(),
                 "u".dimmed(),
                 "update".bold(),
@@ -544,6 +1225,10 @@ Keyboard shortcut lists:
                 "q".dimmed(),
                 "version".bold(),
                 "v".dimmed(),
+                "watch".bold(),
+                "wa".dimmed(),
+                "week".bold(),
+                "wk".dimmed(),
                 "write".bold(),
                 "w".dimmed(),
                 EDITOR_CONFIG.edit_mode(),
@@ -551,32 +1236,54 @@ Keyboard shortcut lists:
         }
         2 => match split_input[1] {
             "add" | "a" => println!("{add_doc}"),
+            "briefing" | "br" => println!("{briefing_doc}"),
             "backup" | "b" => println!("{backup_doc}"),
             "cal" | "c" => println!("{cal_doc}"),
             "clear" => println!("{clear_doc}"),
+            "conflicts" | "co" => println!("{conflicts_doc}"),
             "date" | "D" => println!("{date_doc}"),
+            "done" | "dn" => println!("{done_doc}"),
             "duration" | "d" => println!("{duration_doc}"),
             "edit" | "e" => println!("{edit_doc}"),
+            "edit-deadline" | "ed" => println!("{edit_deadline_doc}"),
+            "export-ics" | "ei" => println!("{export_ics_doc}"),
+            "find" | "f" => println!("{find_doc}"),
+            "free" | "fr" => println!("{free_doc}"),
             "help" | "h" => println!("{help_doc}"),
+            "import-ics" | "ii" => println!("{import_ics_doc}"),
             "list" | "l" | "ls" => println!("{list_doc}"),
             "listcal" | "lc" => println!("{listcal_doc}"),
+            "list-deadlines" | "ld" => println!("{list_deadlines_doc}"),
+            "month" | "mo" => println!("{month_doc}"),
+            "postpone" | "pp" => println!("{postpone_doc}"),
+            "progress" | "pg" => println!("{progress_doc}"),
             "raw" | "R" => println!("{raw_doc}"),
+            "redo" | "Z" => println!("{redo_doc}"),
             "remove" | "rm" | "r" => println!("{remove_doc}"),
             "removecal" | "rmcal" | "rc" => println!("{removecal_doc}"),
+            "reopen" | "ro" => println!("{reopen_doc}"),
             "set" | "s" => println!("{set_doc}"),
+            "show" | "sh" => println!("{show_doc}"),
             "sort" | "S" => println!("{sort_doc}"),
             "time" | "T" => println!("{time_doc}"),
+            "undo" | "z" => println!("{undo_doc}"),
             "until" | "u" => println!("{until_doc}"),
             "update" | "U" => println!("{update_doc}"),
             "update-index" | "Ui" => println!("{update_index_doc}"),
             "quit" | "q" => println!("{quit_doc}"),
             "version" | "v" => println!("{version_doc}"),
+            "watch" | "wa" => println!("{watch_doc}"),
+            "week" | "wk" => println!("{week_doc}"),
             "write" | "w" => println!("{write_doc}"),
-            _ => warning!("help: No documentation for command \"{}\"", split_input[1]),
+            _ => {
+                fail!("help: No documentation for command \"{}\"", split_input[1]);
+            }
         },
-        _ => warning!(
-            "help: Too many arguments provided. Expected: 0 or 1. Got: {}",
-            split_input.len()
-        ),
+        _ => {
+            fail!(
+                "help: Too many arguments provided. Expected: 0 or 1. Got: {}",
+                split_input.len()
+            );
+        }
     }
 }
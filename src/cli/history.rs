@@ -0,0 +1,304 @@
+use crate::{
+    cal::{calmar_error::CalmarError, storage::write_atomic},
+    cli::{
+        hooks::{after_save, before_save},
+        messages::print_err_msg,
+        validator::get_home_dir,
+    },
+    CONFIG,
+};
+use chrono::{DateTime, Local};
+use lazy_static::lazy_static;
+use serde_derive::{Deserialize, Serialize};
+use std::{fs::read_to_string, path::PathBuf, sync::Mutex};
+
+/// Largest size of `history.json` in bytes. The oldest commands are dropped to stay below it.
+pub const MAX_HISTORY_SIZE: usize = 1 << 20;
+
+/// A file before or after a command: its number of lines and the lines the command changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileState {
+    pub length: usize,
+    pub lines: Vec<String>,
+}
+
+/// Change a command made to a file. Only the lines from `line` on that differ are stored,
+/// the lines around them are the same before and after. `None` if the file did not exist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub line: usize,
+    pub before: Option<FileState>,
+    pub after: Option<FileState>,
+}
+
+impl FileChange {
+    /// The change from the contents `before` to `after` of the file under `path`.
+    pub fn new(path: String, before: Option<&str>, after: Option<&str>) -> Self {
+        let old: Vec<&str> = before.unwrap_or_default().split_inclusive('\n').collect();
+        let new: Vec<&str> = after.unwrap_or_default().split_inclusive('\n').collect();
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let state = |content: Option<&str>, lines: &[&str]| {
+            content.map(|_| FileState {
+                length: lines.len(),
+                lines: lines[prefix..lines.len() - suffix]
+                    .iter()
+                    .map(|l| l.to_string())
+                    .collect(),
+            })
+        };
+        FileChange {
+            line: prefix,
+            before: state(before, &old),
+            after: state(after, &new),
+            path,
+        }
+    }
+
+    /// Contents of the file after putting it back the way it was before the change, or after it if `forward`,
+    /// given its `current` contents. `None` if the file is removed.
+    /// Fails if `current` does not match the change, because the file was changed since.
+    pub fn apply(&self, current: Option<&str>, forward: bool) -> Result<Option<String>, String> {
+        let (from, to) = match forward {
+            true => (&self.before, &self.after),
+            false => (&self.after, &self.before),
+        };
+        let changed = Err(format!("{} was changed since.", self.path));
+        let lines: Vec<&str> = current.unwrap_or_default().split_inclusive('\n').collect();
+        let end = self.line + from.as_ref().map_or(0, |f| f.lines.len());
+        match from {
+            None if current.is_some() => return changed,
+            Some(_) if current.is_none() => return changed,
+            Some(f) if f.length != lines.len() || lines[self.line..end] != f.lines[..] => {
+                return changed
+            }
+            _ => {}
+        }
+        Ok(to.as_ref().map(|t| {
+            lines[..self.line]
+                .iter()
+                .copied()
+                .chain(t.lines.iter().map(String::as_str))
+                .chain(lines[end..].iter().copied())
+                .collect()
+        }))
+    }
+}
+
+/// Files changed by a single command, as typed by the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub command: String,
+    pub time: DateTime<Local>,
+    pub changes: Vec<FileChange>,
+}
+
+impl HistoryEntry {
+    /// Names of the changed files, such as "uni.json, index.json".
+    pub fn file_names(&self) -> String {
+        self.changes
+            .iter()
+            .map(|c| match PathBuf::from(&c.path).file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => c.path.clone(),
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Number of bytes the entry takes up in `history.json`.
+    fn size(&self) -> usize {
+        serde_json::to_string(self).map_or(0, |json| json.len())
+    }
+}
+
+/// Commands that can be undone and redone, the most recent last.
+/// Stored in `$HOME/.config/calmar/history.json`, so that it survives restarts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    pub undo: Vec<HistoryEntry>,
+    pub redo: Vec<HistoryEntry>,
+}
+
+fn history_path() -> PathBuf {
+    get_home_dir().join(".config/calmar/history.json")
+}
+
+impl History {
+    /// Returns the saved history, or an empty one if there is no history file yet.
+    pub fn get() -> Result<Self, CalmarError> {
+        let content = match read_to_string(history_path()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(History::default()),
+            Err(e) => return Err(CalmarError::ReadFile { e }),
+        };
+        match serde_json::from_str(&content) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(CalmarError::ParseJSON { e }),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), CalmarError> {
+        let json = match serde_json::to_string(&self) {
+            Ok(result) => result,
+            Err(e) => return Err(CalmarError::ToJSON { e }),
+        };
+        write_atomic(&history_path().to_string_lossy(), &json, false)
+    }
+
+    /// Adds a new command to undo. Commands undone before can no longer be redone.
+    /// Only the `limit` most recent commands are kept, and older ones are dropped
+    /// while they take up more than `MAX_HISTORY_SIZE`. The most recent command is always kept.
+    pub fn push(&mut self, entry: HistoryEntry, limit: usize) {
+        self.redo.clear();
+        self.undo.push(entry);
+        if self.undo.len() > limit {
+            self.undo.drain(..self.undo.len() - limit);
+        }
+        let mut size: usize = self.undo.iter().map(HistoryEntry::size).sum();
+        while size > MAX_HISTORY_SIZE && self.undo.len() > 1 {
+            size -= self.undo.remove(0).size();
+        }
+    }
+}
+
+/// Path to the file a command changes and what was in it before the change.
+struct Recording {
+    command: String,
+    before: Vec<(String, Option<String>)>,
+}
+
+lazy_static! {
+    static ref RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+}
+
+/// Records the files changed while it is alive as a single command to undo.
+/// Returned by `begin`.
+pub struct RecordingGuard {
+    owner: bool,
+}
+
+/// Starts recording the changes made by `command`.
+/// Changes are recorded until the returned guard is dropped.
+/// A command run by another command is recorded as part of it.
+pub fn begin(command: &str) -> RecordingGuard {
+    let mut recording = RECORDING.lock().unwrap();
+    if recording.is_some() {
+        return RecordingGuard { owner: false };
+    }
+    *recording = Some(Recording {
+        command: command.to_string(),
+        before: vec![],
+    });
+    RecordingGuard { owner: true }
+}
+
+/// The command whose changes are being recorded, if any.
+pub fn current_command() -> Option<String> {
+    RECORDING
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|r| r.command.clone())
+}
+
+/// Remembers the contents of the file under `path`, which is about to be changed or removed,
+/// so that the current command can be undone.
+pub fn track(path: &str) {
+    let mut recording = RECORDING.lock().unwrap();
+    let recording = match recording.as_mut() {
+        Some(r) => r,
+        None => return,
+    };
+    if recording.before.iter().any(|(p, _)| p == path) {
+        return;
+    }
+    recording
+        .before
+        .push((path.to_string(), read_to_string(path).ok()));
+}
+
+impl Drop for RecordingGuard {
+    fn drop(&mut self) {
+        if !self.owner {
+            return;
+        }
+        let recording = match RECORDING.lock().unwrap().take() {
+            Some(r) => r,
+            None => return,
+        };
+        let changes: Vec<FileChange> = recording
+            .before
+            .into_iter()
+            .filter_map(|(path, before)| {
+                let after = read_to_string(&path).ok();
+                match before == after {
+                    true => None,
+                    false => Some(FileChange::new(path, before.as_deref(), after.as_deref())),
+                }
+            })
+            .collect();
+        if changes.is_empty() || CONFIG.undo_limit == 0 {
+            return;
+        }
+
+        let mut history = match History::get() {
+            Ok(h) => h,
+            Err(e) => {
+                print_err_msg(e, history_path().display());
+                return;
+            }
+        };
+        history.push(
+            HistoryEntry {
+                command: recording.command,
+                time: Local::now(),
+                changes,
+            },
+            CONFIG.undo_limit,
+        );
+        if let Err(e) = history.save() {
+            print_err_msg(e, history_path().display());
+        }
+    }
+}
+
+/// Puts the files changed by `entry` back the way they were before it, or after it if `forward`.
+/// Nothing is changed if any of the files was modified since, or if a pre-save hook refuses a change.
+/// On error, returns a message naming the first file that could not be restored.
+pub fn restore(entry: &HistoryEntry, forward: bool) -> Result<(), String> {
+    let mut contents = vec![];
+    for change in &entry.changes {
+        let current = read_to_string(&change.path).ok();
+        let target = change.apply(current.as_deref(), forward)?;
+        contents.push((current, target));
+    }
+    for (change, (current, target)) in entry.changes.iter().zip(&contents) {
+        if let Some(content) = target {
+            if let Err(CalmarError::Hook { e }) =
+                before_save(&change.path, current.as_deref(), content)
+            {
+                return Err(format!("A pre-save hook refused the change to {}.\n{e}", change.path));
+            }
+        }
+    }
+    for (change, (current, target)) in entry.changes.iter().zip(&contents) {
+        let result = match target {
+            Some(content) => write_atomic(&change.path, content, false).is_ok(),
+            None => std::fs::remove_file(&change.path).is_ok(),
+        };
+        if !result {
+            return Err(format!("{} cannot be written to.", change.path));
+        }
+        if let Some(content) = target {
+            after_save(&change.path, current.as_deref(), content);
+        }
+    }
+    Ok(())
+}
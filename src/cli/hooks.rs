@@ -0,0 +1,197 @@
+use crate::{cal::calmar_error::CalmarError, cli::history::current_command, warning, CONFIG};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// External commands run when a calendar or the index is saved, set in the config.
+/// Each command is run through sh with the path of the affected calendar as its first argument
+/// and a JSON description of the change on stdin.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Hooks {
+    /// Run before a file is saved. A non-zero exit status cancels the save.
+    pub pre_save: Vec<String>,
+    pub post_save: Vec<String>,
+    /// Run after a save that added events, deadlines or calendars.
+    pub post_add: Vec<String>,
+    /// Run after a save that removed events, deadlines or calendars.
+    pub post_remove: Vec<String>,
+    /// Run after the active calendar is changed, with the path of the new one.
+    pub on_calendar_switch: Vec<String>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_save.is_empty()
+            && self.post_save.is_empty()
+            && self.post_add.is_empty()
+            && self.post_remove.is_empty()
+            && self.on_calendar_switch.is_empty()
+    }
+}
+
+/// Arrays of the calendar and index files whose items are compared by ID.
+const ITEM_ARRAYS: [&str; 3] = ["events", "deadlines", "calendars"];
+
+fn items<'a>(document: &'a Value, key: &str) -> Vec<&'a Value> {
+    match document.get(key).and_then(|v| v.as_array()) {
+        Some(items) => items.iter().collect(),
+        None => vec![],
+    }
+}
+
+fn id(item: &Value) -> Option<&Value> {
+    item.get("id")
+}
+
+/// The item in `list` with the same ID as `item`.
+fn find<'a>(list: &[&'a Value], item: &Value) -> Option<&'a Value> {
+    list.iter().find(|i| id(i) == id(item)).copied()
+}
+
+/// The reference to the active calendar in the index `document`, if there is one.
+fn active(document: &Value) -> Option<&Value> {
+    items(document, "calendars")
+        .into_iter()
+        .find(|r| r.get("active") == Some(&json!(true)))
+}
+
+/// Describes how the file under `path` changes from `before` to `after`,
+/// both parsed from JSON, with the items added, removed and changed in each of its arrays.
+pub fn describe(path: &str, before: &Value, after: &Value) -> Value {
+    let mut added = Map::new();
+    let mut removed = Map::new();
+    let mut changed = Map::new();
+    for key in ITEM_ARRAYS {
+        let (old, new) = (items(before, key), items(after, key));
+        let put = |map: &mut Map<String, Value>, list: Vec<&Value>| {
+            if !list.is_empty() {
+                map.insert(key.to_string(), json!(list));
+            }
+        };
+        put(
+            &mut added,
+            new.iter()
+                .filter(|i| find(&old, i).is_none())
+                .copied()
+                .collect(),
+        );
+        put(
+            &mut removed,
+            old.iter()
+                .filter(|i| find(&new, i).is_none())
+                .copied()
+                .collect(),
+        );
+        put(
+            &mut changed,
+            new.iter()
+                .filter(|i| find(&old, i).is_some_and(|o| o != **i))
+                .copied()
+                .collect(),
+        );
+    }
+    json!({
+        "path": path,
+        "command": current_command(),
+        "added": added,
+        "removed": removed,
+        "changed": changed,
+    })
+}
+
+/// Runs `command` with `path` as its first argument and `description` of the change,
+/// along with the name of the `hook`, on stdin, and waits for it to finish.
+fn run(hook: &str, command: &str, path: &str, description: &Value) -> Result<(), String> {
+    let mut description = description.clone();
+    description["hook"] = json!(hook);
+    let mut child = match Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .arg("sh")
+        .arg(path)
+        .stdin(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return Err(format!("Failed to run {command}.\n{e}")),
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // a hook does not have to read the description
+        let _ = stdin.write_all(description.to_string().as_bytes());
+    }
+    match child.wait() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("{command} exited with {status}")),
+        Err(e) => Err(format!("Failed to run {command}.\n{e}")),
+    }
+}
+
+fn parse(content: Option<&str>) -> Value {
+    content
+        .and_then(|c| serde_json::from_str(c).ok())
+        .unwrap_or(Value::Null)
+}
+
+/// Runs the pre-save hooks before the file under `path` is changed from `before` to `after`.
+/// Fails with `CalmarError::Hook` as soon as one of them exits with a non-zero status.
+pub fn before_save(path: &str, before: Option<&str>, after: &str) -> Result<(), CalmarError> {
+    if CONFIG.hooks.pre_save.is_empty() {
+        return Ok(());
+    }
+    let description = describe(path, &parse(before), &parse(Some(after)));
+    for command in &CONFIG.hooks.pre_save {
+        if let Err(e) = run("pre-save", command, path, &description) {
+            return Err(CalmarError::Hook { e });
+        }
+    }
+    Ok(())
+}
+
+/// Runs the post-save hooks after the file under `path` was changed from `before` to `after`,
+/// the post-add and post-remove hooks if items were added or removed,
+/// and the calendar switch hooks if the active calendar changed.
+/// Hooks that fail are reported, but the change stays.
+pub fn after_save(path: &str, before: Option<&str>, after: &str) {
+    let hooks = &CONFIG.hooks;
+    if hooks.post_save.is_empty()
+        && hooks.post_add.is_empty()
+        && hooks.post_remove.is_empty()
+        && hooks.on_calendar_switch.is_empty()
+    {
+        return;
+    }
+    let (before, after) = (parse(before), parse(Some(after)));
+    let description = describe(path, &before, &after);
+    let non_empty = |key: &str| description[key].as_object().is_some_and(|m| !m.is_empty());
+
+    let mut runs: Vec<(&str, &Vec<String>, String, Value)> = vec![];
+    runs.push(("post-save", &hooks.post_save, path.to_string(), description.clone()));
+    if non_empty("added") {
+        runs.push(("post-add", &hooks.post_add, path.to_string(), description.clone()));
+    }
+    if non_empty("removed") {
+        runs.push(("post-remove", &hooks.post_remove, path.to_string(), description.clone()));
+    }
+    if let Some(to) = active(&after).filter(|to| active(&before).map(id) != Some(id(to))) {
+        let calendar_path = to["path"].as_str().unwrap_or(path).to_string();
+        let switch = json!({
+            "path": calendar_path,
+            "command": current_command(),
+            "from": active(&before),
+            "to": to,
+        });
+        runs.push(("on-calendar-switch", &hooks.on_calendar_switch, calendar_path, switch));
+    }
+
+    for (hook, commands, path, description) in runs {
+        for command in commands {
+            if let Err(e) = run(hook, command, &path, &description) {
+                warning!("The {hook} hook failed. The change was saved.\n{e}");
+            }
+        }
+    }
+}
@@ -5,6 +5,7 @@ macro_rules! error {
     };
     ($($arg:tt)*) => {{
 	use colored::Colorize;
+	$crate::cli::messages::set_failed();
 	if CONFIG.print_error_messages {
             println!("{}", format_args!($($arg)*).to_string().red().bold());
 	}
@@ -22,6 +23,14 @@ macro_rules! warning {
 	}
     }};
 }
+/// A warning that the command failed, which is reported through the exit status.
+#[macro_export]
+macro_rules! fail {
+    ($($arg:tt)*) => {{
+	$crate::cli::messages::set_failed();
+	$crate::warning!($($arg)*);
+    }};
+}
 #[macro_export]
 macro_rules! success {
     () => {
@@ -1,4 +1,21 @@
-use crate::{cal::calmar_error::CalmarError, error, CONFIG};
+use crate::{
+    cal::{calmar_error::CalmarError, migration::SCHEMA_VERSION},
+    error, CONFIG,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by the `error!` and `fail!` macros, so that a failed command
+/// can be reported through the exit status in command-line mode.
+static FAILED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_failed() {
+    FAILED.store(true, Ordering::SeqCst)
+}
+
+/// Returns whether a command failed since the last call and resets the flag.
+pub fn take_failed() -> bool {
+    FAILED.swap(false, Ordering::SeqCst)
+}
 
 pub fn print_err_msg<T: std::fmt::Display>(err: CalmarError, info: T) {
     match err {
@@ -15,5 +32,18 @@ pub fn print_err_msg<T: std::fmt::Display>(err: CalmarError, info: T) {
         CalmarError::CreateDir { e } => {
             error!("Failed to create directory at {info}.\n{e}")
         }
+        CalmarError::ParseICS { e } => error!("Failed to parse {} as iCalendar.\n{}", info, e),
+        CalmarError::LockFile { e } => error!("Failed to lock {}.\n{}", info, e),
+        CalmarError::NewerSchema { version } => error!(
+            "{} was written by a newer version of calmar, in file format version {}. This version only reads up to {}.\nUpdate calmar to use this file. It was left unchanged.",
+            info,
+            version,
+            SCHEMA_VERSION
+        ),
+        CalmarError::UnknownSchema { e } => error!("Failed to read the file format of {}.\n{}", info, e),
+        CalmarError::Hook { e } => error!("{} was not saved, because a pre-save hook refused the change.\n{}", info, e),
+        CalmarError::ChangedOnDisk { path } => error!(
+            "{path} was changed by another program since it was read. The change was not saved.\nRun the command again to apply it to the current contents."
+        ),
     }
 }
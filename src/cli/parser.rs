@@ -9,13 +9,16 @@ use crate::{
         functions::handle_unknown_command,
         help::print_help,
         help::print_version,
+        history,
     },
     error, CONFIG,
 };
 use std::ops::Range;
 
 use super::commands::{
-    backup, briefing, deadline, edit_cal, except, ls_deadlines, remove_deadline,
+    backup, briefing, conflicts, deadline, done, edit_cal, edit_deadline, except, export_ics, find,
+    free, import_ics, ls_deadlines, month, postpone, progress, redo, remove_deadline, reopen, show,
+    undo, watch, week,
 };
 
 /// Handle input and call appropriate functions.
@@ -26,37 +29,60 @@ pub fn parse(input: String) {
     }
     let quote_parsed = handle_quotes(input);
     let split_input: Vec<&str> = quote_parsed.iter().map(|s| &**s).collect();
+    dispatch(&split_input);
+}
+
+/// Call the function bound to the command in `split_input[0]`.
+/// The remaining elements are passed to it as arguments.
+pub fn dispatch(split_input: &[&str]) {
+    let _recording = history::begin(&split_input.join(" "));
     match split_input[0].trim() {
-        "add" | "a" => add(&split_input),
-        "backup" | "b" => backup(&split_input),
-        "briefing" | "br" => briefing(),
-        "cal" | "c" => cal(&split_input),
-        "clear" => clear(&split_input),
+        "add" | "a" => add(split_input),
+        "backup" | "b" => backup(split_input),
+        "briefing" | "br" => briefing(split_input),
+        "cal" | "c" => cal(split_input),
+        "clear" => clear(split_input),
+        "conflicts" | "co" => conflicts(split_input),
         "date" | "D" => date(),
-        "deadline" | "dead" | "de" => deadline(&split_input),
-        "duration" | "d" => duration(&split_input),
-        "edit" | "e" => edit(&split_input),
-        "edit-calendar" | "edit-cal" | "ec" => edit_cal(&split_input),
-        "except" | "x" => except(&split_input),
-        "help" | "h" => print_help(&split_input),
-        "list" | "l" | "ls" => list(&split_input),
-        "listcal" | "lc" => listcal(&split_input),
-        "list-deadlines" | "ld" => ls_deadlines(&split_input),
+        "deadline" | "dead" | "de" => deadline(split_input),
+        "done" | "dn" => done(split_input),
+        "duration" | "d" => duration(split_input),
+        "edit" | "e" => edit(split_input),
+        "edit-calendar" | "edit-cal" | "ec" => edit_cal(split_input),
+        "edit-deadline" | "ed" => edit_deadline(split_input),
+        "except" | "x" => except(split_input),
+        "export-ics" | "ei" => export_ics(split_input),
+        "find" | "f" => find(split_input),
+        "free" | "fr" => free(split_input),
+        "help" | "h" => print_help(split_input),
+        "import-ics" | "ii" => import_ics(split_input),
+        "list" | "l" | "ls" => list(split_input),
+        "listcal" | "lc" => listcal(split_input),
+        "list-deadlines" | "ld" => ls_deadlines(split_input),
         "mkindex" => mkindex(),
+        "month" | "mo" => month(split_input),
         "mkconfig" => mkconfig(),
-        "raw" | "R" => raw(&split_input),
-        "remove" | "rm" | "r" => remove(&split_input),
-        "removecal" | "rmcal" | "rc" => removecal(&split_input),
-        "remove-deadline" | "rmd" | "rd" => remove_deadline(&split_input),
-        "set" | "s" => set(&split_input),
-        "sort" | "S" => sort(&split_input),
+        "postpone" | "pp" => postpone(split_input),
+        "progress" | "pg" => progress(split_input),
+        "raw" | "R" => raw(split_input),
+        "remove" | "rm" | "r" => remove(split_input),
+        "redo" | "Z" => redo(),
+        "removecal" | "rmcal" | "rc" => removecal(split_input),
+        "reopen" | "ro" => reopen(split_input),
+        "remove-deadline" | "rmd" | "rd" => remove_deadline(split_input),
+        "set" | "s" => set(split_input),
+        "show" | "sh" => show(split_input),
+        "sort" | "S" => sort(split_input),
         "time" | "T" => time(),
-        "until" | "u" => until(&split_input),
+        "undo" | "z" => undo(),
+        "until" | "u" => until(split_input),
         "update" | "U" => update(),
         "update-index" | "Ui" => update_index(),
         "quit" | "q" => std::process::exit(0),
         "version" | "v" => print_version(),
-        "write" | "w" => write(&split_input),
+        "watch" | "wa" => watch(split_input),
+        "week" | "wk" => week(split_input),
+        "write" | "w" => write(split_input),
         _ => handle_unknown_command(split_input[0]),
     }
 }
@@ -87,10 +113,7 @@ fn handle_quotes(input: String) -> Vec<String> {
                     known_quote[j] = true;
                     // i+1 to skip the opening quote
                     quoted_ranges.push(i + 1..j);
-                    let mut x = String::new();
-                    for i in i + 1..j {
-                        x.push_str(chars[i])
-                    }
+                    let x = chars[i + 1..j].concat();
                     out.push(x.clone());
                     break;
                 }
@@ -104,12 +127,9 @@ fn handle_quotes(input: String) -> Vec<String> {
             for j in i..chars.len() {
                 // if we find a space
                 if chars[j] == " " || j == chars.len() - 1 || chars[j] == quotation_symbol {
-                    let mut x = String::new();
                     // i..j since we don't skip a quote here
                     non_quoted_ranges.push(i..j);
-                    for p in i..=j {
-                        x.push_str(chars[p])
-                    }
+                    let mut x = chars[i..=j].concat();
                     if x.ends_with(" ") || chars[j] == quotation_symbol {
                         x.pop();
                     }
@@ -119,10 +139,12 @@ fn handle_quotes(input: String) -> Vec<String> {
             }
         }
         // last iteration
-        if i == chars.len() - 1 && chars[i] != quotation_symbol && chars.len() != 1 {
-            if input.split_ascii_whitespace().last().unwrap().len() != 1 {
-                out.pop();
-            }
+        if i == chars.len() - 1
+            && chars[i] != quotation_symbol
+            && chars.len() != 1
+            && input.split_ascii_whitespace().last().unwrap().len() != 1
+        {
+            out.pop();
         }
     }
     out
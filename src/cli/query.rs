@@ -0,0 +1,272 @@
+use crate::{
+    cal::{
+        calendar_ref::CalendarReference, calmar_trait::CalendarDataType, deadline::Deadline,
+        details::Details, event::Event, id::find_by_key, recurrence::days_in_month,
+        timezone::from_wall_clock,
+    },
+    cli::{
+        getdata::{parse_into_date, parse_into_duration, parse_month},
+        validator::validate_date,
+    },
+    CONFIG,
+};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
+use regex::Regex;
+
+/// How a number such as the priority is compared in a query, as in `priority>=7`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    fn parse(operator: &str) -> Option<Self> {
+        match operator {
+            "<" => Some(Comparison::Less),
+            "<=" => Some(Comparison::LessOrEqual),
+            ":" | "=" => Some(Comparison::Equal),
+            ">=" => Some(Comparison::GreaterOrEqual),
+            ">" => Some(Comparison::Greater),
+            _ => None,
+        }
+    }
+
+    fn holds(&self, left: u8, right: u8) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Equal => left == right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Greater => left > right,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum NameFilter {
+    /// Matches names containing the text, ignoring case
+    Contains(String),
+    Regex(Regex),
+}
+
+impl NameFilter {
+    /// A regular expression between slashes, such as `/^lab [0-9]+$/`, or text to look for.
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.len() > 1 && value.starts_with('/') && value.ends_with('/') {
+            true => match Regex::new(&value[1..value.len() - 1]) {
+                Ok(re) => Ok(NameFilter::Regex(re)),
+                Err(e) => Err(format!("{value} is not a valid regular expression: {e}")),
+            },
+            false => Ok(NameFilter::Contains(value.to_lowercase())),
+        }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            NameFilter::Contains(text) => name.to_lowercase().contains(text),
+            NameFilter::Regex(re) => re.is_match(name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Event,
+    Deadline,
+}
+
+/// Conditions events and deadlines are searched for with, all of which have to hold.
+/// Events are matched as they are stored, or by each of their occurrences if a date range is given.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    names: Vec<NameFilter>,
+    priority: Vec<(Comparison, u8)>,
+    difficulty: Vec<(Comparison, u8)>,
+    tags: Vec<String>,
+    calendars: Vec<String>,
+    kind: Option<ItemKind>,
+    recurring: Option<bool>,
+    /// First day of the date range
+    from: Option<NaiveDate>,
+    /// Day after the last day of the date range
+    to: Option<NaiveDate>,
+}
+
+/// First day of the period `value` names and the day after its last one.
+/// A period is a date, a month such as 11/2026, or one of the words
+/// today, tomorrow, week, next-week, month and next-month.
+fn parse_period(value: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let today = Local::now().date_naive();
+    let first_weekday: Weekday = CONFIG.first_weekday.parse().unwrap_or(Weekday::Mon);
+    let week_start = today
+        - Duration::days(
+            ((7 + today.weekday().num_days_from_monday() - first_weekday.num_days_from_monday())
+                % 7) as i64,
+        );
+    let month = |year: i32, month: u32| {
+        let first = NaiveDate::from_ymd(year, month, 1);
+        (first, first + Duration::days(days_in_month(year, month) as i64))
+    };
+    let next_month = match today.month() {
+        12 => (today.year() + 1, 1),
+        m => (today.year(), m + 1),
+    };
+    match value.to_lowercase().as_str() {
+        "today" => Some((today, today + Duration::days(1))),
+        "tomorrow" => Some((today + Duration::days(1), today + Duration::days(2))),
+        "week" => Some((week_start, week_start + Duration::days(7))),
+        "next-week" => Some((week_start + Duration::days(7), week_start + Duration::days(14))),
+        "month" => Some(month(today.year(), today.month())),
+        "next-month" => Some(month(next_month.0, next_month.1)),
+        _ if validate_date(value) => {
+            let date = parse_into_date(value).naive_local();
+            Some((date, date + Duration::days(1)))
+        }
+        _ => parse_month(value).map(|(y, m)| month(y, m)),
+    }
+}
+
+/// Splits a term such as `priority>=7` into its key, operator and value.
+fn split_term(term: &str) -> Option<(&str, &str, &str)> {
+    let i = term.find(|c| ":<>=".contains(c))?;
+    let operator_length = match term[i..].starts_with("<=") || term[i..].starts_with(">=") {
+        true => 2,
+        false => 1,
+    };
+    Some((&term[..i], &term[i..i + operator_length], &term[i + operator_length..]))
+}
+
+impl Query {
+    /// Parses a query made of `terms` such as `exam`, `tag:math` or `priority>=7`.
+    /// On error, returns a message explaining what is wrong.
+    pub fn parse(terms: &[&str]) -> Result<Self, String> {
+        let mut query = Query::default();
+        for term in terms {
+            let (key, operator, value) = match split_term(term) {
+                Some(split) => split,
+                None => {
+                    query.names.push(NameFilter::parse(term)?);
+                    continue;
+                }
+            };
+            if value.is_empty() {
+                return Err(format!("{term}: Expected a value after \"{operator}\""));
+            }
+            let number = || match value.parse::<u8>() {
+                Ok(n) if n <= 10 => Ok((Comparison::parse(operator).unwrap(), n)),
+                _ => Err(format!("{term}: Expected a number from 0 to 10")),
+            };
+            let period = || match parse_period(value) {
+                Some(period) => Ok(period),
+                None => Err(format!("{term}: {value} is not a valid date, month or period")),
+            };
+            let key = key.to_lowercase();
+            let comparable = matches!(key.as_str(), "priority" | "p" | "difficulty" | "d");
+            if !comparable && !matches!(operator, ":" | "=") {
+                return Err(format!("{term}: Only priority and difficulty can be compared"));
+            }
+            match key.as_str() {
+                "priority" | "p" => query.priority.push(number()?),
+                "difficulty" | "d" => query.difficulty.push(number()?),
+                "name" | "n" => query.names.push(NameFilter::parse(value)?),
+                "tag" | "t" => query.tags.push(value.to_lowercase()),
+                "calendar" | "cal" | "c" => query
+                    .calendars
+                    .extend(value.split(',').map(str::trim).map(str::to_string)),
+                "is" => match value.to_lowercase().as_str() {
+                    "event" => query.kind = Some(ItemKind::Event),
+                    "deadline" => query.kind = Some(ItemKind::Deadline),
+                    "recurring" => query.recurring = Some(true),
+                    "once" => query.recurring = Some(false),
+                    _ => {
+                        return Err(format!("{term}: Expected event, deadline, recurring or once"))
+                    }
+                },
+                "from" => query.from = Some(period()?.0),
+                "to" => query.to = Some(period()?.1),
+                "date" => {
+                    let (from, to) = period()?;
+                    query.from = Some(from);
+                    query.to = Some(to);
+                }
+                _ => return Err(format!("{term}: Unknown filter \"{key}\"")),
+            }
+        }
+        Ok(query)
+    }
+
+    /// Calendar names or IDs the query is limited to. Empty if it searches every calendar.
+    pub fn calendars(&self) -> &Vec<String> {
+        &self.calendars
+    }
+
+    pub fn includes_calendar(&self, reference: &CalendarReference) -> bool {
+        self.calendars.is_empty()
+            || self.calendars.iter().any(|key| {
+                !find_by_key(std::iter::once((reference.name(), reference.id())), key).is_empty()
+            })
+    }
+
+    /// Start and end of the date range, or `None` if the query has no date range.
+    /// A range without an end lasts `default_calendar_span` from the config,
+    /// and a range without a start starts now.
+    pub fn range(&self) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        let start = match self.from {
+            Some(from) => from_wall_clock(from.and_hms(0, 0, 0), None)?,
+            None if self.to.is_some() => Local::now(),
+            None => return None,
+        };
+        let end = match self.to {
+            Some(to) => from_wall_clock(to.and_hms(0, 0, 0), None)?,
+            None => {
+                start
+                    + parse_into_duration(&CONFIG.default_calendar_span)
+                        .unwrap_or_else(|_| Duration::zero())
+            }
+        };
+        Some((start, end))
+    }
+
+    fn matches_item<T: CalendarDataType>(
+        &self,
+        item: &T,
+        kind: ItemKind,
+        details: &Details,
+    ) -> bool {
+        self.kind.is_none_or(|k| k == kind)
+            && self.names.iter().all(|n| n.matches(&item.name()))
+            && self
+                .priority
+                .iter()
+                .all(|(c, p)| c.holds(item.priority(), *p))
+            && self
+                .tags
+                .iter()
+                .all(|wanted| details.tags().iter().any(|t| t.to_lowercase() == *wanted))
+    }
+
+    /// Whether `event` meets every condition but the date range.
+    pub fn matches_event(&self, event: &Event) -> bool {
+        self.matches_item(event, ItemKind::Event, event.details())
+            && self
+                .difficulty
+                .iter()
+                .all(|(c, d)| c.holds(event.difficulty(), *d))
+            && self.recurring.is_none_or(|r| r != event.repeat().is_none())
+    }
+
+    /// Whether `deadline` meets every condition, including the date range.
+    /// Deadlines have no difficulty and do not repeat.
+    pub fn matches_deadline(&self, deadline: &Deadline) -> bool {
+        self.matches_item(deadline, ItemKind::Deadline, deadline.details())
+            && self.difficulty.is_empty()
+            && self.recurring != Some(true)
+            && self
+                .range()
+                .is_none_or(|(start, end)| start <= deadline.date() && deadline.date() < end)
+    }
+}
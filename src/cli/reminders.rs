@@ -0,0 +1,194 @@
+use crate::{
+    cal::{
+        calendar::Calendar, calendar_index::CalendarIndex, calmar_error::CalmarError,
+        calmar_trait::CalendarDataType, details::Details, storage::write_atomic,
+    },
+    cli::{
+        functions::generate_until,
+        util::{datetime_fmt, time_fmt},
+        validator::get_home_dir,
+    },
+    CONFIG,
+};
+use chrono::{DateTime, Duration, Local};
+use colored::Colorize;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap, fs::read_to_string, path::PathBuf, process::Command, time::SystemTime,
+};
+
+/// Seconds `watch` waits between looking for due reminders and changed calendar files.
+pub const WATCH_INTERVAL: u64 = 10;
+
+/// A reminder of an occurrence of an event or of a deadline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reminder {
+    pub name: String,
+    pub calendar: String,
+    /// Start of the occurrence or due date of the deadline
+    pub time: DateTime<Local>,
+    /// Minutes before `time` the reminder is given at
+    pub offset: u32,
+    pub deadline: bool,
+    id: String,
+}
+
+impl Reminder {
+    /// When the reminder is given.
+    pub fn at(&self) -> DateTime<Local> {
+        self.time - Duration::minutes(self.offset as i64)
+    }
+
+    /// Identifies the reminder across restarts of `watch`.
+    /// A rescheduled event or deadline is reminded of again.
+    pub fn key(&self) -> String {
+        format!("{}@{}-{}", self.id, self.time.to_rfc3339(), self.offset)
+    }
+
+    pub fn message(&self) -> String {
+        format!(
+            "{} {} at {} ({})",
+            self.name,
+            match self.deadline {
+                true => "is due",
+                false => "starts",
+            },
+            datetime_fmt(self.time),
+            self.calendar
+        )
+    }
+}
+
+/// Reminders of every event occurrence and unfinished deadline in `calendars`
+/// that are due by `now` and whose event or deadline is still ahead, sorted by when they are due.
+/// Of several reminders due for the same occurrence or deadline, only the last one is returned.
+pub fn due_reminders(calendars: &[(String, Calendar)], now: DateTime<Local>) -> Vec<Reminder> {
+    let mut reminders = vec![];
+    let mut add = |calendar: &str, id: &str, name: String, time, deadline, details: &Details| {
+        // the shortest offsets are due last
+        for offset in details.reminders() {
+            let reminder = Reminder {
+                name: name.clone(),
+                calendar: calendar.to_string(),
+                time,
+                offset: *offset,
+                deadline,
+                id: id.to_string(),
+            };
+            if reminder.at() <= now && now < time {
+                reminders.push(reminder);
+                break;
+            }
+        }
+    };
+    for (name, calendar) in calendars {
+        let longest = calendar
+            .events()
+            .iter()
+            .filter_map(|e| e.details().reminders().iter().max())
+            .max();
+        if let Some(longest) = longest {
+            // occurrences starting after the longest offset from now have no reminder due yet
+            let end = now + Duration::minutes(*longest as i64 + 1);
+            for event in generate_until(calendar, end) {
+                add(name, event.id(), event.name(), event.start(), false, event.details());
+            }
+        }
+        for deadline in calendar.deadlines().iter().filter(|d| !d.is_finished()) {
+            add(name, deadline.id(), deadline.name(), deadline.date(), true, deadline.details());
+        }
+    }
+    reminders.sort_by_key(|r| (r.at(), r.key()));
+    reminders
+}
+
+/// Gives `reminder`, by running `notify_command` from the config with its message
+/// as the last argument, or by printing it if there is no such command.
+pub fn notify(reminder: &Reminder) -> Result<(), String> {
+    if CONFIG.notify_command.trim().is_empty() {
+        println!("{} {}", time_fmt(Local::now().time()).dimmed(), reminder.message().bold());
+        return Ok(());
+    }
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", CONFIG.notify_command))
+        .arg("sh")
+        .arg(reminder.message())
+        .status();
+    match status {
+        Ok(s) if s.success() => Ok(()),
+        Ok(s) => Err(format!("{} exited with {s}", CONFIG.notify_command)),
+        Err(e) => Err(format!("Failed to run {}.\n{e}", CONFIG.notify_command)),
+    }
+}
+
+fn fired_path() -> PathBuf {
+    get_home_dir().join(".config/calmar/reminders.json")
+}
+
+/// Keys of the reminders given already, each with the time of its event or deadline.
+/// Stored in `$HOME/.config/calmar/reminders.json`, so that no reminder is given twice.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FiredReminders {
+    fired: BTreeMap<String, DateTime<Local>>,
+}
+
+impl FiredReminders {
+    /// Returns the saved reminders, or none if there is no file yet.
+    pub fn get() -> Result<Self, CalmarError> {
+        let content = match read_to_string(fired_path()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(FiredReminders::default())
+            }
+            Err(e) => return Err(CalmarError::ReadFile { e }),
+        };
+        match serde_json::from_str(&content) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(CalmarError::ParseJSON { e }),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), CalmarError> {
+        let json = match serde_json::to_string_pretty(&self) {
+            Ok(result) => result,
+            Err(e) => return Err(CalmarError::ToJSON { e }),
+        };
+        write_atomic(&fired_path().to_string_lossy(), &json, false)
+    }
+
+    pub fn contains(&self, reminder: &Reminder) -> bool {
+        self.fired.contains_key(&reminder.key())
+    }
+
+    pub fn insert(&mut self, reminder: &Reminder) {
+        self.fired.insert(reminder.key(), reminder.time);
+    }
+
+    /// Forgets the reminders of events and deadlines that are over by `now`,
+    /// as they are never due again. Returns whether any were forgotten.
+    pub fn prune(&mut self, now: DateTime<Local>) -> bool {
+        let count = self.fired.len();
+        self.fired.retain(|_, time| *time > now);
+        self.fired.len() != count
+    }
+
+    pub fn path() -> String {
+        fired_path().display().to_string()
+    }
+}
+
+/// Paths to the index and to every calendar in `index`, the files `watch` reads again when they change.
+pub fn watched_paths(index: &CalendarIndex) -> Vec<String> {
+    let mut paths = vec![CONFIG.index_path.clone()];
+    paths.extend(index.calendars().iter().map(|r| r.path()));
+    paths
+}
+
+/// When each of the files under `paths` was last modified, `None` for files that cannot be read.
+pub fn modification_times(paths: &[String]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+        .collect()
+}
@@ -1,6 +1,23 @@
-use crate::{cli::validator::get_home_dir, error, CONFIG, EDITOR_CONFIG};
+use crate::{
+    cli::{
+        script::{answer_follows, next_answer, PromptRejected},
+        validator::get_home_dir,
+    },
+    error, CONFIG, EDITOR_CONFIG,
+};
 use colored::{ColoredString, Colorize};
 use rustyline::{error::ReadlineError, Editor};
+use std::{
+    io::IsTerminal,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Set when a single command is run from the command line instead of the REPL.
+static COMMAND_LINE_MODE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_command_line_mode() {
+    COMMAND_LINE_MODE.store(true, Ordering::SeqCst)
+}
 
 /*
 Perfom everything necessary to get clean input from stdin:
@@ -9,14 +26,26 @@ Perfom everything necessary to get clean input from stdin:
 - pop the '\n' character
 - trim trailing whitespace
  */
-/// Get clean stdin input without trailing spaces and newline
-pub fn get_input(prompt: &str, initial: Option<&str>) -> String {
+/// Get clean stdin input without trailing spaces and newline.
+/// In a script, the answer is taken from its next line, and the prompt is rejected if there is none.
+pub fn get_input(prompt: &str, initial: Option<&str>) -> Result<String, PromptRejected> {
+    if let Some(answer) = next_answer(prompt)? {
+        return Ok(answer);
+    }
+    // Nobody can answer the prompt, so waiting on stdin could hang forever
+    if COMMAND_LINE_MODE.load(Ordering::SeqCst) && !std::io::stdin().is_terminal() {
+        error!(
+            "Input required for \"{}\", but stdin is not a terminal.\nPass the data as arguments or run the command interactively.",
+            prompt.trim().trim_end_matches(':')
+        );
+        std::process::exit(1);
+    }
     let mut rl = Editor::<()>::with_config(*EDITOR_CONFIG).unwrap();
 
     let readline = rl.readline_with_initial(prompt, (initial.unwrap_or(""), ""));
 
     match readline {
-        Ok(line) => line,
+        Ok(line) => Ok(line),
         Err(ReadlineError::Interrupted) => {
             std::process::exit(1);
         }
@@ -30,6 +59,15 @@ pub fn get_input(prompt: &str, initial: Option<&str>) -> String {
     }
 }
 
+/// Whether an optional prompt can be answered.
+/// It cannot in command-line mode without a terminal, or in a script without an answer line after the command.
+pub fn can_prompt() -> bool {
+    match answer_follows() {
+        Some(follows) => follows,
+        None => !COMMAND_LINE_MODE.load(Ordering::SeqCst) || std::io::stdin().is_terminal(),
+    }
+}
+
 /// Print a prompt as defined in config.json,
 /// add a space at the end.
 fn get_prompt() -> ColoredString {
@@ -0,0 +1,157 @@
+use crate::{
+    cli::{messages::take_failed, parser::parse},
+    error, warning, CONFIG,
+};
+use lazy_static::lazy_static;
+use std::{io::Read, sync::Mutex};
+
+/// A single line of a calmar script.
+/// # Syntax
+/// * Lines starting with `#` are comments, empty lines are ignored.
+/// * Lines starting with `>` answer the prompts of the command before them, in order.
+///   `>` alone answers with an empty string, which accepts the default where a prompt has one.
+/// * Every other line is a command, exactly as it would be typed in the REPL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptLine {
+    Command(String),
+    Answer(String),
+    Skip,
+}
+
+impl From<&str> for ScriptLine {
+    fn from(line: &str) -> Self {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            ScriptLine::Skip
+        } else if let Some(answer) = trimmed.strip_prefix('>') {
+            ScriptLine::Answer(answer.trim().to_string())
+        } else {
+            ScriptLine::Command(trimmed.to_string())
+        }
+    }
+}
+
+/// Returned by prompts of a script command when no answer line follows it.
+/// Commands pass it up with `?` and stop, the rejection has already been reported as an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptRejected;
+
+struct Script {
+    lines: Vec<ScriptLine>,
+    // index of the next line to be read
+    pos: usize,
+}
+
+lazy_static! {
+    static ref SCRIPT: Mutex<Option<Script>> = Mutex::new(None);
+}
+
+/// Returns the answer to `prompt` taken from the running script,
+/// or `None` if no script is running.
+///
+/// If the next line of the script is not an answer, the prompt is rejected,
+/// so that the command currently being run stops.
+pub fn next_answer(prompt: &str) -> Result<Option<String>, PromptRejected> {
+    let mut guard = SCRIPT.lock().unwrap();
+    let script = match guard.as_mut() {
+        Some(script) => script,
+        None => return Ok(None),
+    };
+
+    while let Some(ScriptLine::Skip) = script.lines.get(script.pos) {
+        script.pos += 1;
+    }
+    if let Some(ScriptLine::Answer(answer)) = script.lines.get(script.pos) {
+        script.pos += 1;
+        println!("{prompt}{answer}");
+        return Ok(Some(answer.clone()));
+    }
+    drop(guard);
+    error!(
+        "The command asks for \"{}\", but no answer line follows it.",
+        prompt.trim().trim_end_matches(':')
+    );
+    Err(PromptRejected)
+}
+
+/// Returns whether the next line of the running script answers a prompt,
+/// or `None` if no script is running.
+pub fn answer_follows() -> Option<bool> {
+    let guard = SCRIPT.lock().unwrap();
+    let script = guard.as_ref()?;
+    let next = script.lines[script.pos..]
+        .iter()
+        .find(|l| **l != ScriptLine::Skip);
+    Some(matches!(next, Some(ScriptLine::Answer(_))))
+}
+
+/// Read the script from `path` or from stdin if `path` is `None` or "-".
+pub fn read_script(path: Option<&str>) -> Result<String, std::io::Error> {
+    match path {
+        None | Some("-") => {
+            let mut content = String::new();
+            std::io::stdin().read_to_string(&mut content)?;
+            Ok(content)
+        }
+        Some(path) => std::fs::read_to_string(path),
+    }
+}
+
+/// Run every command in `content` and return the exit status.
+/// Stops on the first failing line, unless `continue_on_error` is set.
+pub fn run(content: &str, name: &str, continue_on_error: bool) -> i32 {
+    *SCRIPT.lock().unwrap() = Some(Script {
+        lines: content.lines().map(ScriptLine::from).collect(),
+        pos: 0,
+    });
+    let mut failures = 0;
+
+    loop {
+        // The lock must not be held while the command runs, as its prompts read from the script.
+        let (line_number, line) = {
+            let mut guard = SCRIPT.lock().unwrap();
+            let script = guard.as_mut().unwrap();
+            let pos = script.pos;
+            script.pos += 1;
+            match script.lines.get(pos) {
+                Some(line) => (pos + 1, line.clone()),
+                None => break,
+            }
+        };
+
+        let failed = match line {
+            ScriptLine::Skip => continue,
+            ScriptLine::Answer(answer) => {
+                warning!("Answer \"{answer}\" does not belong to any prompt.");
+                true
+            }
+            // a rejected prompt is reported as an error, which marks the command as failed
+            ScriptLine::Command(command) => {
+                take_failed();
+                parse(command);
+                take_failed()
+            }
+        };
+
+        if failed {
+            failures += 1;
+            error!("{name}:{line_number}: Command failed.");
+            if !continue_on_error {
+                break;
+            }
+            // skip answers left over from the failed command
+            let mut guard = SCRIPT.lock().unwrap();
+            let script = guard.as_mut().unwrap();
+            while let Some(ScriptLine::Answer(_) | ScriptLine::Skip) = script.lines.get(script.pos)
+            {
+                script.pos += 1;
+            }
+        }
+    }
+
+    *SCRIPT.lock().unwrap() = None;
+    match failures {
+        0 => 0,
+        _ => 1,
+    }
+}
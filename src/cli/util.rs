@@ -1,25 +1,29 @@
-use std::fmt::Display;
+use std::{collections::BTreeSet, fmt::Display};
 
-use crate::{cli::repl::get_input, warning, CONFIG};
-use chrono::{DateTime, Duration, Local, Timelike};
+use crate::{
+    cal::event::Event,
+    cli::{repl::get_input, script::PromptRejected},
+    warning, CONFIG,
+};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, Timelike};
 
 pub fn uppercase_first_letter(s: &str) -> String {
     s[0..1].to_uppercase() + &s[1..]
 }
 
-pub fn select_in_range<S: Display>(prompt: S, max: usize) -> usize {
+pub fn select_in_range<S: Display>(prompt: S, max: usize) -> Result<usize, PromptRejected> {
     let displayed_range = match max {
         1 => 1.to_string(),
         _ => 1.to_string() + "-" + max.to_string().as_str(),
     };
 
     loop {
-        match get_input(format!("{} [{}]: ", prompt, displayed_range).as_str(), None)
+        match get_input(format!("{} [{}]: ", prompt, displayed_range).as_str(), None)?
             .parse::<usize>()
         {
             Ok(num) => match (1..=max).contains(&num) {
                 true => {
-                    return num;
+                    return Ok(num);
                 }
                 false => warning!("Number not in range"),
             },
@@ -93,24 +97,133 @@ pub fn get_now_even() -> DateTime<Local> {
         .unwrap()
 }
 
+/// Date formats `date_format` in the config can be set to, each with its `chrono` layout.
+pub const DATE_FORMATS: [(&str, &str); 6] = [
+    ("DD/MM/YYYY", "%d/%m/%Y"),
+    ("MM/DD/YYYY", "%m/%d/%Y"),
+    ("YYYY-MM-DD", "%Y-%m-%d"),
+    ("DD.MM.YYYY", "%d.%m.%Y"),
+    ("D.M.YYYY", "%-d.%-m.%Y"),
+    ("DD.MM.YY", "%d.%m.%y"),
+];
+
+/// Time formats `time_format` in the config can be set to, each with its `chrono` layout.
+pub const TIME_FORMATS: [(&str, &str); 2] = [("HH:MM", "%H:%M"), ("hh:mm AM/PM", "%I:%M %p")];
+
+/// `format` and its layout among `formats`, or the first one if it is not supported.
+fn format_entry(
+    formats: &[(&'static str, &'static str)],
+    format: &str,
+) -> (&'static str, &'static str) {
+    *formats
+        .iter()
+        .find(|(f, _)| *f == format)
+        .unwrap_or(&formats[0])
+}
+
+/// Layout of `format` among `formats`, or of the first one if it is not supported.
+fn layout(formats: &[(&'static str, &'static str)], format: &str) -> &'static str {
+    format_entry(formats, format).1
+}
+
+/// `chrono` layout of `date_format` from the config, such as "%d/%m/%Y".
+pub fn date_layout() -> &'static str {
+    layout(&DATE_FORMATS, &CONFIG.date_format)
+}
+
+/// `chrono` layout of `time_format` from the config, such as "%H:%M".
+pub fn time_layout() -> &'static str {
+    layout(&TIME_FORMATS, &CONFIG.time_format)
+}
+
+/// Layout of the day and month of `date_format` from the config, without the year, such as "%d/%m".
+pub fn day_month_layout() -> String {
+    date_layout()
+        .replace("%Y", "")
+        .replace("%y", "")
+        .trim_matches(['/', '-', '.'])
+        .to_string()
+}
+
+/// A date format or layout without its day, such as "MM/YYYY" for "DD/MM/YYYY".
+pub fn without_day(format: &str) -> String {
+    // the '-' in a layout such as "%-d" is a flag, not a separator
+    let separator = format
+        .replace("%-", "%")
+        .chars()
+        .find(|c| ['/', '-', '.'].contains(c))
+        .unwrap_or('/');
+    format
+        .split(separator)
+        .filter(|part| !part.ends_with(['d', 'D']))
+        .collect::<Vec<&str>>()
+        .join(&separator.to_string())
+}
+
+/// The month and year of `date_format` from the config, such as "MM/YYYY".
+pub fn month_format() -> String {
+    without_day(format_entry(&DATE_FORMATS, &CONFIG.date_format).0)
+}
+
+/// `chrono` layout of the month and year of `date_format` from the config, such as "%m/%Y".
+pub fn month_layout() -> String {
+    without_day(date_layout())
+}
+
+/// Returns `date` in `date_format` from the config.
+pub fn date_fmt(date: NaiveDate) -> String {
+    date.format(date_layout()).to_string()
+}
+
+/// Returns `time` in `time_format` from the config.
+pub fn time_fmt(time: NaiveTime) -> String {
+    time.format(time_layout()).to_string()
+}
+
+/// Returns the date and time of `t` in `date_format` and `time_format` from the config.
+pub fn datetime_fmt(t: DateTime<Local>) -> String {
+    format!("{} {}", date_fmt(t.date_naive()), time_fmt(t.time()))
+}
+
+/// Returns the time span during which `a` and `b` overlap, such as "2022-10-03 10:15 - 11:00"
+pub fn overlap_fmt(a: &Event, b: &Event) -> String {
+    let start = a.start().max(b.start());
+    let end = a.end().min(b.end());
+    let end = match start.date_naive() == end.date_naive() {
+        true => time_fmt(end.time()),
+        false => datetime_fmt(end),
+    };
+    format!("{} - {end}", datetime_fmt(start))
+}
+
+/// Returns reminder offsets the way they are entered, such as "15m, 1d".
+pub fn reminders_fmt(reminders: &BTreeSet<u32>) -> String {
+    reminders
+        .iter()
+        .map(|m| duration_fmt(Duration::minutes(*m as i64)))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Returns `duration` in days, hours, minutes and seconds, leaving out the ones that are 0,
+/// such as "1d 4h" or "1h 30m". The result for a positive duration can be typed back in
+/// as a duration. A negative duration is returned with a leading minus sign, such as "-15m".
 pub fn duration_fmt(duration: Duration) -> String {
-    if duration.num_seconds() < 60 {
-        format!("{}s", duration.num_seconds())
-    } else if duration.num_minutes() < 60 {
-        format!(
-            "{}m {}s",
-            duration.num_minutes(),
-            duration.num_seconds() - duration.num_minutes() * 60
-        )
-    } else if duration.num_hours() < 24 {
-        let num_h = duration.num_hours();
-        // remaining minutes after accounting for the whole hours (occurs further into the function as well)
-        let num_m = duration.num_minutes() - num_h * 60;
-        format!("{}h {}m", num_h, num_m)
-    } else {
-        let num_d = duration.num_days();
-        let num_h = duration.num_hours() - num_d * 24;
-        let num_m = duration.num_minutes() - num_h * 60 - num_d * 24 * 60;
-        format!("{}d {}h {}m", num_d, num_h, num_m)
+    if duration < Duration::zero() {
+        return format!("-{}", duration_fmt(-duration));
+    }
+    let parts: Vec<String> = [
+        (duration.num_days(), "d"),
+        (duration.num_hours() % 24, "h"),
+        (duration.num_minutes() % 60, "m"),
+        (duration.num_seconds() % 60, "s"),
+    ]
+    .iter()
+    .filter(|(n, _)| *n != 0)
+    .map(|(n, unit)| format!("{n}{unit}"))
+    .collect();
+    match parts.is_empty() {
+        true => "0m".to_string(),
+        false => parts.join(" "),
     }
 }
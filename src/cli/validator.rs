@@ -1,7 +1,16 @@
-use crate::{error, warning, CONFIG};
-use chrono::{LocalResult, TimeZone, Utc};
+use crate::{
+    cal::timezone::parse_timezone,
+    cli::{
+        getdata::{
+            parse_clock_time, parse_formatted_date, parse_frequency, parse_into_duration,
+            parse_relative_date,
+        },
+        util::date_layout,
+    },
+    error, warning, CONFIG,
+};
+use chrono::{Datelike, Local};
 use home::home_dir;
-use regex::Regex;
 use std::{path::PathBuf, str::FromStr};
 
 pub fn get_home_dir() -> PathBuf {
@@ -26,16 +35,6 @@ pub fn is_numeric(string: &str) -> bool {
     true
 }
 
-fn str_to_num(string: &str) -> Result<i32, core::num::ParseIntError> {
-    match string.trim().parse::<i32>() {
-        Ok(num) => Ok(num),
-        Err(err) => {
-            error!("Failed to parse {string} to i32.\n{err}");
-            Err(err)
-        }
-    }
-}
-
 pub fn validate_dir_path(path: &str) -> bool {
     if path.trim().is_empty() {
         return true;
@@ -53,52 +52,38 @@ pub fn validate_dir_path(path: &str) -> bool {
 
 /*
 Verifies time.
-Checks if time conforms to HH:MM
+Checks if time conforms to HH:MM or is one of the other inputs accepted by `parse_clock_time`
 */
 pub fn validate_time(time_string: &str) -> bool {
     if time_string.trim().is_empty() {
         return true;
     }
-
-    let re = Regex::new("^[0-9]{2}:[0-9]{2}$").unwrap();
-    if !re.is_match(time_string.trim()) {
-        warning!("Input does not conform to specified time format");
+    if parse_clock_time(time_string).is_none() {
+        warning!("Input does not conform to specified time format ({}).", CONFIG.time_format);
         return false;
     }
-    let split_input: Vec<&str> = time_string.split(':').collect();
-    let hours = str_to_num(split_input[0]).unwrap();
-    let minutes = str_to_num(split_input[1]).unwrap();
-
-    (0..=23).contains(&hours) && (0..=59).contains(&minutes)
+    true
 }
 
 /*
 Verifies date.
-Checks if the date conforms to DD/MM/YYYY (subject to change by config file)
+Checks if the date conforms to `date_format` from the config file
+or is a relative date accepted by `parse_relative_date`
 */
 pub fn validate_date(date_string: &str) -> bool {
     if date_string.trim().is_empty() {
         return true;
     }
-    let re = Regex::new(r"^[0-9]{2}/[0-9]{2}/[0-9]{4}$").unwrap();
-    if !re.is_match(date_string.trim()) {
-        warning!("Input does not conform to specified format");
-        return false;
+    if parse_relative_date(date_string, Local::now().date_naive()).is_some() {
+        return true;
     }
-
-    let split_input: Vec<&str> = date_string.split('/').collect();
-    let day = str_to_num(split_input[0]).unwrap();
-    let month = str_to_num(split_input[1]).unwrap();
-    let year = str_to_num(split_input[2]).unwrap();
-
-    if year < 1970 {
-        return false;
+    match parse_formatted_date(date_string, date_layout()) {
+        Some(date) => date.year() >= 1970,
+        None => {
+            warning!("Input does not conform to specified format ({}).", CONFIG.date_format);
+            false
+        }
     }
-
-    matches!(
-        Utc.ymd_opt(year, month.try_into().unwrap(), day.try_into().unwrap(),),
-        LocalResult::Single(_)
-    )
 }
 
 /*
@@ -114,17 +99,57 @@ Valid formats:
 10 minutes
 10 days
 10days
+2w
+1h 30m
+1d4h
+1.5h
+1:20
+PT1H30M
 */
 pub fn validate_duration(duration_string: &str) -> bool {
-    if duration_string.trim().is_empty() {
+    parse_into_duration(duration_string).is_ok()
+}
+
+/*
+Verifies a repeat input.
+Valid inputs are durations accepted by `validate_duration`
+and the words accepted by `parse_frequency`
+*/
+pub fn validate_repeat(repeat_string: &str) -> bool {
+    validate_duration(repeat_string) || parse_frequency(repeat_string).is_some()
+}
+
+/*
+Verifies a time zone.
+Valid inputs are IANA time zone names such as Europe/Warsaw, in any case,
+and an empty string or 'local' for local time
+*/
+pub fn validate_timezone(timezone_string: &str) -> bool {
+    let trimmed = timezone_string.trim();
+    trimmed.is_empty() || trimmed.eq_ignore_ascii_case("local") || parse_timezone(trimmed).is_some()
+}
+
+/*
+Verifies a URL.
+Valid inputs have a scheme such as https: or mailto: followed by an address without whitespace,
+and an empty string for no URL
+*/
+pub fn validate_url(url_string: &str) -> bool {
+    let trimmed = url_string.trim();
+    if trimmed.is_empty() {
         return true;
     }
-
-    let re = Regex::new(
-        "^[0-9]+(minutes| +minutes|min| +min|m| +m|h| +h|hours| +hours|d| +d|days| +days)$",
-    )
-    .unwrap();
-    re.is_match(duration_string.trim())
+    match trimmed.split_once(':') {
+        Some((scheme, address)) => {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+                && !address.is_empty()
+                && !trimmed.contains(char::is_whitespace)
+        }
+        None => false,
+    }
 }
 
 pub fn validate_difficulty(difficulty: &str) -> bool {
@@ -5,25 +5,39 @@ mod cal {
     pub mod calmar_error;
     pub mod calmar_trait;
     pub mod deadline;
+    pub mod details;
     pub mod event;
+    pub mod ics;
+    pub mod id;
     pub mod macros;
+    pub mod migration;
+    pub mod recurrence;
+    pub mod storage;
+    pub mod timezone;
 }
 mod cli {
+    pub mod args;
     pub mod commands;
     pub mod config;
     pub mod display;
     pub mod functions;
     pub mod getdata;
     pub mod help;
+    pub mod history;
+    pub mod hooks;
     pub mod macros;
     pub mod messages;
     pub mod parser;
+    pub mod query;
+    pub mod reminders;
     pub mod repl;
+    pub mod script;
     pub mod util;
     pub mod validator;
 }
 mod test;
 use crate::cli::{
+    args,
     config::{get_config, Config},
     functions::{check_calmar_dir, check_config},
     help::print_startup_message,
@@ -40,6 +54,11 @@ lazy_static! {
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(args::run(&args));
+    }
+
     print_startup_message();
     check_calmar_dir();
     check_config();
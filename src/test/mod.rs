@@ -1,13 +1,42 @@
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
-    use chrono::Duration;
-
+    use crate::cal::{
+        calendar::Calendar,
+        calmar_error::CalmarError,
+        calmar_trait::CalendarDataType,
+        deadline::{Deadline, DeadlineStatus},
+        details::parse_tags,
+        event::Event,
+        ics::{
+            escape_text, fold_line, from_ics, interval_rrule, parse_ics_duration, rule_rrule,
+            to_ics, to_ics_priority,
+        },
+        id::{find_by_key, new_id, short_id},
+        migration::{keep_original, load, migrate, FileKind, SCHEMA_VERSION},
+        recurrence::{Frequency, NthWeekday, Recurrence, RecurrenceRule},
+        storage::{read, write_atomic},
+    };
     use crate::cli::{
-        getdata::parse_into_duration,
+        functions::{assign_lanes, find_conflicts, find_free_slots, generate_agenda, WorkingHours},
+        getdata::{
+            parse_clock_time, parse_into_duration, parse_month, parse_month_days,
+            parse_relative_date, parse_reminders, parse_weekdays,
+        },
+        history::{FileChange, History, HistoryEntry, MAX_HISTORY_SIZE},
+        hooks::describe,
+        query::Query,
+        reminders::due_reminders,
+        script::ScriptLine,
+        util::{duration_fmt, reminders_fmt},
         validator::{
-            validate_date, validate_difficulty, validate_duration, validate_priority, validate_time,
+            validate_date, validate_difficulty, validate_duration, validate_priority,
+            validate_time, validate_timezone, validate_url,
         },
     };
+    use chrono::Duration;
+    use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Weekday};
+    use std::collections::BTreeSet;
 
     // DATE TESTS
     #[test]
@@ -113,6 +142,9 @@ mod tests {
         assert_eq!(false, validate_duration("-123min"));
         assert_eq!(false, validate_duration("-123 h"));
         assert_eq!(false, validate_duration("-123h"));
+        assert_eq!(true, validate_duration("1h 30m"));
+        assert_eq!(true, validate_duration("PT1H30M"));
+        assert_eq!(false, validate_duration("1h30"));
     }
 
     // DIFFICULTY TESTS
@@ -141,13 +173,1248 @@ mod tests {
     }
     #[test]
     fn parse_duration() {
-        assert_eq!(Duration::minutes(15), parse_into_duration("15m"));
-        assert_eq!(Duration::minutes(15), parse_into_duration("15min"));
-        assert_eq!(Duration::minutes(15), parse_into_duration("15 m"));
-        assert_eq!(Duration::minutes(15), parse_into_duration("15 min"));
-        assert_eq!(Duration::hours(4), parse_into_duration("4h"));
-        assert_eq!(Duration::hours(4), parse_into_duration("4 h"));
-        assert_eq!(Duration::days(2), parse_into_duration("2d"));
-        assert_eq!(Duration::days(2), parse_into_duration("2 d"));
+        assert_eq!(Ok(Duration::minutes(15)), parse_into_duration("15m"));
+        assert_eq!(Ok(Duration::minutes(15)), parse_into_duration("15min"));
+        assert_eq!(Ok(Duration::minutes(15)), parse_into_duration("15 m"));
+        assert_eq!(Ok(Duration::minutes(15)), parse_into_duration("15 min"));
+        assert_eq!(Ok(Duration::hours(4)), parse_into_duration("4h"));
+        assert_eq!(Ok(Duration::hours(4)), parse_into_duration("4 h"));
+        assert_eq!(Ok(Duration::days(2)), parse_into_duration("2d"));
+        assert_eq!(Ok(Duration::days(2)), parse_into_duration("2 d"));
+        assert_eq!(Ok(Duration::zero()), parse_into_duration(""));
+    }
+    #[test]
+    fn parse_compound_duration() {
+        let ok = |h, m| Ok(Duration::hours(h) + Duration::minutes(m));
+        assert_eq!(ok(1, 30), parse_into_duration("1h30m"));
+        assert_eq!(ok(1, 30), parse_into_duration("1h 30m"));
+        assert_eq!(ok(1, 30), parse_into_duration("1 hour 30 minutes"));
+        assert_eq!(ok(1, 30), parse_into_duration("30m 1h"));
+        assert_eq!(ok(28, 0), parse_into_duration("1d 4h"));
+        assert_eq!(ok(1, 20), parse_into_duration("1:20"));
+        assert_eq!(ok(1, 20), parse_into_duration("1:20h"));
+        assert_eq!(ok(336, 0), parse_into_duration("2w"));
+        assert_eq!(ok(1, 30), parse_into_duration("1.5h"));
+        assert_eq!(ok(0, 45), parse_into_duration(".75h"));
+        assert_eq!(ok(1, 30), parse_into_duration("PT1H30M"));
+        assert_eq!(ok(1, 30), parse_into_duration("pt1h30m"));
+        assert_eq!(ok(7 * 24 + 24 + 12, 0), parse_into_duration("P1W1DT12H"));
+        assert_eq!(Ok(Duration::seconds(45)), parse_into_duration("45s"));
+
+        for invalid in [
+            "1h30",
+            "h",
+            "1x",
+            "1h 2h",
+            "1.2.3h",
+            "-1h",
+            "1:5",
+            "1:60",
+            ":30",
+            "P",
+            "PT",
+            "P1M",
+            "P1H",
+            "1000000000000d",
+            "1h, 30m",
+        ] {
+            assert!(parse_into_duration(invalid).is_err(), "{invalid} was accepted");
+        }
+        assert_eq!(
+            Err("A unit is missing after 30, such as 30m or 30h".to_string()),
+            parse_into_duration("1h30")
+        );
+
+        // anything displayed can be typed back in
+        for duration in [
+            Duration::zero(),
+            Duration::seconds(45),
+            Duration::seconds(125),
+            Duration::minutes(90),
+            Duration::days(7),
+            Duration::days(1) + Duration::hours(4),
+            Duration::days(400) + Duration::minutes(1),
+        ] {
+            assert_eq!(Ok(duration), parse_into_duration(&duration_fmt(duration)));
+        }
+        assert_eq!("1h 30m", duration_fmt(Duration::minutes(90)));
+        assert_eq!("7d", duration_fmt(Duration::days(7)));
+        assert_eq!("0m", duration_fmt(Duration::zero()));
+        assert_eq!("-15m", duration_fmt(-Duration::minutes(15)));
+    }
+
+    // SCRIPT TESTS
+    #[test]
+    fn script_line_kinds() {
+        assert_eq!(ScriptLine::Skip, ScriptLine::from(""));
+        assert_eq!(ScriptLine::Skip, ScriptLine::from("   "));
+        assert_eq!(ScriptLine::Skip, ScriptLine::from("# add foo"));
+        assert_eq!(ScriptLine::Answer("".to_string()), ScriptLine::from(">"));
+        assert_eq!(ScriptLine::Answer("10:00".to_string()), ScriptLine::from("  > 10:00 "));
+        assert_eq!(
+            ScriptLine::Command("add \"Linear Algebra\"".to_string()),
+            ScriptLine::from("add \"Linear Algebra\"")
+        );
+    }
+
+    // ICS TESTS
+    #[test]
+    fn ics_text_escaping() {
+        assert_eq!("a\\, b\\; c\\\\d\\ne", escape_text("a, b; c\\d\ne"));
+    }
+    #[test]
+    fn ics_line_folding() {
+        assert_eq!("BEGIN:VEVENT\r\n", fold_line("BEGIN:VEVENT"));
+        let folded = fold_line(&format!("SUMMARY:{}", "ą".repeat(60)));
+        for line in folded.split("\r\n") {
+            assert!(line.len() <= 75);
+        }
+        assert_eq!(format!("SUMMARY:{}", "ą".repeat(60)), folded.replace("\r\n ", "").trim_end());
+    }
+    #[test]
+    fn ics_rrule_from_interval() {
+        assert_eq!("FREQ=WEEKLY;INTERVAL=2", interval_rrule(Duration::weeks(2)));
+        assert_eq!("FREQ=DAILY;INTERVAL=3", interval_rrule(Duration::days(3)));
+        assert_eq!("FREQ=HOURLY;INTERVAL=36", interval_rrule(Duration::hours(36)));
+        assert_eq!("FREQ=MINUTELY;INTERVAL=90", interval_rrule(Duration::minutes(90)));
+    }
+    #[test]
+    fn ics_priority_mapping() {
+        assert_eq!(0, to_ics_priority(0));
+        assert_eq!(9, to_ics_priority(1));
+        assert_eq!(5, to_ics_priority(5));
+        assert_eq!(1, to_ics_priority(9));
+        assert_eq!(1, to_ics_priority(10));
+    }
+    #[test]
+    fn ics_duration() {
+        assert_eq!(Some(Duration::minutes(90)), parse_ics_duration("PT1H30M"));
+        assert_eq!(Some(Duration::days(1)), parse_ics_duration("P1D"));
+        assert_eq!(Some(Duration::weeks(2)), parse_ics_duration("P2W"));
+        assert_eq!(Some(-Duration::minutes(15)), parse_ics_duration("-PT15M"));
+        assert_eq!(None, parse_ics_duration("PT"));
+        assert_eq!(None, parse_ics_duration("1H"));
+        assert_eq!(None, parse_ics_duration("P1H"));
+    }
+    #[test]
+    fn ics_round_trip() {
+        let start = Local.ymd(2022, 10, 3).and_hms(10, 15, 0);
+        let mut calendar = Calendar::new("uni");
+        calendar.add_event(Event::new(
+            "Algebra; lecture, room 1".to_string(),
+            start,
+            start + Duration::minutes(90),
+            Duration::weeks(1).into(),
+            7,
+            4,
+            vec![start + Duration::weeks(2)],
+        ));
+        calendar.add_deadline(Deadline::new("Essay".to_string(), start + Duration::days(30), 10));
+
+        let import = from_ics(&to_ics(&calendar)).unwrap();
+        assert!(import.problems.is_empty());
+        assert_eq!(1, import.events.len());
+        let event = &import.events[0];
+        assert_eq!(calendar.events()[0].name(), event.name());
+        assert_eq!(calendar.events()[0].start(), event.start());
+        assert_eq!(calendar.events()[0].end(), event.end());
+        assert_eq!(calendar.events()[0].repeat(), event.repeat());
+        assert_eq!(calendar.events()[0].exceptions(), event.exceptions());
+        assert_eq!(7, event.priority());
+        assert_eq!(4, event.difficulty());
+        assert_eq!(1, import.deadlines.len());
+        assert_eq!(calendar.deadlines()[0].date(), import.deadlines[0].date());
+        assert_eq!(10, import.deadlines[0].priority());
+    }
+    #[test]
+    fn ics_details_round_trip() {
+        let start = Local.ymd(2022, 10, 3).and_hms(10, 15, 0);
+        let mut calendar = Calendar::new("uni");
+        let mut event = Event::new(
+            "Algebra".to_string(),
+            start,
+            start + Duration::minutes(90),
+            Recurrence::none(),
+            7,
+            4,
+            vec![],
+        );
+        let details = event.details_mut();
+        details.set_description("Chapter 3;\nbring notes, a pen");
+        details.set_location("Room 1, building B");
+        details.set_url("https://example.com/algebra?week=1,2");
+        details.set_tags(BTreeSet::from(["math".to_string(), "exam, oral\\".to_string()]));
+        details.set_reminders(BTreeSet::from([15]));
+        calendar.add_event(event);
+        let mut deadline = Deadline::new("Essay".to_string(), start + Duration::days(30), 10);
+        deadline.details_mut().set_tags(parse_tags("writing"));
+        calendar.add_deadline(deadline);
+
+        let import = from_ics(&to_ics(&calendar)).unwrap();
+        assert!(import.problems.is_empty());
+        let (original, imported) = (calendar.events()[0].details(), import.events[0].details());
+        assert_eq!(original.description(), imported.description());
+        assert_eq!(original.location(), imported.location());
+        assert_eq!(original.url(), imported.url());
+        assert_eq!(original.tags(), imported.tags());
+        assert_eq!(calendar.deadlines()[0].details().tags(), import.deadlines[0].details().tags());
+    }
+    #[test]
+    fn ics_reimport_keeps_local_state() {
+        let start = Local.ymd(2022, 10, 3).and_hms(10, 15, 0);
+        let mut event = Event::new(
+            "Algebra".to_string(),
+            start,
+            start + Duration::minutes(90),
+            Recurrence::none(),
+            7,
+            4,
+            vec![],
+        );
+        event.details_mut().set_reminders(BTreeSet::from([15]));
+        event.details_mut().set_description("Chapter 3");
+        let mut deadline = Deadline::new("Essay".to_string(), start + Duration::days(30), 10);
+        deadline.postpone(Duration::days(2));
+        deadline.set_progress(Some(40));
+        let (event_id, deadline_id) = (event.id().to_string(), deadline.id().to_string());
+
+        let mut imported_event = event.clone();
+        imported_event.set_name(&"Linear algebra".to_string());
+        *imported_event.details_mut() = Default::default();
+        imported_event.details_mut().set_location("Room 1");
+        let imported_deadline =
+            Deadline::new("Final essay".to_string(), start + Duration::days(40), 9);
+        event.update_from(imported_event);
+        deadline.update_from(imported_deadline.clone());
+
+        assert_eq!(event_id, event.id());
+        assert_eq!("Linear algebra", event.name());
+        assert_eq!(&BTreeSet::from([15]), event.details().reminders());
+        assert_eq!(Some("Chapter 3"), event.details().description());
+        assert_eq!(Some("Room 1"), event.details().location());
+        assert_eq!(deadline_id, deadline.id());
+        assert_eq!("Final essay", deadline.name());
+        assert_eq!(imported_deadline.date(), deadline.date());
+        assert_eq!(DeadlineStatus::InProgress, deadline.status());
+        assert_eq!(Some(40), deadline.progress());
+        assert_eq!(1, deadline.postponements());
+
+        let mut finished = imported_deadline;
+        finished.finish(DeadlineStatus::Done, start);
+        deadline.update_from(finished);
+        assert_eq!(DeadlineStatus::Done, deadline.status());
+        assert_eq!(Some(start), deadline.completed());
+    }
+
+    // RECURRENCE TESTS
+    fn dates(recurrence: &Recurrence, start: DateTime<Local>, n: usize) -> Vec<String> {
+        recurrence
+            .occurrences(start, None)
+            .take(n)
+            .map(|d| d.format("%d/%m/%Y %H:%M").to_string())
+            .collect()
+    }
+    #[test]
+    fn recurrence_interval() {
+        let start = Local.ymd(2023, 1, 2).and_hms(10, 0, 0);
+        assert_eq!(
+            vec!["02/01/2023 10:00", "09/01/2023 10:00"],
+            dates(&Duration::weeks(1).into(), start, 2)
+        );
+        assert_eq!(vec!["02/01/2023 10:00"], dates(&Recurrence::none(), start, 5));
+    }
+    #[test]
+    fn recurrence_weekly_on_weekdays() {
+        let mut rule = RecurrenceRule::new(Frequency::Weekly, 2);
+        rule.by_weekday = vec![Weekday::Wed, Weekday::Mon];
+        assert_eq!(
+            vec![
+                "02/01/2023 09:00",
+                "04/01/2023 09:00",
+                "16/01/2023 09:00",
+                "18/01/2023 09:00"
+            ],
+            dates(&Recurrence::Rule(rule), Local.ymd(2023, 1, 2).and_hms(9, 0, 0), 4)
+        );
+    }
+    #[test]
+    fn recurrence_every_weekday() {
+        let mut rule = RecurrenceRule::new(Frequency::Daily, 1);
+        rule.by_weekday = vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ];
+        assert_eq!("every weekday", rule.to_string());
+        assert_eq!(
+            vec!["06/01/2023 08:00", "09/01/2023 08:00"],
+            dates(&Recurrence::Rule(rule), Local.ymd(2023, 1, 6).and_hms(8, 0, 0), 2)
+        );
+    }
+    #[test]
+    fn recurrence_month_end() {
+        let start = Local.ymd(2023, 1, 31).and_hms(12, 0, 0);
+        let mut rule = RecurrenceRule::new(Frequency::Monthly, 1);
+        rule.by_month_day = vec![31];
+        assert_eq!(
+            vec!["31/01/2023 12:00", "31/03/2023 12:00", "31/05/2023 12:00"],
+            dates(&Recurrence::Rule(rule.clone()), start, 3)
+        );
+        rule.clamp_month_end = true;
+        assert_eq!(
+            vec![
+                "31/01/2023 12:00",
+                "28/02/2023 12:00",
+                "31/03/2023 12:00",
+                "30/04/2023 12:00"
+            ],
+            dates(&Recurrence::Rule(rule), start, 4)
+        );
+    }
+    #[test]
+    fn recurrence_nth_weekday() {
+        let mut rule = RecurrenceRule::new(Frequency::Monthly, 1);
+        rule.by_nth_weekday = vec![
+            NthWeekday {
+                n: 1,
+                weekday: Weekday::Mon,
+            },
+            NthWeekday {
+                n: -1,
+                weekday: Weekday::Fri,
+            },
+        ];
+        assert_eq!("every month on 1st Mon, last Fri", rule.to_string());
+        assert_eq!(
+            vec![
+                "02/01/2023 18:00",
+                "27/01/2023 18:00",
+                "06/02/2023 18:00",
+                "24/02/2023 18:00"
+            ],
+            dates(&Recurrence::Rule(rule), Local.ymd(2023, 1, 2).and_hms(18, 0, 0), 4)
+        );
+    }
+    #[test]
+    fn recurrence_yearly() {
+        let mut rule = RecurrenceRule::new(Frequency::Yearly, 1);
+        rule.by_month_day = vec![29];
+        assert_eq!(
+            vec!["29/02/2024 00:00", "29/02/2028 00:00"],
+            dates(&Recurrence::Rule(rule), Local.ymd(2024, 2, 29).and_hms(0, 0, 0), 2)
+        );
+    }
+    #[test]
+    fn recurrence_serialization() {
+        let legacy: Recurrence = serde_json::from_str("604800").unwrap();
+        assert_eq!(Recurrence::Interval(Duration::weeks(1)), legacy);
+        assert_eq!("604800", serde_json::to_string(&legacy).unwrap());
+
+        let mut rule = RecurrenceRule::new(Frequency::Monthly, 2);
+        rule.by_nth_weekday = vec![NthWeekday {
+            n: -1,
+            weekday: Weekday::Fri,
+        }];
+        let rule = Recurrence::Rule(rule);
+        let json = serde_json::to_string(&rule).unwrap();
+        assert_eq!(rule, serde_json::from_str(&json).unwrap());
+    }
+    #[test]
+    fn month_argument() {
+        assert_eq!(Some((2026, 10)), parse_month("10/2026"));
+        assert_eq!(Some((2027, 1)), parse_month("1/2027"));
+        assert_eq!(None, parse_month("13/2026"));
+        assert_eq!(None, parse_month("0/2026"));
+        assert_eq!(None, parse_month("10/1969"));
+        assert_eq!(None, parse_month("10-2026"));
+    }
+    #[test]
+    fn repeat_day_lists() {
+        assert_eq!(Some(vec![Weekday::Mon, Weekday::Wed]), parse_weekdays("mon, Wednesday"));
+        assert_eq!(None, parse_weekdays("mon, someday"));
+        assert_eq!(
+            Some((
+                vec![15, -1],
+                vec![NthWeekday {
+                    n: -1,
+                    weekday: Weekday::Fri
+                }]
+            )),
+            parse_month_days("15, -1 -1fri")
+        );
+        assert_eq!(None, parse_month_days("0"));
+        assert_eq!(None, parse_month_days("32"));
+        assert_eq!(None, parse_month_days("6mon"));
+    }
+    #[test]
+    fn ics_rrule_from_rule() {
+        let start = Local.ymd(2023, 1, 31).and_hms(12, 0, 0);
+        let mut rule = RecurrenceRule::new(Frequency::Monthly, 1);
+        rule.by_month_day = vec![31];
+        rule.clamp_month_end = true;
+        assert_eq!(
+            Some("FREQ=MONTHLY;INTERVAL=1;BYMONTHDAY=28,29,30,31;BYSETPOS=-1".to_string()),
+            rule_rrule(&rule, start)
+        );
+        rule.by_nth_weekday = vec![NthWeekday {
+            n: 1,
+            weekday: Weekday::Mon,
+        }];
+        assert_eq!(None, rule_rrule(&rule, start));
+    }
+    #[test]
+    fn ics_rule_round_trip() {
+        let start = Local.ymd(2023, 1, 31).and_hms(12, 0, 0);
+        let mut calendar = Calendar::new("uni");
+        for (day, clamp) in [(vec![31], true), (vec![-1, 15], false)] {
+            let mut rule = RecurrenceRule::new(Frequency::Monthly, 1);
+            rule.by_month_day = day;
+            rule.clamp_month_end = clamp;
+            calendar.add_event(Event::new(
+                "Rent".to_string(),
+                start,
+                start + Duration::hours(1),
+                Recurrence::Rule(rule),
+                5,
+                0,
+                vec![Local.ymd(2023, 3, 31).and_hms(12, 0, 0)],
+            ));
+        }
+        let import = from_ics(&to_ics(&calendar)).unwrap();
+        assert!(import.problems.is_empty());
+        for (original, imported) in calendar.events().iter().zip(import.events.iter()) {
+            assert_eq!(original.repeat(), imported.repeat());
+            assert_eq!(original.start(), imported.start());
+            assert_eq!(original.exceptions(), imported.exceptions());
+        }
+    }
+    #[test]
+    fn ics_non_ascii_byday() {
+        let start = Local.ymd(2023, 1, 2).and_hms(12, 0, 0);
+        let mut rule = RecurrenceRule::new(Frequency::Monthly, 1);
+        rule.by_nth_weekday = vec![NthWeekday {
+            n: 1,
+            weekday: Weekday::Mon,
+        }];
+        let mut calendar = Calendar::new("uni");
+        calendar.add_event(Event::new(
+            "Seminar".to_string(),
+            start,
+            start + Duration::hours(1),
+            Recurrence::Rule(rule),
+            5,
+            0,
+            vec![],
+        ));
+        let ics = to_ics(&calendar);
+        assert!(ics.contains("BYDAY=1MO"));
+        for byday in ["1M€", "1€", "€", "1MÖ", "2ÅÄÖ"] {
+            let import = from_ics(&ics.replace("BYDAY=1MO", &format!("BYDAY={byday}"))).unwrap();
+            assert!(import.events.is_empty());
+            assert_eq!(1, import.problems.len());
+        }
+    }
+    #[test]
+    fn ics_interval_too_long() {
+        let start = Local.ymd(2023, 1, 2).and_hms(12, 0, 0);
+        let mut calendar = Calendar::new("uni");
+        calendar.add_event(Event::new(
+            "Check".to_string(),
+            start,
+            start + Duration::minutes(5),
+            Duration::hours(1).into(),
+            5,
+            0,
+            vec![],
+        ));
+        let ics = to_ics(&calendar);
+        assert!(ics.contains("RRULE:FREQ=HOURLY;INTERVAL=1"));
+        for rrule in [
+            "FREQ=HOURLY;INTERVAL=4294967295",
+            "FREQ=DAILY;INTERVAL=100000000",
+            "FREQ=YEARLY;INTERVAL=101",
+        ] {
+            let import = from_ics(&ics.replace("FREQ=HOURLY;INTERVAL=1", rrule)).unwrap();
+            assert!(import.events.is_empty());
+            assert_eq!(1, import.problems.len());
+        }
+        let import = from_ics(&ics.replace("INTERVAL=1", "INTERVAL=876000")).unwrap();
+        assert_eq!(&Recurrence::Interval(Duration::hours(876000)), import.events[0].repeat());
+    }
+    #[test]
+    fn ics_rdate_rule_round_trip() {
+        // the first day and the last Friday of every month, which an RRULE cannot express
+        let start = Local.ymd(2023, 1, 1).and_hms(9, 0, 0);
+        let mut rule = RecurrenceRule::new(Frequency::Monthly, 1);
+        rule.by_month_day = vec![1];
+        rule.by_nth_weekday = vec![NthWeekday {
+            n: -1,
+            weekday: Weekday::Fri,
+        }];
+        let mut event = Event::new(
+            "Report".to_string(),
+            start,
+            start + Duration::hours(1),
+            Recurrence::Rule(rule),
+            5,
+            2,
+            vec![Local.ymd(2023, 2, 1).and_hms(9, 0, 0)],
+        );
+        event.set_repeat_count(Some(6));
+        let mut calendar = Calendar::new("work");
+        calendar.add_event(event);
+
+        let ics = to_ics(&calendar);
+        assert!(ics.contains("RDATE:"));
+        assert!(!ics.contains("RRULE:"));
+        let import = from_ics(&ics).unwrap();
+        assert!(import.problems.is_empty());
+        let (original, imported) = (&calendar.events()[0], &import.events[0]);
+        assert_eq!(original.repeat(), imported.repeat());
+        assert_eq!(Some(6), imported.repeat_count());
+        assert_eq!(original.exceptions(), imported.exceptions());
+        assert_eq!(
+            original.occurrences().collect::<Vec<_>>(),
+            imported.occurrences().collect::<Vec<_>>()
+        );
+    }
+    #[test]
+    fn occurrences_end_with_the_range_of_dates() {
+        let start = Local.ymd(2023, 1, 2).and_hms(10, 0, 0);
+        let weeks = Recurrence::Interval(Duration::weeks(1_000_000));
+        assert!((2..20).contains(&weeks.occurrences(start, None).count()));
+        let backwards = Recurrence::Interval(Duration::hours(-1));
+        assert_eq!(1, backwards.occurrences(start, None).count());
+        for frequency in [Frequency::Daily, Frequency::Monthly, Frequency::Yearly] {
+            let rule = Recurrence::Rule(RecurrenceRule::new(frequency, u32::MAX));
+            assert!(rule.occurrences(start, None).count() < 20);
+        }
+    }
+    #[test]
+    fn bounded_recurrence() {
+        let start = Local.ymd(2023, 1, 2).and_hms(10, 0, 0);
+        let mut event = Event::new(
+            "Lecture".to_string(),
+            start,
+            start + Duration::minutes(90),
+            Duration::weeks(1).into(),
+            5,
+            5,
+            vec![],
+        );
+        assert_eq!(None, event.last_occurrence());
+        event.set_repeat_count(Some(3));
+        assert_eq!(3, event.occurrences().count());
+        assert_eq!(Some(start + Duration::weeks(2)), event.last_occurrence());
+        event.set_repeat_until(Some(chrono::NaiveDate::from_ymd(2023, 1, 9)));
+        assert_eq!(Some(start + Duration::weeks(1)), event.last_occurrence());
+        event.set_repeat_count(None);
+        assert_eq!(2, event.occurrences().count());
+    }
+    #[test]
+    fn ics_bounded_round_trip() {
+        let start = Local.ymd(2023, 1, 2).and_hms(10, 0, 0);
+        let mut calendar = Calendar::new("uni");
+        for (until, count) in [
+            (None, Some(12)),
+            (Some(chrono::NaiveDate::from_ymd(2023, 2, 6)), None),
+        ] {
+            let mut event = Event::new(
+                "Lecture".to_string(),
+                start,
+                start + Duration::minutes(90),
+                Duration::weeks(1).into(),
+                5,
+                5,
+                vec![],
+            );
+            event.set_repeat_until(until);
+            event.set_repeat_count(count);
+            calendar.add_event(event);
+        }
+        let import = from_ics(&to_ics(&calendar)).unwrap();
+        assert!(import.problems.is_empty());
+        for (original, imported) in calendar.events().iter().zip(import.events.iter()) {
+            assert_eq!(original.repeat_until(), imported.repeat_until());
+            assert_eq!(original.repeat_count(), imported.repeat_count());
+        }
+    }
+
+    // TIME ZONE TESTS
+    #[test]
+    fn timezone_names() {
+        assert_eq!(true, validate_timezone("Europe/Warsaw"));
+        assert_eq!(true, validate_timezone("america/new_york"));
+        assert_eq!(true, validate_timezone(""));
+        assert_eq!(true, validate_timezone("local"));
+        assert_eq!(false, validate_timezone("Europe/Atlantis"));
+    }
+    #[test]
+    fn recurrence_across_daylight_saving_change() {
+        let tz = chrono_tz::Europe::Warsaw;
+        // the clocks in Warsaw moved forward on 26/03/2023
+        let start = tz.ymd(2023, 3, 20).and_hms(10, 0, 0).with_timezone(&Local);
+        for recurrence in [
+            Duration::weeks(1).into(),
+            Recurrence::Rule(RecurrenceRule::new(Frequency::Weekly, 1)),
+        ] {
+            let times: Vec<String> = recurrence
+                .occurrences(start, Some(tz))
+                .take(3)
+                .map(|t| t.with_timezone(&tz).format("%d/%m %H:%M").to_string())
+                .collect();
+            assert_eq!(vec!["20/03 10:00", "27/03 10:00", "03/04 10:00"], times);
+        }
+        // intervals shorter than a day are elapsed time
+        let hourly: Recurrence = Duration::hours(12).into();
+        let second = hourly.occurrences(start, Some(tz)).nth(14).unwrap();
+        assert_eq!(Duration::hours(168), second - start);
+    }
+    #[test]
+    fn event_time_zone_storage() {
+        let json = |timezone: &str| {
+            format!(
+                r#"{{"name":"Call","start":"2023-01-02T10:00:00+05:00","end":"2023-01-02T11:00:00+05:00","repeat":0,"priority":1,"difficulty":1,"exceptions":[]{timezone}}}"#
+            )
+        };
+        // floating events keep their wall clock time
+        let floating: Event = serde_json::from_str(&json("")).unwrap();
+        assert_eq!(None, floating.timezone());
+        assert_eq!("10:00", floating.start().format("%H:%M").to_string());
+        // events in a time zone keep their moment
+        let zoned: Event = serde_json::from_str(&json(r#","timezone":"Asia/Karachi""#)).unwrap();
+        assert_eq!(Some(chrono_tz::Asia::Karachi), zoned.timezone());
+        assert_eq!(
+            chrono::DateTime::parse_from_rfc3339("2023-01-02T10:00:00+05:00").unwrap(),
+            zoned.start()
+        );
+        let reloaded: Event =
+            serde_json::from_str(&serde_json::to_string(&zoned).unwrap()).unwrap();
+        assert_eq!(zoned, reloaded);
+    }
+    #[test]
+    fn ics_time_zone_round_trip() {
+        let tz = chrono_tz::America::New_York;
+        let start = tz.ymd(2023, 3, 6).and_hms(9, 30, 0).with_timezone(&Local);
+        let mut event = Event::new(
+            "Standup".to_string(),
+            start,
+            start + Duration::minutes(15),
+            Duration::days(1).into(),
+            3,
+            1,
+            vec![start + Duration::days(7)],
+        );
+        event.set_timezone(Some(tz));
+        let mut calendar = Calendar::new("work");
+        calendar.add_event(event);
+
+        let ics = to_ics(&calendar);
+        assert!(ics.contains("DTSTART;TZID=America/New_York:20230306T093000"));
+        let import = from_ics(&ics).unwrap();
+        assert!(import.problems.is_empty());
+        let imported = &import.events[0];
+        assert_eq!(Some(tz), imported.timezone());
+        assert_eq!(start, imported.start());
+        assert_eq!(calendar.events()[0].exceptions(), imported.exceptions());
+    }
+    #[test]
+    fn ics_time_zones_are_defined() {
+        let event = |name: &str, tz: chrono_tz::Tz| {
+            let start = tz.ymd(2023, 3, 6).and_hms(9, 30, 0).with_timezone(&Local);
+            let mut event = Event::new(
+                name.to_string(),
+                start,
+                start + Duration::hours(1),
+                Duration::weeks(1).into(),
+                3,
+                1,
+                vec![],
+            );
+            event.set_timezone(Some(tz));
+            event
+        };
+        let mut calendar = Calendar::new("work");
+        calendar.add_event(event("Standup", chrono_tz::America::New_York));
+        calendar.add_event(event("Review", chrono_tz::Asia::Kolkata));
+        calendar.add_event(event("Retro", chrono_tz::America::New_York));
+
+        let ics = to_ics(&calendar).replace("\r\n ", "");
+        let lines: Vec<&str> = ics.lines().collect();
+        let defined: Vec<&str> = lines
+            .iter()
+            .filter_map(|l| l.strip_prefix("TZID:"))
+            .collect();
+        assert_eq!(vec!["America/New_York", "Asia/Kolkata"], defined);
+        for line in &lines {
+            if let Some((_, rest)) = line.split_once(";TZID=") {
+                let tzid = rest.split([';', ':']).next().unwrap();
+                assert!(defined.contains(&tzid), "no VTIMEZONE for {tzid}");
+            }
+        }
+        // the switch to daylight saving time on the second Sunday of March 2023
+        assert!(ics.contains(
+            "BEGIN:DAYLIGHT\r\nDTSTART:20230312T020000\r\nTZOFFSETFROM:-0500\r\nTZOFFSETTO:-0400\r\nTZNAME:EDT\r\nEND:DAYLIGHT"
+        ));
+        assert!(ics.contains("TZOFFSETFROM:+0530\r\nTZOFFSETTO:+0530"));
+        assert!(from_ics(&ics).unwrap().problems.is_empty());
+    }
+    #[test]
+    fn overlapping_events() {
+        let at = |h, m| Local.ymd(2023, 1, 2).and_hms(h, m, 0);
+        let event = |name: &str, start, end| {
+            Event::new(name.to_string(), start, end, Recurrence::none(), 5, 5, vec![])
+        };
+        let events = vec![
+            event("Lecture", at(10, 0), at(11, 30)),
+            event("Lab", at(11, 0), at(12, 0)),
+            event("Lunch", at(12, 0), at(13, 0)),
+            event("Call", at(12, 30), at(12, 45)),
+            event("Gym", at(14, 0), at(15, 0)),
+        ];
+        let names: Vec<(String, String)> = find_conflicts(&events)
+            .iter()
+            .map(|(a, b)| (a.name(), b.name()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("Lecture".to_string(), "Lab".to_string()),
+                ("Lunch".to_string(), "Call".to_string())
+            ],
+            names
+        );
+    }
+    #[test]
+    fn free_slots() {
+        let at = |d, h, m| Local.ymd(2023, 1, d).and_hms(h, m, 0);
+        let event = |start, end| {
+            Event::new("Busy".to_string(), start, end, Recurrence::none(), 5, 5, vec![])
+        };
+        let events = vec![
+            event(at(2, 10, 0), at(2, 11, 30)),
+            event(at(2, 13, 0), at(2, 14, 0)),
+            event(at(3, 7, 0), at(3, 9, 0)),
+        ];
+        let hours = WorkingHours {
+            start: NaiveTime::from_hms(8, 0, 0),
+            end: NaiveTime::from_hms(18, 0, 0),
+            days: vec![Weekday::Mon, Weekday::Tue],
+            buffer_before: Duration::zero(),
+            buffer_after: Duration::minutes(30),
+        };
+        // the weekend is skipped, 11:30 - 13:00 is too short once the buffer is kept free
+        let slots =
+            find_free_slots(&events, at(1, 12, 0), at(3, 12, 0), Duration::minutes(90), &hours);
+        assert_eq!(
+            vec![
+                (at(2, 8, 0), at(2, 10, 0)),
+                (at(2, 14, 30), at(2, 18, 0)),
+                (at(3, 9, 30), at(3, 12, 0)),
+            ],
+            slots
+        );
+        let slots = find_free_slots(&events, at(2, 9, 0), at(2, 18, 0), Duration::hours(4), &hours);
+        assert!(slots.is_empty());
+    }
+    #[test]
+    fn merged_agenda() {
+        let start = Local::now() + Duration::hours(1);
+        let mut uni = Calendar::new("uni");
+        uni.add_event(Event::new(
+            "Lecture".to_string(),
+            start + Duration::hours(2),
+            start + Duration::hours(3),
+            Recurrence::none(),
+            5,
+            5,
+            vec![],
+        ));
+        let mut work = Calendar::new("work");
+        work.add_event(Event::new(
+            "Standup".to_string(),
+            start,
+            start + Duration::minutes(15),
+            Duration::days(1).into(),
+            5,
+            5,
+            vec![],
+        ));
+        let calendars = vec![("uni".to_string(), uni), ("work".to_string(), work)];
+        let agenda: Vec<(String, String)> = generate_agenda(
+            &calendars,
+            Local::now(),
+            start + Duration::days(1) + Duration::hours(1),
+        )
+        .into_iter()
+        .map(|(calendar, e)| (calendar, e.name()))
+        .collect();
+        assert_eq!(
+            vec![
+                ("work".to_string(), "Standup".to_string()),
+                ("uni".to_string(), "Lecture".to_string()),
+                ("work".to_string(), "Standup".to_string()),
+            ],
+            agenda
+        );
+    }
+    #[test]
+    fn timetable_lanes() {
+        // 9-11 and 10-12 overlap, 11-13 fits under the first one, 14-15 is on its own
+        let spans = [(9, 11), (10, 12), (11, 13), (14, 15)];
+        assert_eq!(vec![(0, 2), (1, 2), (0, 2), (0, 1)], assign_lanes(&spans));
+        assert_eq!(vec![(0, 3), (1, 3), (2, 3)], assign_lanes(&[(8, 9), (8, 10), (8, 9)]));
+        assert!(assign_lanes(&[]).is_empty());
+    }
+    #[test]
+    fn bounded_history() {
+        let entry = |command: &str| HistoryEntry {
+            command: command.to_string(),
+            time: Local::now(),
+            changes: vec![],
+        };
+        let mut history = History::default();
+        for command in ["add a", "add b", "add c"] {
+            history.push(entry(command), 2);
+        }
+        let commands: Vec<String> = history.undo.iter().map(|e| e.command.clone()).collect();
+        assert_eq!(vec!["add b", "add c"], commands);
+
+        // a new change after an undo cannot be followed by a redo
+        history.redo.push(history.undo.pop().unwrap());
+        history.push(entry("remove a"), 2);
+        assert!(history.redo.is_empty());
+        assert_eq!(2, history.undo.len());
+
+        // large changes push older ones out, but the most recent one is kept
+        let large = |command: &str| HistoryEntry {
+            changes: vec![FileChange::new(
+                "uni.json".to_string(),
+                None,
+                Some(&"x".repeat(MAX_HISTORY_SIZE / 2)),
+            )],
+            ..entry(command)
+        };
+        history.push(large("import a"), 10);
+        history.push(large("import b"), 10);
+        let commands: Vec<String> = history.undo.iter().map(|e| e.command.clone()).collect();
+        assert_eq!(vec!["import b"], commands);
+    }
+    #[test]
+    fn history_diffs() {
+        let before = "{\n  \"events\": [\n    \"a\",\n    \"b\"\n  ]\n}";
+        let after = "{\n  \"events\": [\n    \"a\",\n    \"c\",\n    \"b\"\n  ]\n}";
+        let change = FileChange::new("uni.json".to_string(), Some(before), Some(after));
+        // only the inserted line is stored
+        assert_eq!(3, change.line);
+        assert!(change.before.as_ref().unwrap().lines.is_empty());
+        assert_eq!(vec!["    \"c\",\n"], change.after.as_ref().unwrap().lines);
+        assert_eq!(Ok(Some(before.to_string())), change.apply(Some(after), false));
+        assert_eq!(Ok(Some(after.to_string())), change.apply(Some(before), true));
+        // the file was changed by something else since
+        assert!(change.apply(Some(before), false).is_err());
+        assert!(change.apply(None, false).is_err());
+
+        let created = FileChange::new("work.json".to_string(), None, Some(after));
+        assert_eq!(Ok(None), created.apply(Some(after), false));
+        assert_eq!(Ok(Some(after.to_string())), created.apply(None, true));
+        assert!(created.apply(Some(after), true).is_err());
+    }
+    #[test]
+    fn changed_on_disk() {
+        let path = std::env::temp_dir().join(format!("calmar-test-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        write_atomic(path, "first", false).unwrap();
+        assert_eq!("first", read(path).unwrap());
+        write_atomic(path, "second", true).unwrap();
+
+        // another program writes to the file
+        std::fs::write(path, "third").unwrap();
+        assert!(matches!(
+            write_atomic(path, "fourth", true),
+            Err(CalmarError::ChangedOnDisk { .. })
+        ));
+        assert_eq!("third", read(path).unwrap());
+        write_atomic(path, "fourth", false).unwrap();
+        assert_eq!("fourth", read(path).unwrap());
+        std::fs::remove_file(path).unwrap();
+        let _ = std::fs::remove_file(
+            std::env::temp_dir().join(format!(".calmar-test-{}.json.lock", std::process::id())),
+        );
+    }
+    #[test]
+    fn unversioned_calendar() {
+        // written before deadlines existed
+        let mut document = serde_json::json!({
+            "name": "uni",
+            "events": [{
+                "name": "lecture",
+                "start": "2022-10-03T10:00:00+02:00",
+                "end": "2022-10-03T11:30:00+02:00",
+                "repeat": 604800,
+                "priority": 5,
+                "difficulty": 3
+            }]
+        });
+        assert_eq!(true, migrate(&mut document, FileKind::Calendar).unwrap());
+        assert_eq!(SCHEMA_VERSION as u64, document["version"].as_u64().unwrap());
+        let calendar: Calendar = serde_json::from_value(document.clone()).unwrap();
+        assert_eq!(1, calendar.events().len());
+        assert!(calendar.deadlines().is_empty());
+        assert!(calendar.events()[0].exceptions().is_empty());
+        assert_eq!(false, migrate(&mut document, FileKind::Calendar).unwrap());
+
+        let mut newer = serde_json::json!({ "version": SCHEMA_VERSION + 1, "calendars": [] });
+        assert!(matches!(
+            migrate(&mut newer, FileKind::Index),
+            Err(CalmarError::NewerSchema { .. })
+        ));
+    }
+    #[test]
+    fn migration_waits_for_save() {
+        let path = std::env::temp_dir().join(format!("calmar-test-{}-v1.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let event = serde_json::json!({
+            "name": "lecture",
+            "start": "2022-10-03T10:00:00+02:00",
+            "end": "2022-10-03T11:30:00+02:00",
+            "repeat": 604800,
+            "priority": 5,
+            "difficulty": 3,
+            "exceptions": []
+        });
+        let content = serde_json::json!({
+            "version": 1,
+            "name": "uni",
+            "events": [event, event],
+            "deadlines": []
+        })
+        .to_string();
+        std::fs::write(path, &content).unwrap();
+
+        // reading leaves the file alone, and the IDs stay the same until it is saved
+        let first: Calendar = load(path, &content, FileKind::Calendar).unwrap();
+        let second: Calendar = load(path, &content, FileKind::Calendar).unwrap();
+        assert_eq!(first.events()[0].id(), second.events()[0].id());
+        assert_ne!(first.events()[0].id(), first.events()[1].id());
+        assert_eq!(content, std::fs::read_to_string(path).unwrap());
+
+        let copy = format!("{path}.pre-migration");
+        assert!(!std::path::Path::new(&copy).exists());
+        keep_original(path).unwrap();
+        assert_eq!(content, std::fs::read_to_string(&copy).unwrap());
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(&copy).unwrap();
+    }
+    #[test]
+    fn ids() {
+        let first = new_id();
+        assert_eq!(16, first.len());
+        assert_ne!(first, new_id());
+        assert_eq!(&first[..6], short_id(&first));
+
+        let items = [
+            ("lecture".to_string(), "3fa9c1d200000000"),
+            ("lecture".to_string(), "3fb0000000000000"),
+            ("3fa9".to_string(), "ffff000000000000"),
+        ];
+        let find = |key| find_by_key(items.iter().map(|(n, i)| (n.clone(), *i)), key);
+        assert_eq!(vec![0, 1], find("lecture"));
+        // names come before IDs
+        assert_eq!(vec![2], find("3fa9"));
+        assert_eq!(vec![0], find("3fa9c"));
+        assert_eq!(vec![0], find("3FA9C1D2"));
+        // too short to be taken as an ID
+        assert!(find("3fa").is_empty());
+    }
+    #[test]
+    fn details() {
+        let tags = parse_tags(" math, exam,,math ");
+        assert_eq!(vec!["exam", "math"], tags.iter().collect::<Vec<&String>>());
+
+        assert!(validate_url("https://example.com/room?id=2"));
+        assert!(validate_url("mailto:someone@example.com"));
+        assert!(validate_url(""));
+        assert!(!validate_url("example.com"));
+        assert!(!validate_url("https://example.com/a room"));
+
+        let mut event = Event::new(
+            "lecture".to_string(),
+            Local.ymd(2022, 12, 19).and_hms(10, 0, 0),
+            Local.ymd(2022, 12, 19).and_hms(11, 30, 0),
+            Recurrence::none(),
+            5,
+            3,
+            vec![],
+        );
+        // details are stored next to the other fields and left out while unset
+        let json = serde_json::to_value(&event).unwrap();
+        assert!(json.get("location").is_none() && json.get("tags").is_none());
+
+        event.details_mut().set_location(" Room 101 ");
+        event.details_mut().set_tags(tags);
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!("Room 101", json["location"]);
+        assert_eq!(serde_json::json!(["exam", "math"]), json["tags"]);
+        assert_eq!(event, serde_json::from_value::<Event>(json).unwrap());
+
+        event.details_mut().set_location(" ");
+        assert_eq!(None, event.details().location());
+    }
+    #[test]
+    fn query() {
+        let mut event = Event::new(
+            "Linear Algebra exam".to_string(),
+            Local.ymd(2026, 11, 16).and_hms(9, 0, 0),
+            Local.ymd(2026, 11, 16).and_hms(11, 0, 0),
+            Recurrence::none(),
+            8,
+            9,
+            vec![],
+        );
+        event.details_mut().set_tags(parse_tags("Math"));
+        let matches = |terms: &[&str]| Query::parse(terms).unwrap().matches_event(&event);
+        assert!(matches(&["exam", "tag:math", "p>=7", "d=9", "is:once"]));
+        // regular expressions are case sensitive
+        assert!(!matches(&["name:/^linear .* exam$/"]));
+        assert!(matches(&["name:/^Linear .* exam$/"]));
+        assert!(!matches(&["p<8"]));
+        assert!(!matches(&["is:deadline"]));
+        assert!(!matches(&["tag:physics"]));
+
+        let deadline =
+            Deadline::new("essay".to_string(), Local.ymd(2026, 11, 30).and_hms(23, 59, 0), 5);
+        let matches = |terms: &[&str]| Query::parse(terms).unwrap().matches_deadline(&deadline);
+        assert!(matches(&["date:11/2026", "p=5"]));
+        assert!(matches(&["from:01/11/2026", "to:30/11/2026"]));
+        assert!(!matches(&["to:29/11/2026"]));
+        assert!(!matches(&["d=5"]));
+        assert!(!matches(&["is:recurring"]));
+
+        let (start, end) = Query::parse(&["date:12/2026"]).unwrap().range().unwrap();
+        assert_eq!(Local.ymd(2026, 12, 1).and_hms(0, 0, 0), start);
+        assert_eq!(Local.ymd(2027, 1, 1).and_hms(0, 0, 0), end);
+
+        assert!(Query::parse(&["priority>eleven"]).is_err());
+        assert!(Query::parse(&["tag>math"]).is_err());
+        assert!(Query::parse(&["colour:red"]).is_err());
+        assert!(Query::parse(&["name:/[/"]).is_err());
+    }
+    #[test]
+    fn deadline_status() {
+        let date = Local.ymd(2026, 11, 30).and_hms(23, 59, 0);
+        let mut deadline = Deadline::new("essay".to_string(), date, 5);
+        assert_eq!(DeadlineStatus::Open, deadline.status());
+        deadline.set_progress(Some(40));
+        assert_eq!(DeadlineStatus::InProgress, deadline.status());
+        assert_eq!("in progress (40%)", deadline.status_fmt());
+
+        let finished = Local.ymd(2026, 11, 29).and_hms(18, 0, 0);
+        deadline.finish(DeadlineStatus::Done, finished);
+        assert!(deadline.is_finished());
+        assert_eq!(Some(100), deadline.progress());
+        assert_eq!("done on 29/11/2026", deadline.status_fmt());
+
+        // the status survives an export to iCalendar
+        let mut calendar = Calendar::new("uni");
+        calendar.add_deadline(deadline.clone());
+        let import = from_ics(&to_ics(&calendar)).unwrap();
+        assert_eq!(DeadlineStatus::Done, import.deadlines[0].status());
+        assert_eq!(Some(finished), import.deadlines[0].completed());
+
+        deadline.reopen();
+        assert_eq!(DeadlineStatus::Open, deadline.status());
+        assert_eq!(None, deadline.completed());
+
+        // deadlines saved before statuses existed are open
+        let mut document = serde_json::json!({
+            "version": 3,
+            "name": "uni",
+            "events": [],
+            "deadlines": [{ "id": new_id(), "name": "essay", "date": date, "priority": 5 }]
+        });
+        migrate(&mut document, FileKind::Calendar).unwrap();
+        assert_eq!("open", document["deadlines"][0]["status"]);
+    }
+    #[test]
+    fn postpone_deadline() {
+        let date = Local.ymd(2026, 11, 30).and_hms(23, 59, 0);
+        let mut deadline = Deadline::new("essay".to_string(), date, 5);
+        deadline.postpone(Duration::days(2));
+        deadline.postpone(Duration::hours(12));
+        assert_eq!(date + Duration::hours(60), deadline.date());
+        assert_eq!(Some(date), deadline.original_date());
+        assert_eq!(2, deadline.postponements());
+
+        // never postponed deadlines are stored without the fields
+        let json = serde_json::to_value(Deadline::new("report".to_string(), date, 5)).unwrap();
+        assert!(json.get("original_date").is_none() && json.get("postponements").is_none());
+    }
+    #[test]
+    fn reminders() {
+        let offsets = parse_reminders("15m, 1d,").unwrap();
+        assert_eq!(vec![15, 1440], offsets.iter().copied().collect::<Vec<u32>>());
+        assert_eq!("15m, 1d", reminders_fmt(&offsets));
+        assert_eq!(Some(BTreeSet::new()), parse_reminders(" "));
+        assert_eq!(None, parse_reminders("15m, soon"));
+
+        let now = Local::now();
+        let mut calendar = Calendar::new("uni");
+        let mut event = |name: &str, start| {
+            let mut e = Event::new(
+                name.to_string(),
+                start,
+                start + Duration::hours(1),
+                Recurrence::none(),
+                5,
+                3,
+                vec![],
+            );
+            e.details_mut().set_reminders(offsets.clone());
+            calendar.add_event(e);
+        };
+        event("soon", now + Duration::minutes(10));
+        event("later", now + Duration::days(3));
+        event("started", now - Duration::minutes(5));
+        let mut essay = Deadline::new("essay".to_string(), now + Duration::hours(20), 5);
+        essay.details_mut().set_reminders(offsets.clone());
+        let mut report = essay.clone();
+        report.finish(DeadlineStatus::Done, now);
+        calendar.add_deadline(essay);
+        calendar.add_deadline(report);
+
+        // only the last reminder due by now of what is still ahead is given
+        let due = due_reminders(&[("uni".to_string(), calendar)], now);
+        assert_eq!(
+            vec![("essay", 1440), ("soon", 15)],
+            due.iter()
+                .map(|r| (r.name.as_str(), r.offset))
+                .collect::<Vec<(&str, u32)>>()
+        );
+    }
+    #[test]
+    fn hook_description() {
+        let before = serde_json::json!({
+            "version": SCHEMA_VERSION,
+            "events": [{ "id": "a1", "name": "lecture" }, { "id": "b2", "name": "lab" }],
+            "deadlines": [{ "id": "c3", "name": "essay" }],
+        });
+        let after = serde_json::json!({
+            "version": SCHEMA_VERSION,
+            "events": [{ "id": "b2", "name": "lab 2" }, { "id": "d4", "name": "exam" }],
+            "deadlines": [{ "id": "c3", "name": "essay" }],
+        });
+        let description = describe("/tmp/uni.json", &before, &after);
+        assert_eq!("/tmp/uni.json", description["path"]);
+        assert_eq!(
+            serde_json::json!({ "events": [{ "id": "d4", "name": "exam" }] }),
+            description["added"]
+        );
+        assert_eq!(
+            serde_json::json!({ "events": [{ "id": "a1", "name": "lecture" }] }),
+            description["removed"]
+        );
+        assert_eq!(
+            serde_json::json!({ "events": [{ "id": "b2", "name": "lab 2" }] }),
+            description["changed"]
+        );
+
+        // a new file has no items before it
+        let description = describe("/tmp/uni.json", &serde_json::Value::Null, &after);
+        assert_eq!(2, description["added"]["events"].as_array().unwrap().len());
+        assert_eq!(serde_json::json!({}), description["removed"]);
+    }
+    #[test]
+    fn relative_dates() {
+        // a Sunday
+        let today = NaiveDate::from_ymd(2026, 10, 18);
+        let date = |input| parse_relative_date(input, today);
+        assert_eq!(Some(today), date("today"));
+        assert_eq!(Some(NaiveDate::from_ymd(2026, 10, 19)), date("Tomorrow"));
+        assert_eq!(Some(NaiveDate::from_ymd(2026, 10, 19)), date("mon"));
+        assert_eq!(Some(NaiveDate::from_ymd(2026, 10, 23)), date("next friday"));
+        assert_eq!(Some(NaiveDate::from_ymd(2026, 10, 25)), date("sun"));
+        assert_eq!(Some(NaiveDate::from_ymd(2026, 10, 21)), date("+3d"));
+        assert_eq!(Some(NaiveDate::from_ymd(2026, 10, 11)), date("-1w"));
+        assert_eq!(Some(NaiveDate::from_ymd(2026, 11, 1)), date("in 2 weeks"));
+        assert_eq!(Some(NaiveDate::from_ymd(2027, 10, 18)), date("in 1 year"));
+        assert_eq!(Some(NaiveDate::from_ymd(2026, 10, 31)), date("end of month"));
+        assert_eq!(Some(NaiveDate::from_ymd(2026, 12, 31)), date("end of year"));
+        assert_eq!(None, date("someday"));
+        assert_eq!(None, date("in 2 fortnights"));
+        assert_eq!(None, date("21/12/2022"));
+
+        // months keep the day, or end on the last day of shorter months
+        let date = |input| parse_relative_date(input, NaiveDate::from_ymd(2026, 1, 31));
+        assert_eq!(Some(NaiveDate::from_ymd(2026, 2, 28)), date("+1m"));
+        assert_eq!(Some(NaiveDate::from_ymd(2025, 12, 31)), date("in -1 month"));
+
+        assert_eq!(true, validate_date("next tue"));
+        assert_eq!(false, validate_date("next"));
+    }
+    #[test]
+    fn clock_times() {
+        let time = |h, m| Some(NaiveTime::from_hms(h, m, 0));
+        assert_eq!(time(14, 30), parse_clock_time("14:30"));
+        assert_eq!(time(9, 5), parse_clock_time("9:05"));
+        assert_eq!(time(14, 0), parse_clock_time("14"));
+        assert_eq!(time(14, 0), parse_clock_time("2pm"));
+        assert_eq!(time(14, 30), parse_clock_time("2:30 PM"));
+        assert_eq!(time(0, 15), parse_clock_time("12:15am"));
+        assert_eq!(time(12, 0), parse_clock_time("noon"));
+        assert_eq!(time(0, 0), parse_clock_time("midnight"));
+        assert_eq!(None, parse_clock_time("13pm"));
+        assert_eq!(None, parse_clock_time("0am"));
+        assert_eq!(None, parse_clock_time("+9"));
+        assert_eq!(None, parse_clock_time("9:5"));
+        assert_eq!(true, validate_time("2pm"));
+    }
+    #[test]
+    fn date_and_time_formats() {
+        use crate::cli::{
+            getdata::{parse_formatted_date, parse_formatted_month},
+            util::{without_day, DATE_FORMATS, TIME_FORMATS},
+        };
+        let layout = |name| DATE_FORMATS.iter().find(|(f, _)| *f == name).unwrap().1;
+        let date = Some(NaiveDate::from_ymd(2026, 3, 7));
+        assert_eq!(date, parse_formatted_date("07/03/2026", layout("DD/MM/YYYY")));
+        assert_eq!(date, parse_formatted_date("03/07/2026", layout("MM/DD/YYYY")));
+        assert_eq!(date, parse_formatted_date("2026-03-07", layout("YYYY-MM-DD")));
+        assert_eq!(date, parse_formatted_date("07.03.2026", layout("DD.MM.YYYY")));
+        assert_eq!(date, parse_formatted_date("7.3.2026", layout("D.M.YYYY")));
+        assert_eq!(date, parse_formatted_date(" 07.03.26 ", layout("DD.MM.YY")));
+        assert_eq!(None, parse_formatted_date("2026-03-07", layout("DD/MM/YYYY")));
+        assert_eq!(None, parse_formatted_date("13/13/2026", layout("MM/DD/YYYY")));
+        assert_eq!(None, parse_formatted_date("2026-02-29", layout("YYYY-MM-DD")));
+
+        // anything listed can be typed back in
+        for (_, layout) in DATE_FORMATS {
+            let formatted = NaiveDate::from_ymd(2026, 3, 7).format(layout).to_string();
+            assert_eq!(date, parse_formatted_date(&formatted, layout));
+        }
+        // months are typed like dates without the day
+        for (name, layout) in DATE_FORMATS {
+            let formatted = NaiveDate::from_ymd(2026, 3, 1)
+                .format(&without_day(layout))
+                .to_string();
+            assert_eq!(
+                Some((2026, 3)),
+                parse_formatted_month(&formatted, &without_day(layout)),
+                "{name}"
+            );
+        }
+        assert_eq!("MM/YYYY", without_day("DD/MM/YYYY"));
+        assert_eq!("YYYY-MM", without_day("YYYY-MM-DD"));
+        assert_eq!("%-m.%Y", without_day("%-d.%-m.%Y"));
+        assert_eq!(Some((2026, 10)), parse_formatted_month("2026-10", "%Y-%m"));
+        assert_eq!(None, parse_formatted_month("10/2026", "%Y-%m"));
+        for (_, layout) in TIME_FORMATS {
+            for (h, m) in [(0, 0), (9, 5), (12, 0), (12, 30), (23, 59)] {
+                let time = NaiveTime::from_hms(h, m, 0);
+                let formatted = time.format(layout).to_string();
+                assert_eq!(Some(time), parse_clock_time(&formatted));
+            }
+        }
     }
 }